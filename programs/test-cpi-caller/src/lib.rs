@@ -0,0 +1,182 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use token_extensions::program::TokenExtensions;
+
+declare_id!("J22JpdmWAo9MKxHPr2motjVX8VyNvLykBpwjmiZoSWZf");
+
+// stands in for an escrow or vault program owning token accounts through its PDAs
+#[program]
+pub mod test_cpi_caller {
+    use super::*;
+
+    pub fn toggle_escrow_memo(ctx: Context<ToggleEscrowMemo>, enable: bool) -> Result<()> {
+        let bump = [ctx.bumps.escrow_authority];
+        let signer_seeds: &[&[u8]] = &[b"escrow", &bump];
+        let signer = &[signer_seeds];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_extensions_program.to_account_info(),
+            token_extensions::cpi::accounts::RequiredMemoTransfersCpi {
+                token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                owner: ctx.accounts.escrow_authority.to_account_info(),
+                account_registry: None,
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            signer,
+        );
+
+        if enable {
+            token_extensions::cpi::enable_required_memo_transfers_cpi(cpi_ctx)
+        } else {
+            token_extensions::cpi::disable_required_memo_transfers_cpi(cpi_ctx)
+        }
+    }
+
+    pub fn route_vault_fee_transfer(ctx: Context<RouteVaultFeeTransfer>, amount: u64) -> Result<()> {
+        let bump = [ctx.bumps.vault_authority];
+        let signer_seeds: &[&[u8]] = &[b"vault", &bump];
+        let signer = &[signer_seeds];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_extensions_program.to_account_info(),
+            token_extensions::cpi::accounts::TransferWithFee {
+                source: ctx.accounts.vault_token_account.to_account_info(),
+                destination: ctx.accounts.destination.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+                instructions: ctx.accounts.instructions.to_account_info(),
+                memo_program: ctx.accounts.memo_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            signer,
+        );
+
+        token_extensions::cpi::transfer_with_fee(cpi_ctx, amount, None)
+    }
+
+    // only here to prove create_locked_account refuses to run under CPI
+    pub fn nest_create_locked_account(ctx: Context<NestCreateLockedAccount>) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_extensions_program.to_account_info(),
+            token_extensions::cpi::accounts::CreateLockedAccount {
+                payer: ctx.accounts.payer.to_account_info(),
+                token_account: ctx.accounts.token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                owner: ctx.accounts.owner.to_account_info(),
+                instructions: ctx.accounts.instructions.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        );
+
+        token_extensions::cpi::create_locked_account(cpi_ctx)
+    }
+
+    // only here to prove create_account_with_cpi_guard refuses to run under CPI
+    pub fn nest_create_account_with_cpi_guard(ctx: Context<NestCreateAccountWithCpiGuard>) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_extensions_program.to_account_info(),
+            token_extensions::cpi::accounts::CreateAccountWithCpiGuard {
+                payer: ctx.accounts.payer.to_account_info(),
+                token_account: ctx.accounts.token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                owner: ctx.accounts.owner.to_account_info(),
+                instructions: ctx.accounts.instructions.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        );
+
+        token_extensions::cpi::create_account_with_cpi_guard(cpi_ctx)
+    }
+
+    // only here to prove batch_set_cpi_guard refuses to run under CPI
+    pub fn nest_batch_set_cpi_guard<'info>(
+        ctx: Context<'_, '_, 'info, 'info, NestBatchSetCpiGuard<'info>>,
+        enable: bool,
+    ) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_extensions_program.to_account_info(),
+            token_extensions::cpi::accounts::BatchSetCpiGuard {
+                owner: ctx.accounts.owner.to_account_info(),
+                instructions: ctx.accounts.instructions.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+
+        token_extensions::cpi::batch_set_cpi_guard(cpi_ctx, enable)
+    }
+}
+
+#[derive(Accounts)]
+pub struct ToggleEscrowMemo<'info> {
+    #[account(mut)]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: PDA owning the escrow token account, signs through invoke_signed
+    #[account(seeds = [b"escrow"], bump)]
+    pub escrow_authority: UncheckedAccount<'info>,
+    pub token_extensions_program: Program<'info, TokenExtensions>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RouteVaultFeeTransfer<'info> {
+    #[account(mut)]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: PDA owning the vault token account, signs through invoke_signed
+    #[account(seeds = [b"vault"], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: instructions sysvar, checked by the token extensions program
+    pub instructions: UncheckedAccount<'info>,
+    /// CHECK: spl memo program, checked by the token extensions program
+    pub memo_program: UncheckedAccount<'info>,
+    pub token_extensions_program: Program<'info, TokenExtensions>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct NestCreateLockedAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub token_account: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub owner: Signer<'info>,
+    /// CHECK: instructions sysvar, checked by the token extensions program
+    pub instructions: UncheckedAccount<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_extensions_program: Program<'info, TokenExtensions>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct NestCreateAccountWithCpiGuard<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub token_account: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub owner: Signer<'info>,
+    /// CHECK: instructions sysvar, checked by the token extensions program
+    pub instructions: UncheckedAccount<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_extensions_program: Program<'info, TokenExtensions>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct NestBatchSetCpiGuard<'info> {
+    pub owner: Signer<'info>,
+    /// CHECK: instructions sysvar, checked by the token extensions program
+    pub instructions: UncheckedAccount<'info>,
+    pub token_extensions_program: Program<'info, TokenExtensions>,
+    pub token_program: Program<'info, Token2022>,
+}
@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::instruction::{ExecuteInstruction, InitializeExtraAccountMetaListInstruction};
+
+declare_id!("FSCfsmuyNAbwkQZt5KjBBop1KbE7QJpQqjiyvQHg6qx3");
+
+// stands in for a third-party hook program owning its own extra account meta list
+#[program]
+pub mod test_transfer_hook {
+    use super::*;
+
+    // the list pda must be pre-funded, the interface instruction carries no payer
+    #[instruction(discriminator = InitializeExtraAccountMetaListInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+        extra_account_metas: Vec<ExtraAccountMetaData>,
+    ) -> Result<()> {
+        let extra_account_metas: Vec<ExtraAccountMeta> =
+            extra_account_metas.iter().map(ExtraAccountMeta::from).collect();
+        let space = ExtraAccountMetaList::size_of(extra_account_metas.len())?;
+
+        let mint_key = ctx.accounts.mint.key();
+        let bump = [ctx.bumps.extra_account_meta_list];
+        let signer_seeds: &[&[u8]] = &[b"extra-account-metas", mint_key.as_ref(), &bump];
+
+        anchor_lang::solana_program::program::invoke_signed(
+            &anchor_lang::solana_program::system_instruction::allocate(
+                &ctx.accounts.extra_account_meta_list.key(),
+                space as u64,
+            ),
+            &[ctx.accounts.extra_account_meta_list.to_account_info()],
+            &[signer_seeds],
+        )?;
+
+        anchor_lang::solana_program::program::invoke_signed(
+            &anchor_lang::solana_program::system_instruction::assign(
+                &ctx.accounts.extra_account_meta_list.key(),
+                &crate::ID,
+            ),
+            &[ctx.accounts.extra_account_meta_list.to_account_info()],
+            &[signer_seeds],
+        )?;
+
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &extra_account_metas,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn initialize_counter(ctx: Context<InitializeCounter>) -> Result<()> {
+        ctx.accounts.counter.mint = ctx.accounts.mint.key();
+
+        Ok(())
+    }
+
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn execute(ctx: Context<Execute>, amount: u64) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.count = counter.count.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        counter.volume = counter.volume.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+
+        Ok(())
+    }
+}
+
+/// Borsh layout of the pod `ExtraAccountMeta` carried by the interface instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ExtraAccountMetaData {
+    pub discriminator: u8,
+    pub address_config: [u8; 32],
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl From<&ExtraAccountMetaData> for ExtraAccountMeta {
+    fn from(meta: &ExtraAccountMetaData) -> Self {
+        ExtraAccountMeta {
+            discriminator: meta.discriminator,
+            address_config: meta.address_config,
+            is_signer: meta.is_signer.into(),
+            is_writable: meta.is_writable.into(),
+        }
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct Counter {
+    pub mint: Pubkey,
+    pub count: u64,
+    pub volume: u64,
+}
+
+impl Counter {
+    // structure: discriminator(8) + mint(32) + count(8) + volume(8)
+    pub const SIZE: usize = 8 + 32 + 8 + 8;
+}
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    /// CHECK: pre-funded ["extra-account-metas", mint] pda, allocated and assigned here
+    #[account(mut, seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    #[account(constraint = mint.mint_authority == COption::Some(authority.key()))]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCounter<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = payer,
+        space = Counter::SIZE,
+        seeds = [b"counter", mint.key().as_ref()],
+        bump,
+    )]
+    pub counter: Box<Account<'info, Counter>>,
+    pub system_program: Program<'info, System>,
+}
+
+// account order fixed by the transfer hook interface
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    #[account(token::mint = mint)]
+    pub source: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(token::mint = mint)]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: source owner or delegate, verified by the token program
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: this program's extra account meta list for the mint
+    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"counter", mint.key().as_ref()],
+        bump,
+    )]
+    pub counter: Box<Account<'info, Counter>>,
+}
@@ -54,4 +54,70 @@ pub enum TokenExtensionError {
     MemoRequiredForTransfer,
     #[msg("Invalid memo")]
     InvalidMemo,
+    #[msg("No valid source accounts provided")]
+    NoValidSourceAccounts,
+    #[msg("Token account does not belong to the mint")]
+    MintMismatch,
+    #[msg("Revoking this authority is permanent and must be confirmed")]
+    AuthorityRevocationNotConfirmed,
+    #[msg("Token account balance is not zero")]
+    AccountNotEmpty,
+    #[msg("Insufficient funds")]
+    InsufficientFunds,
+    #[msg("Destination is not the configured fee escrow")]
+    InvalidFeeEscrow,
+    #[msg("Registry account is required when registering the mint")]
+    RegistryAccountMissing,
+    #[msg("Confidential transfer balances are not empty")]
+    ConfidentialBalanceNotEmpty,
+    #[msg("Account is not frozen")]
+    AccountNotFrozen,
+    #[msg("A freeze authority is required for this mint")]
+    FreezeAuthorityRequired,
+    #[msg("CPI guard can only be set up from a top-level instruction")]
+    CpiGuardRequiresTopLevel,
+    #[msg("Next instruction must enable the CPI guard on the new account")]
+    CpiGuardEnableMissing,
+    #[msg("Batch size does not match the accounts or exceeds the limit")]
+    InvalidBatchSize,
+    #[msg("Batch account creation failed")]
+    BatchAccountCreationFailed,
+    #[msg("Recipient accounts are required to mint to the recipient")]
+    RecipientAccountMissing,
+    #[msg("Label is too long")]
+    LabelTooLong,
+    #[msg("Issuer registry is full")]
+    IssuerRegistryFull,
+    #[msg("Registry capacity cannot shrink")]
+    InvalidRegistryCapacity,
+    #[msg("Memo requirement does not match the expected status")]
+    MemoStatusMismatch,
+    #[msg("Effective time must be in the future")]
+    InvalidEffectiveTime,
+    #[msg("Pending rate change is not yet executable")]
+    RateChangeNotReady,
+    #[msg("CPI guard is disabled")]
+    CpiGuardDisabled,
+    #[msg("Instructions after the batch must toggle the CPI guard of each changed account in order")]
+    CpiGuardToggleMissing,
+    #[msg("Invalid extra account meta")]
+    InvalidExtraAccountMeta,
+    #[msg("Transfer hook can only be executed during a transfer")]
+    NotTransferring,
+    #[msg("Metadata account targeted by the metadata pointer was not supplied")]
+    MetadataAccountMissing,
+    #[msg("Metadata pointer does not target the Metaplex metadata account of the mint")]
+    MetadataPointerMismatch,
+    #[msg("Metadata is immutable")]
+    MetadataImmutable,
+    #[msg("Close config account is required when the close authority is a program PDA")]
+    CloseConfigMissing,
+    #[msg("Registry account was passed without registering the mint")]
+    UnexpectedRegistryAccount,
+    #[msg("Soulbound metadata account is required to store a label")]
+    SbtMetaAccountMissing,
+    #[msg("Close config account was passed without a PDA close authority")]
+    UnexpectedCloseConfig,
+    #[msg("Token account appears more than once in the batch")]
+    DuplicateBatchAccount,
 } 
\ No newline at end of file
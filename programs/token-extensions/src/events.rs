@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct TransferFeeUpdated {
+    pub mint: Pubkey,
+    pub old_transfer_fee_basis_points: u16,
+    pub old_maximum_fee: u64,
+    pub new_transfer_fee_basis_points: u16,
+    pub new_maximum_fee: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct WithheldTokensWithdrawn {
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub accounts_swept: u32,
+}
+
+#[event]
+pub struct TransferWithFeeEvent {
+    pub mint: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub net: u64,
+    pub epoch: u64,
+    pub pending_fee_change: bool,
+}
+
+#[event]
+pub struct MintCreated {
+    pub mint: Pubkey,
+    pub extension_types: Vec<u8>,
+}
+
+#[event]
+pub struct MintClosed {
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub lamports_reclaimed: u64,
+    pub close_authority: Pubkey,
+}
+
+#[event]
+pub struct AccountCreatedFrozen {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct AccountStateChanged {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub frozen: bool,
+}
+
+#[event]
+pub struct AccountCreated {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct RequiredMemoBatchUpdated {
+    pub owner: Pubkey,
+    pub enabled: bool,
+    pub changed: u32,
+    pub skipped: u32,
+}
+
+#[event]
+pub struct RateAuthorityChanged {
+    pub mint: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct RateUpdated {
+    pub mint: Pubkey,
+    pub old_rate: i16,
+    pub new_rate: i16,
+    pub timestamp: i64,
+    pub ui_amount_per_token_before: String,
+}
+
+#[event]
+pub struct Clawback {
+    pub mint: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct DelegatedBurnEvent {
+    pub mint: Pubkey,
+    pub source: Pubkey,
+    pub amount: u64,
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct CpiGuardReallocated {
+    pub token_account: Pubkey,
+    pub reallocated: bool,
+    pub already_enabled: bool,
+}
+
+#[event]
+pub struct CpiGuardBatchUpdated {
+    pub owner: Pubkey,
+    pub enabled: bool,
+    pub changed: u32,
+    pub skipped: u32,
+}
+
+#[event]
+pub struct HookRemoved {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct CollectionCreated {
+    pub mint: Pubkey,
+    pub update_authority: Pubkey,
+    pub max_size: u32,
+}
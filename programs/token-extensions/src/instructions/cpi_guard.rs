@@ -1,9 +1,13 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::sysvar::instructions::load_current_index_checked;
 use anchor_spl::token_2022::{self, Token2022};
-use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::{Mint, TokenAccount};
 use spl_token_2022::extension::{
     cpi_guard::CpiGuard,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
 
 pub fn enable_cpi_guard(ctx: Context<EnableCpiGuard>) -> Result<()> {
@@ -46,6 +50,260 @@ pub fn disable_cpi_guard(ctx: Context<DisableCpiGuard>) -> Result<()> {
     Ok(())
 }
 
+pub fn create_account_with_cpi_guard(ctx: Context<CreateAccountWithCpiGuard>) -> Result<()> {
+    let token_account = &ctx.accounts.token_account;
+    let mint = &ctx.accounts.mint;
+    let owner = &ctx.accounts.owner;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    crate::utils::require_cpi_guard_enable_follows(
+        &ctx.accounts.instructions.to_account_info(),
+        &token_program.key(),
+        &token_account.key(),
+        &owner.key(),
+    )?;
+
+    // space for token account with cpi guard extension
+    let space = ExtensionType::CpiGuard.try_calculate_account_len::<spl_token_2022::state::Account>(&[])?;
+
+    // token account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &token_account.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            token_account.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init token account
+    let init_account_ix = spl_token_2022::instruction::initialize_account3(
+        &token_program.key(),
+        &token_account.key(),
+        &mint.key(),
+        &owner.key(),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_account_ix,
+        &[
+            token_account.to_account_info(),
+            mint.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn reallocate_for_cpi_guard(ctx: Context<ReallocateForCpiGuard>) -> Result<()> {
+    let token_account = &ctx.accounts.token_account;
+    let owner = &ctx.accounts.owner;
+    let token_program = &ctx.accounts.token_program;
+
+    let (has_space, already_enabled) = {
+        let account_info = token_account.to_account_info();
+        let account_data = account_info.try_borrow_data()?;
+        let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+        match account_state.get_extension::<CpiGuard>() {
+            Ok(cpi_guard) => (true, bool::from(cpi_guard.lock_cpi)),
+            Err(_) => (false, false),
+        }
+    };
+
+    // the owner enables the guard in the next top-level instruction
+    if !already_enabled {
+        crate::utils::require_cpi_guard_enable_follows(
+            &ctx.accounts.instructions.to_account_info(),
+            &token_program.key(),
+            &token_account.key(),
+            &owner.key(),
+        )?;
+    }
+
+    if !has_space {
+        let reallocate_ix = spl_token_2022::instruction::reallocate(
+            &token_program.key(),
+            &token_account.key(),
+            &ctx.accounts.payer.key(),
+            &owner.key(),
+            &[],
+            &[ExtensionType::CpiGuard],
+        )?;
+
+        anchor_lang::solana_program::program::invoke(
+            &reallocate_ix,
+            &[
+                token_account.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                owner.to_account_info(),
+            ],
+        )?;
+    }
+
+    emit!(crate::events::CpiGuardReallocated {
+        token_account: token_account.key(),
+        reallocated: !has_space,
+        already_enabled,
+    });
+
+    Ok(())
+}
+
+// each changed account is toggled by the owner in the top-level instructions right after the
+// batch, in the order of remaining_accounts, e.g. [batch_set_cpi_guard, toggle(a), toggle(c)]
+// when b is already in place. capped so the sysvar walk stays cheap
+#[constant]
+pub const MAX_BATCH_CPI_GUARD_ACCOUNTS: u8 = 16;
+
+pub fn batch_set_cpi_guard<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchSetCpiGuard<'info>>,
+    enable: bool,
+) -> Result<()> {
+    if get_stack_height() != TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Err(crate::errors::TokenExtensionError::CpiGuardRequiresTopLevel.into());
+    }
+
+    if ctx.remaining_accounts.len() > MAX_BATCH_CPI_GUARD_ACCOUNTS as usize {
+        return Err(crate::errors::TokenExtensionError::InvalidBatchSize.into());
+    }
+
+    let instructions_sysvar = ctx.accounts.instructions.to_account_info();
+    let token_program_key = ctx.accounts.token_program.key();
+    let owner_key = ctx.accounts.owner.key();
+    let mut next_index = load_current_index_checked(&instructions_sysvar)? as usize + 1;
+    let mut changed: u32 = 0;
+    let mut skipped: u32 = 0;
+
+    for (index, token_account) in ctx.remaining_accounts.iter().enumerate() {
+        if token_account.owner != &spl_token_2022::id() {
+            return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+        }
+
+        // a repeated account would demand a second toggle and be counted twice
+        if ctx.remaining_accounts[..index].iter().any(|previous| previous.key == token_account.key) {
+            return Err(crate::errors::TokenExtensionError::DuplicateBatchAccount.into());
+        }
+
+        let guard_enabled = {
+            let account_data = token_account.try_borrow_data()?;
+            let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+            // any account of another owner fails the whole batch
+            if account_state.base.owner != owner_key {
+                return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+            }
+            match account_state.get_extension::<CpiGuard>() {
+                Ok(cpi_guard) => bool::from(cpi_guard.lock_cpi),
+                Err(_) => false,
+            }
+        };
+
+        if guard_enabled == enable {
+            skipped += 1;
+            continue;
+        }
+
+        if !crate::utils::cpi_guard_toggle_at(
+            &instructions_sysvar,
+            next_index,
+            enable,
+            &token_program_key,
+            &token_account.key(),
+            &owner_key,
+        )? {
+            msg!("missing cpi guard toggle for {} at instruction {}", token_account.key(), next_index);
+            return Err(crate::errors::TokenExtensionError::CpiGuardToggleMissing.into());
+        }
+
+        next_index += 1;
+        changed += 1;
+    }
+
+    emit!(crate::events::CpiGuardBatchUpdated {
+        owner: owner_key,
+        enabled: enable,
+        changed,
+        skipped,
+    });
+
+    Ok(())
+}
+
+pub fn get_cpi_guard_status(ctx: Context<CpiGuardStatus>) -> Result<bool> {
+    cpi_guard_enabled(&ctx.accounts.token_account.to_account_info())
+}
+
+pub fn assert_cpi_guard(ctx: Context<CpiGuardStatus>, expected: bool) -> Result<()> {
+    let cpi_guard_enabled = cpi_guard_enabled(&ctx.accounts.token_account.to_account_info())?;
+
+    match (expected, cpi_guard_enabled) {
+        (true, false) => Err(crate::errors::TokenExtensionError::CpiGuardDisabled.into()),
+        (false, true) => Err(crate::errors::TokenExtensionError::CpiGuardEnabled.into()),
+        _ => Ok(()),
+    }
+}
+
+// accounts without the extension count as unguarded
+fn cpi_guard_enabled(account_info: &AccountInfo) -> Result<bool> {
+    let account_data = account_info.try_borrow_data()?;
+    let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+    match account_state.get_extension::<CpiGuard>() {
+        Ok(cpi_guard) => Ok(bool::from(cpi_guard.lock_cpi)),
+        Err(_) => Ok(false),
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateAccountWithCpiGuard<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub token_account: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // signs the cpi guard enable instruction that follows
+    pub owner: Signer<'info>,
+    /// CHECK: instructions sysvar, checked by address
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct ReallocateForCpiGuard<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub owner: Signer<'info>,
+    /// CHECK: instructions sysvar, checked by address
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct BatchSetCpiGuard<'info> {
+    pub owner: Signer<'info>,
+    /// CHECK: instructions sysvar, checked by address
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
 #[derive(Accounts)]
 pub struct EnableCpiGuard<'info> {
     #[account(mut)]
@@ -60,4 +318,9 @@ pub struct DisableCpiGuard<'info> {
     pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     pub owner: Signer<'info>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct CpiGuardStatus<'info> {
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+}
@@ -1,14 +1,37 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token_2022::{self, Token2022};
-use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::state::{AllowlistConfig, AllowlistEntry, StateHistory};
 use spl_token_2022::extension::{
     default_account_state::DefaultAccountState,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
 
+/// State every new token account of the mint starts in.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultState {
+    /// Accounts are usable immediately.
+    Initialized,
+    /// Accounts start frozen until the freeze authority thaws them.
+    Frozen,
+}
+
+impl From<DefaultState> for spl_token_2022::state::AccountState {
+    fn from(state: DefaultState) -> Self {
+        match state {
+            DefaultState::Initialized => spl_token_2022::state::AccountState::Initialized,
+            DefaultState::Frozen => spl_token_2022::state::AccountState::Frozen,
+        }
+    }
+}
+
 pub fn create_mint_with_default_state(
     ctx: Context<CreateMintWithDefaultState>,
-    default_state: u8,
+    default_state: DefaultState,
+    freeze_authority: Option<Pubkey>,
     decimals: u8,
 ) -> Result<()> {
     let mint = &ctx.accounts.mint;
@@ -16,6 +39,12 @@ pub fn create_mint_with_default_state(
     let rent = &ctx.accounts.rent;
     let system_program = &ctx.accounts.system_program;
     let token_program = &ctx.accounts.token_program;
+
+    // without a freeze authority default-frozen accounts could never be thawed
+    let freeze_authority = match freeze_authority {
+        Some(freeze_authority) => freeze_authority,
+        None => return Err(crate::errors::TokenExtensionError::FreezeAuthorityRequired.into()),
+    };
     
     // space for mint with the default account state extension
     let space = ExtensionType::DefaultAccountState.try_calculate_account_len::<spl_token_2022::state::Mint>(&[])?;
@@ -39,12 +68,7 @@ pub fn create_mint_with_default_state(
     )?;
     
     // init default account state extension
-    let account_state = match default_state {
-        0 => spl_token_2022::state::AccountState::Uninitialized,
-        1 => spl_token_2022::state::AccountState::Initialized,
-        2 => spl_token_2022::state::AccountState::Frozen,
-        _ => return Err(crate::errors::TokenExtensionError::InvalidDefaultAccountState.into()),
-    };
+    let account_state: spl_token_2022::state::AccountState = default_state.into();
     
     let init_default_state_ix = spl_token_2022::instruction::initialize_default_account_state(
         &token_program.key(),
@@ -64,7 +88,7 @@ pub fn create_mint_with_default_state(
         &token_program.key(),
         &mint.key(),
         &mint_authority.key(),
-        Some(&mint_authority.key()),
+        Some(&freeze_authority),
         decimals,
     )?;
     
@@ -81,14 +105,23 @@ pub fn create_mint_with_default_state(
 
 pub fn update_default_account_state(
     ctx: Context<UpdateDefaultAccountState>,
-    default_state: u8,
+    default_state: DefaultState,
 ) -> Result<()> {
-    let account_state = match default_state {
-        0 => spl_token_2022::state::AccountState::Uninitialized,
-        1 => spl_token_2022::state::AccountState::Initialized,
-        2 => spl_token_2022::state::AccountState::Frozen,
-        _ => return Err(crate::errors::TokenExtensionError::InvalidDefaultAccountState.into()),
+    if ctx.accounts.mint.freeze_authority != COption::Some(ctx.accounts.freeze_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let old_state = {
+        let mint_info = ctx.accounts.mint.to_account_info();
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        match mint_state.get_extension::<DefaultAccountState>() {
+            Ok(default_account_state) => default_account_state.state,
+            Err(_) => return Err(crate::errors::TokenExtensionError::InvalidExtensionType.into()),
+        }
     };
+
+    let account_state: spl_token_2022::state::AccountState = default_state.into();
     
     let update_default_state_ix = spl_token_2022::instruction::update_default_account_state(
         &ctx.accounts.token_program.key(),
@@ -106,10 +139,376 @@ pub fn update_default_account_state(
         ],
         &[],
     )?;
+
+    if let Some(state_history) = ctx.accounts.state_history.as_mut() {
+        state_history.record(Clock::get()?.unix_timestamp, old_state, account_state as u8);
+    }
     
     Ok(())
 }
 
+pub fn init_state_history(ctx: Context<InitStateHistory>) -> Result<()> {
+    let state_history = &mut ctx.accounts.state_history;
+    state_history.mint = ctx.accounts.mint.key();
+    state_history.entries = Vec::new();
+
+    Ok(())
+}
+
+pub fn close_state_history(_ctx: Context<CloseStateHistory>) -> Result<()> {
+    // the history account is closed by anchor to the freeze authority
+    Ok(())
+}
+
+pub fn thaw_account(ctx: Context<ThawAccount>) -> Result<()> {
+    if !ctx.accounts.token_account.is_frozen() {
+        return Err(crate::errors::TokenExtensionError::AccountNotFrozen.into());
+    }
+
+    if ctx.accounts.mint.freeze_authority != COption::Some(ctx.accounts.freeze_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let thaw_account_ix = spl_token_2022::instruction::thaw_account(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.token_account.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.freeze_authority.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &thaw_account_ix,
+        &[
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.freeze_authority.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn freeze_account(ctx: Context<FreezeAccount>) -> Result<()> {
+    if ctx.accounts.token_account.is_frozen() {
+        return Err(crate::errors::TokenExtensionError::AccountFrozen.into());
+    }
+
+    if ctx.accounts.mint.freeze_authority != COption::Some(ctx.accounts.freeze_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let freeze_account_ix = spl_token_2022::instruction::freeze_account(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.token_account.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.freeze_authority.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &freeze_account_ix,
+        &[
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.freeze_authority.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn batch_set_account_state<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchSetAccountState<'info>>,
+    freeze: bool,
+) -> Result<u32> {
+    if ctx.accounts.mint.freeze_authority != COption::Some(ctx.accounts.freeze_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let mint_key = ctx.accounts.mint.key();
+    let mut processed: u32 = 0;
+
+    for token_account in ctx.remaining_accounts.iter() {
+        // any foreign account fails the whole batch
+        if token_account.owner != &spl_token_2022::id() {
+            return Err(crate::errors::TokenExtensionError::MintMismatch.into());
+        }
+
+        let is_frozen = {
+            let account_data = token_account.try_borrow_data()?;
+            let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)
+                .map_err(|_| crate::errors::TokenExtensionError::MintMismatch)?;
+            if account_state.base.mint != mint_key {
+                return Err(crate::errors::TokenExtensionError::MintMismatch.into());
+            }
+            account_state.base.is_frozen()
+        };
+
+        // already in the target state
+        if is_frozen == freeze {
+            continue;
+        }
+
+        let set_state_ix = if freeze {
+            spl_token_2022::instruction::freeze_account(
+                &ctx.accounts.token_program.key(),
+                &token_account.key(),
+                &mint_key,
+                &ctx.accounts.freeze_authority.key(),
+                &[],
+            )?
+        } else {
+            spl_token_2022::instruction::thaw_account(
+                &ctx.accounts.token_program.key(),
+                &token_account.key(),
+                &mint_key,
+                &ctx.accounts.freeze_authority.key(),
+                &[],
+            )?
+        };
+
+        anchor_lang::solana_program::program::invoke(
+            &set_state_ix,
+            &[
+                token_account.clone(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.freeze_authority.to_account_info(),
+            ],
+        )?;
+
+        emit!(crate::events::AccountStateChanged {
+            mint: mint_key,
+            token_account: token_account.key(),
+            frozen: freeze,
+        });
+
+        processed += 1;
+    }
+
+    Ok(processed)
+}
+
+pub fn create_allowlisted_mint(
+    ctx: Context<CreateAllowlistedMint>,
+    list_authority: Pubkey,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    // program PDA as freeze authority so thawing follows the allowlist
+    let freeze_authority = freeze_authority_address(&mint.key());
+
+    // space for mint with the default account state extension
+    let space = ExtensionType::DefaultAccountState.try_calculate_account_len::<spl_token_2022::state::Mint>(&[])?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // every new account starts frozen
+    let init_default_state_ix = spl_token_2022::instruction::initialize_default_account_state(
+        &token_program.key(),
+        &mint.key(),
+        &spl_token_2022::state::AccountState::Frozen,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_default_state_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        Some(&freeze_authority),
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    let allowlist_config = &mut ctx.accounts.allowlist_config;
+    allowlist_config.mint = mint.key();
+    allowlist_config.list_authority = list_authority;
+
+    Ok(())
+}
+
+pub fn add_to_allowlist(ctx: Context<AddToAllowlist>, owner: Pubkey) -> Result<()> {
+    let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+    allowlist_entry.mint = ctx.accounts.mint.key();
+    allowlist_entry.owner = owner;
+    allowlist_entry.added_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+pub fn remove_from_allowlist(_ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+    // the entry is closed by anchor, accounts already thawed stay thawed
+    Ok(())
+}
+
+pub fn thaw_if_allowlisted(ctx: Context<ThawIfAllowlisted>) -> Result<()> {
+    if !ctx.accounts.token_account.is_frozen() {
+        return Err(crate::errors::TokenExtensionError::AccountNotFrozen.into());
+    }
+
+    let thaw_account_ix = spl_token_2022::instruction::thaw_account(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.token_account.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.freeze_authority.key(),
+        &[],
+    )?;
+
+    let mint_key = ctx.accounts.mint.key();
+    let bump = [ctx.bumps.freeze_authority];
+    let signer_seeds: &[&[u8]] = &[b"freeze_authority", mint_key.as_ref(), &bump];
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &thaw_account_ix,
+        &[
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.freeze_authority.to_account_info(),
+        ],
+        &[signer_seeds],
+    )?;
+
+    Ok(())
+}
+
+pub fn create_account_for_default_state_mint(
+    ctx: Context<CreateAccountForDefaultStateMint>,
+) -> Result<()> {
+    let token_account = &ctx.accounts.token_account;
+    let mint = &ctx.accounts.mint;
+    let owner = &ctx.accounts.owner;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    let default_state_frozen = {
+        let mint_info = mint.to_account_info();
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        let default_account_state = mint_state.get_extension::<DefaultAccountState>()?;
+        default_account_state.state == spl_token_2022::state::AccountState::Frozen as u8
+    };
+
+    // space for token account with immutable owner and required memo transfers extensions
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&[
+        ExtensionType::ImmutableOwner,
+        ExtensionType::MemoTransfer,
+    ])?;
+
+    // token account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &token_account.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            token_account.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init immutable owner extension
+    let init_immutable_owner_ix = spl_token_2022::instruction::initialize_immutable_owner(
+        &token_program.key(),
+        &token_account.key(),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_immutable_owner_ix,
+        &[
+            token_account.to_account_info(),
+        ],
+    )?;
+
+    // init token account
+    let init_account_ix = spl_token_2022::instruction::initialize_account3(
+        &token_program.key(),
+        &token_account.key(),
+        &mint.key(),
+        &owner.key(),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_account_ix,
+        &[
+            token_account.to_account_info(),
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // enable required memo transfers
+    let enable_memo_ix = spl_token_2022::instruction::enable_required_transfer_memos(
+        &token_program.key(),
+        &token_account.key(),
+        &owner.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &enable_memo_ix,
+        &[
+            token_account.to_account_info(),
+            owner.to_account_info(),
+        ],
+    )?;
+
+    // the client has to request a thaw before the account is usable
+    if default_state_frozen {
+        emit!(crate::events::AccountCreatedFrozen {
+            mint: mint.key(),
+            token_account: token_account.key(),
+            owner: owner.key(),
+        });
+    }
+
+    Ok(())
+}
+
+pub fn freeze_authority_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"freeze_authority", mint.as_ref()], &crate::ID).0
+}
+
 #[derive(Accounts)]
 pub struct CreateMintWithDefaultState<'info> {
     #[account(mut)]
@@ -128,5 +527,174 @@ pub struct UpdateDefaultAccountState<'info> {
     #[account(mut)]
     pub mint: Box<InterfaceAccount<'info, Mint>>,
     pub freeze_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"state_history", mint.key().as_ref()],
+        bump,
+    )]
+    pub state_history: Option<Box<Account<'info, StateHistory>>>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct InitStateHistory<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = payer,
+        space = StateHistory::SIZE,
+        seeds = [b"state_history", mint.key().as_ref()],
+        bump,
+    )]
+    pub state_history: Box<Account<'info, StateHistory>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStateHistory<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = mint.freeze_authority == COption::Some(freeze_authority.key()) @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub freeze_authority: Signer<'info>,
+    #[account(
+        mut,
+        close = freeze_authority,
+        seeds = [b"state_history", mint.key().as_ref()],
+        bump,
+    )]
+    pub state_history: Box<Account<'info, StateHistory>>,
+}
+
+#[derive(Accounts)]
+pub struct ThawAccount<'info> {
+    #[account(
+        mut,
+        constraint = token_account.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub freeze_authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeAccount<'info> {
+    #[account(
+        mut,
+        constraint = token_account.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub freeze_authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateAccountForDefaultStateMint<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub token_account: AccountInfo<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    // signs once to enable required memo transfers
+    pub owner: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct BatchSetAccountState<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub freeze_authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateAllowlistedMint<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = AllowlistConfig::SIZE,
+        seeds = [b"allowlist_config", mint.key().as_ref()],
+        bump,
+    )]
+    pub allowlist_config: Box<Account<'info, AllowlistConfig>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct AddToAllowlist<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub list_authority: Signer<'info>,
+    #[account(
+        seeds = [b"allowlist_config", mint.key().as_ref()],
+        bump,
+        constraint = allowlist_config.list_authority == list_authority.key() @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub allowlist_config: Box<Account<'info, AllowlistConfig>>,
+    #[account(
+        init,
+        payer = payer,
+        space = AllowlistEntry::SIZE,
+        seeds = [b"allowlist", mint.key().as_ref(), owner.as_ref()],
+        bump,
+    )]
+    pub allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromAllowlist<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub list_authority: Signer<'info>,
+    #[account(
+        seeds = [b"allowlist_config", mint.key().as_ref()],
+        bump,
+        constraint = allowlist_config.list_authority == list_authority.key() @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub allowlist_config: Box<Account<'info, AllowlistConfig>>,
+    #[account(
+        mut,
+        close = list_authority,
+        seeds = [b"allowlist", mint.key().as_ref(), allowlist_entry.owner.as_ref()],
+        bump,
+    )]
+    pub allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+}
+
+#[derive(Accounts)]
+pub struct ThawIfAllowlisted<'info> {
+    #[account(
+        mut,
+        constraint = token_account.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [b"allowlist", mint.key().as_ref(), token_account.owner.as_ref()],
+        bump,
+    )]
+    pub allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    /// CHECK: program PDA set as the mint freeze authority
+    #[account(seeds = [b"freeze_authority", mint.key().as_ref()], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
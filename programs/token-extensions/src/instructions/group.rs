@@ -1,10 +1,14 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_2022::{self, Token2022};
 use anchor_spl::token_interface::Mint;
+use crate::state::GroupAccount;
 use spl_token_2022::extension::{
     group_pointer::GroupPointer,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
+use spl_token_group_interface::state::TokenGroup;
 
 pub fn create_mint_with_group(
     ctx: Context<CreateMintWithGroup>,
@@ -94,6 +98,156 @@ pub fn create_mint_with_group(
     Ok(())
 }
 
+pub fn create_collection(
+    ctx: Context<CreateCollection>,
+    name: String,
+    symbol: String,
+    uri: String,
+    max_size: u32,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    crate::utils::validate_token_metadata(&name, &symbol, &uri)?;
+
+    // space for the pointers, the group and metadata initializes realloc for the rest
+    let extensions = [ExtensionType::GroupPointer, ExtensionType::MetadataPointer];
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)?;
+    let group_space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+        ExtensionType::GroupPointer,
+        ExtensionType::MetadataPointer,
+        ExtensionType::TokenGroup,
+    ])?;
+
+    // fund the group and metadata up front
+    let token_metadata = spl_token_metadata_interface::state::TokenMetadata {
+        update_authority: Some(mint_authority.key()).try_into()?,
+        mint: mint.key(),
+        name: name.clone(),
+        symbol: symbol.clone(),
+        uri: uri.clone(),
+        additional_metadata: vec![],
+    };
+    let metadata_space = token_metadata.tlv_size_of()?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(group_space + metadata_space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // group pointer extension (pointing to the mint itself)
+    let init_group_pointer_ix = spl_token_2022::instruction::initialize_group_pointer(
+        &token_program.key(),
+        &mint.key(),
+        Some(&mint_authority.key()),
+        Some(mint.key()),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_group_pointer_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // metadata pointer extension (pointing to the mint itself)
+    let init_metadata_pointer_ix = spl_token_2022::instruction::initialize_metadata_pointer(
+        &token_program.key(),
+        &mint.key(),
+        Some(&mint_authority.key()),
+        Some(mint.key()),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_pointer_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint, a collection has no decimals
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        0,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    // init token metadata
+    let init_metadata_ix = spl_token_metadata_interface::instruction::initialize(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        name,
+        symbol,
+        uri,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_ix,
+        &[
+            mint.to_account_info(),
+            mint_authority.to_account_info(),
+        ],
+    )?;
+
+    // init token group
+    let init_group_ix = spl_token_group_interface::instruction::initialize_group(
+        &token_program.key(),
+        &mint.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        Some(mint_authority.key()),
+        max_size as u64,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &init_group_ix,
+        &[
+            mint.to_account_info(),
+            mint_authority.to_account_info(),
+        ],
+    )?;
+
+    let token_group = token_group(&mint.to_account_info())?;
+    ctx.accounts.group_account.mirror(&token_group)?;
+
+    emit!(crate::events::CollectionCreated {
+        mint: mint.key(),
+        update_authority: mint_authority.key(),
+        max_size,
+    });
+
+    Ok(())
+}
+
 pub fn update_group_max_size(ctx: Context<UpdateGroupMaxSize>, max_size: u32) -> Result<()> {
     let update_group_ix = spl_token_group_interface::instruction::update_group_max_size(
         &ctx.accounts.token_program.key(),
@@ -114,6 +268,50 @@ pub fn update_group_max_size(ctx: Context<UpdateGroupMaxSize>, max_size: u32) ->
     Ok(())
 }
 
+pub fn set_group_update_authority(
+    ctx: Context<SetGroupUpdateAuthority>,
+    new_authority: Option<Pubkey>,
+    confirm_renounce: bool,
+) -> Result<()> {
+    // without an update authority the group max size is fixed for good
+    if new_authority.is_none() && !confirm_renounce {
+        return Err(crate::errors::TokenExtensionError::AuthorityRevocationNotConfirmed.into());
+    }
+
+    let token_group = token_group(&ctx.accounts.mint.to_account_info())?;
+    let update_authority: Option<Pubkey> = token_group.update_authority.into();
+    if update_authority != Some(ctx.accounts.update_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let update_group_authority_ix = spl_token_group_interface::instruction::update_group_authority(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.update_authority.key(),
+        new_authority,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &update_group_authority_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.update_authority.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub(crate) fn token_group(mint_info: &AccountInfo) -> Result<TokenGroup> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let token_group = mint_state
+        .get_extension::<TokenGroup>()
+        .map_err(|_| crate::errors::TokenExtensionError::InvalidGroupConfig)?;
+
+    Ok(*token_group)
+}
+
 #[derive(Accounts)]
 pub struct CreateMintWithGroup<'info> {
     #[account(mut)]
@@ -128,10 +326,39 @@ pub struct CreateMintWithGroup<'info> {
     pub token_program: Program<'info, Token2022>,
 }
 
+#[derive(Accounts)]
+pub struct CreateCollection<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub mint: Signer<'info>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = GroupAccount::SIZE,
+        seeds = [b"group", mint.key().as_ref()],
+        bump,
+    )]
+    pub group_account: Box<Account<'info, GroupAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateGroupMaxSize<'info> {
     #[account(mut)]
     pub mint: Box<InterfaceAccount<'info, Mint>>,
     pub update_authority: Signer<'info>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct SetGroupUpdateAuthority<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub update_authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
@@ -1,9 +1,14 @@
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_2022::{self, Token2022};
 use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::state::{AccountRegistry, Sponsor};
 use spl_token_2022::extension::{
     immutable_owner::ImmutableOwner,
+    memo_transfer::RequiredMemoTransfers,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
 
 pub fn create_account_with_immutable_owner(
@@ -71,6 +76,447 @@ pub fn create_account_with_immutable_owner(
     Ok(())
 }
 
+#[constant]
+pub const MAX_BATCH_ACCOUNTS: u8 = 8;
+
+pub fn create_accounts_with_immutable_owner_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateAccountsWithImmutableOwnerBatch<'info>>,
+    owners: Vec<Pubkey>,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    if owners.len() != ctx.remaining_accounts.len() || owners.len() > MAX_BATCH_ACCOUNTS as usize {
+        return Err(crate::errors::TokenExtensionError::InvalidBatchSize.into());
+    }
+
+    // space for token account with immutable owner extension
+    let space = ExtensionType::ImmutableOwner.try_calculate_account_len::<spl_token_2022::state::Account>(&[])?;
+
+    for (index, (token_account, owner)) in ctx.remaining_accounts.iter().zip(owners.iter()).enumerate() {
+        if !token_account.is_signer || !token_account.is_writable || token_account.lamports() > 0 {
+            msg!("batch account creation failed at index {}", index);
+            return Err(crate::errors::TokenExtensionError::BatchAccountCreationFailed.into());
+        }
+
+        // token account
+        let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+            &ctx.accounts.payer.key(),
+            &token_account.key(),
+            rent.minimum_balance(space),
+            space as u64,
+            &token_program.key(),
+        );
+
+        anchor_lang::solana_program::program::invoke(
+            &create_account_ix,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                token_account.clone(),
+                system_program.to_account_info(),
+            ],
+        )?;
+
+        // init immutable owner extension
+        let init_immutable_owner_ix = spl_token_2022::instruction::initialize_immutable_owner(
+            &token_program.key(),
+            &token_account.key(),
+        )?;
+
+        anchor_lang::solana_program::program::invoke(
+            &init_immutable_owner_ix,
+            &[
+                token_account.clone(),
+            ],
+        )?;
+
+        // init token account
+        let init_account_ix = spl_token_2022::instruction::initialize_account3(
+            &token_program.key(),
+            &token_account.key(),
+            &mint.key(),
+            owner,
+        )?;
+
+        anchor_lang::solana_program::program::invoke(
+            &init_account_ix,
+            &[
+                token_account.clone(),
+                mint.to_account_info(),
+            ],
+        )?;
+
+        emit!(crate::events::AccountCreated {
+            mint: mint.key(),
+            token_account: token_account.key(),
+            owner: *owner,
+        });
+    }
+
+    Ok(())
+}
+
+pub fn create_account_with_immutable_owner_sponsored(
+    ctx: Context<CreateAccountWithImmutableOwnerSponsored>,
+) -> Result<()> {
+    let token_account = &ctx.accounts.token_account;
+    let mint = &ctx.accounts.mint;
+    let owner = &ctx.accounts.owner;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    // space for token account with immutable owner extension
+    let space = ExtensionType::ImmutableOwner.try_calculate_account_len::<spl_token_2022::state::Account>(&[])?;
+
+    // token account, rent paid by the sponsor PDA out of its balance above its own rent
+    let lamports = rent.minimum_balance(space);
+    let available = ctx.accounts.sponsor.get_lamports().saturating_sub(rent.minimum_balance(Sponsor::SIZE));
+    if available < lamports {
+        return Err(crate::errors::TokenExtensionError::InsufficientFunds.into());
+    }
+
+    // the sponsor holds data, so move its lamports directly instead of through create_account
+    ctx.accounts.sponsor.sub_lamports(lamports)?;
+    token_account.add_lamports(lamports)?;
+
+    let allocate_ix = anchor_lang::solana_program::system_instruction::allocate(
+        &token_account.key(),
+        space as u64,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &allocate_ix,
+        &[
+            token_account.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    let assign_ix = anchor_lang::solana_program::system_instruction::assign(
+        &token_account.key(),
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &assign_ix,
+        &[
+            token_account.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init immutable owner extension
+    let init_immutable_owner_ix = spl_token_2022::instruction::initialize_immutable_owner(
+        &token_program.key(),
+        &token_account.key(),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_immutable_owner_ix,
+        &[
+            token_account.to_account_info(),
+        ],
+    )?;
+
+    // init token account
+    let init_account_ix = spl_token_2022::instruction::initialize_account3(
+        &token_program.key(),
+        &token_account.key(),
+        &mint.key(),
+        &owner.key(),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_account_ix,
+        &[
+            token_account.to_account_info(),
+            mint.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn fund_sponsor(ctx: Context<FundSponsor>, amount: u64) -> Result<()> {
+    // each funder gets its own sponsor pda per owner, so nobody can claim another relayer's
+    let sponsor = &mut ctx.accounts.sponsor;
+    sponsor.owner = ctx.accounts.owner.key();
+    sponsor.funder = ctx.accounts.funder.key();
+
+    let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+        &ctx.accounts.funder.key(),
+        &ctx.accounts.sponsor.key(),
+        amount,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &transfer_ix,
+        &[
+            ctx.accounts.funder.to_account_info(),
+            ctx.accounts.sponsor.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn defund_sponsor(ctx: Context<DefundSponsor>, amount: u64) -> Result<()> {
+    // the sponsor keeps its own rent so the funder record survives
+    let rent = Rent::get()?;
+    let available = ctx.accounts.sponsor.get_lamports().saturating_sub(rent.minimum_balance(Sponsor::SIZE));
+    if available < amount {
+        return Err(crate::errors::TokenExtensionError::InsufficientFunds.into());
+    }
+
+    ctx.accounts.sponsor.sub_lamports(amount)?;
+    ctx.accounts.destination.add_lamports(amount)?;
+
+    Ok(())
+}
+
+pub fn create_locked_account(ctx: Context<CreateLockedAccount>) -> Result<()> {
+    let token_account = &ctx.accounts.token_account;
+    let mint = &ctx.accounts.mint;
+    let owner = &ctx.accounts.owner;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    crate::utils::require_cpi_guard_enable_follows(
+        &ctx.accounts.instructions.to_account_info(),
+        &token_program.key(),
+        &token_account.key(),
+        &owner.key(),
+    )?;
+
+    // space for token account with immutable owner and cpi guard extensions
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&[
+        ExtensionType::ImmutableOwner,
+        ExtensionType::CpiGuard,
+    ])?;
+
+    // token account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &token_account.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            token_account.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init immutable owner extension
+    let init_immutable_owner_ix = spl_token_2022::instruction::initialize_immutable_owner(
+        &token_program.key(),
+        &token_account.key(),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_immutable_owner_ix,
+        &[
+            token_account.to_account_info(),
+        ],
+    )?;
+
+    // init token account
+    let init_account_ix = spl_token_2022::instruction::initialize_account3(
+        &token_program.key(),
+        &token_account.key(),
+        &mint.key(),
+        &owner.key(),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_account_ix,
+        &[
+            token_account.to_account_info(),
+            mint.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn create_exchange_deposit_account(ctx: Context<CreateExchangeDepositAccount>) -> Result<()> {
+    let token_account = &ctx.accounts.token_account;
+    let mint = &ctx.accounts.mint;
+    let owner = &ctx.accounts.owner;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    // space for token account with immutable owner and required memo transfers extensions
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&[
+        ExtensionType::ImmutableOwner,
+        ExtensionType::MemoTransfer,
+    ])?;
+
+    // token account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &token_account.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            token_account.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init immutable owner extension
+    let init_immutable_owner_ix = spl_token_2022::instruction::initialize_immutable_owner(
+        &token_program.key(),
+        &token_account.key(),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_immutable_owner_ix,
+        &[
+            token_account.to_account_info(),
+        ],
+    )?;
+
+    // init token account
+    let init_account_ix = spl_token_2022::instruction::initialize_account3(
+        &token_program.key(),
+        &token_account.key(),
+        &mint.key(),
+        &owner.key(),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_account_ix,
+        &[
+            token_account.to_account_info(),
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // deposits without a memo are rejected so every credit is attributable
+    let enable_memo_ix = spl_token_2022::instruction::enable_required_transfer_memos(
+        &token_program.key(),
+        &token_account.key(),
+        &owner.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &enable_memo_ix,
+        &[
+            token_account.to_account_info(),
+            owner.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn create_ata_with_extensions(ctx: Context<CreateAtaWithExtensions>) -> Result<Pubkey> {
+    let token_program = &ctx.accounts.token_program;
+
+    // token-2022 associated token accounts always carry the immutable owner extension
+    let create_ata_ix = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+        &ctx.accounts.payer.key(),
+        &ctx.accounts.owner.key(),
+        &ctx.accounts.mint.key(),
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_ata_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            token_program.to_account_info(),
+            ctx.accounts.associated_token_program.to_account_info(),
+        ],
+    )?;
+
+    Ok(ctx.accounts.token_account.key())
+}
+
+pub fn verify_account_extensions(
+    ctx: Context<VerifyAccountExtensions>,
+    expected: Vec<u8>,
+) -> Result<()> {
+    let (actual, memo_required_incoming): (Vec<u8>, bool) = {
+        let account_info = ctx.accounts.token_account.to_account_info();
+        let account_data = account_info.try_borrow_data()?;
+        let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+        let memo_required_incoming = match account_state.get_extension::<RequiredMemoTransfers>() {
+            Ok(memo_transfer) => bool::from(memo_transfer.require_incoming_transfer_memos),
+            Err(_) => false,
+        };
+        let actual = account_state
+            .get_extension_types()?
+            .into_iter()
+            .map(|ext| ext as u8)
+            .collect();
+        (actual, memo_required_incoming)
+    };
+
+    if let Some(missing) = expected.iter().find(|ext| !actual.contains(ext)) {
+        msg!("extension mismatch: expected {} is missing", missing);
+        return Err(crate::errors::TokenExtensionError::InvalidExtensionType.into());
+    }
+
+    if let Some(unexpected) = actual.iter().find(|ext| !expected.contains(ext)) {
+        msg!("extension mismatch: found unexpected {}", unexpected);
+        return Err(crate::errors::TokenExtensionError::InvalidExtensionType.into());
+    }
+
+    if let Some(account_registry) = ctx.accounts.account_registry.as_mut() {
+        if actual.len() > AccountRegistry::MAX_EXTENSIONS {
+            return Err(crate::errors::TokenExtensionError::InvalidExtensionType.into());
+        }
+        account_registry.token_account = ctx.accounts.token_account.key();
+        account_registry.mint = ctx.accounts.token_account.mint;
+        account_registry.extension_types = actual;
+        account_registry.verified_at = Clock::get()?.unix_timestamp;
+        account_registry.memo_required_incoming = memo_required_incoming;
+    }
+
+    Ok(())
+}
+
+pub fn is_owner_immutable(ctx: Context<IsOwnerImmutable>) -> Result<bool> {
+    let token_account = &ctx.accounts.token_account;
+
+    // legacy spl-token accounts have no extensions, so the owner can always be reassigned
+    let owner_immutable = if token_account.owner == &spl_token::id() {
+        false
+    } else if token_account.owner == &spl_token_2022::id() {
+        let account_data = token_account.try_borrow_data()?;
+        let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+        account_state.get_extension::<ImmutableOwner>().is_ok()
+    } else {
+        return Err(ProgramError::IncorrectProgramId.into());
+    };
+
+    Ok(owner_immutable)
+}
+
 #[derive(Accounts)]
 pub struct CreateAccountWithImmutableOwner<'info> {
     #[account(mut)]
@@ -86,4 +532,154 @@ pub struct CreateAccountWithImmutableOwner<'info> {
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct CreateAccountsWithImmutableOwnerBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateAccountWithImmutableOwnerSponsored<'info> {
+    #[account(
+        mut,
+        seeds = [b"sponsor", relayer.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = sponsor.funder == relayer.key() @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub sponsor: Box<Account<'info, Sponsor>>,
+
+    // relayer that funded the sponsor PDA
+    pub relayer: Signer<'info>,
+    #[account(mut)]
+
+    // account initialized by the token program
+    pub token_account: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // account that will own the token account
+    pub owner: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct FundSponsor<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = Sponsor::SIZE,
+        seeds = [b"sponsor", funder.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub sponsor: Box<Account<'info, Sponsor>>,
+    /// CHECK: owner whose account creation is sponsored
+    pub owner: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DefundSponsor<'info> {
+    #[account(
+        mut,
+        seeds = [b"sponsor", funder.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = sponsor.funder == funder.key() @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub sponsor: Box<Account<'info, Sponsor>>,
+    pub funder: Signer<'info>,
+    /// CHECK: owner whose account creation is sponsored
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: any account receiving the lamports
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLockedAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+
+    // account initialized by the token program
+    pub token_account: AccountInfo<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // signs the cpi guard enable instruction that follows
+    pub owner: Signer<'info>,
+    /// CHECK: instructions sysvar, checked by address
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateExchangeDepositAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+
+    // account initialized by the token program
+    pub token_account: AccountInfo<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // signs to enable required memo transfers
+    pub owner: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateAtaWithExtensions<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: associated token account of owner, created by the associated token program if missing
+    #[account(
+        mut,
+        address = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &owner.key(),
+            &mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub token_account: UncheckedAccount<'info>,
+    /// CHECK: wallet that will own the associated token account
+    pub owner: UncheckedAccount<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyAccountExtensions<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = AccountRegistry::SIZE,
+        seeds = [b"account_registry", token_account.key().as_ref()],
+        bump,
+    )]
+    pub account_registry: Option<Box<Account<'info, AccountRegistry>>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct IsOwnerImmutable<'info> {
+    /// CHECK: token account of either token program, checked in the handler
+    pub token_account: UncheckedAccount<'info>,
+}
@@ -1,11 +1,22 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{get_return_data, set_return_data};
 use anchor_spl::token_2022::{self, Token2022};
+use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token_interface::Mint;
+use crate::state::{InterestConfigView, PendingRate, RateLimits, TokenExtensionMint};
 use spl_token_2022::extension::{
     interest_bearing_mint::InterestBearingConfig,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct FeeAuthorities {
+    pub transfer_fee_config_authority: Option<Pubkey>,
+    pub withdraw_withheld_authority: Option<Pubkey>,
+}
+
 pub fn create_interest_bearing_mint(
     ctx: Context<CreateInterestBearingMint>,
     rate_authority: Option<Pubkey>,
@@ -74,7 +85,256 @@ pub fn create_interest_bearing_mint(
     Ok(())
 }
 
+pub fn create_interest_bearing_mint_with_metadata(
+    ctx: Context<CreateInterestBearingMintWithMetadata>,
+    rate_authority: Option<Pubkey>,
+    rate: i16,
+    name: String,
+    symbol: String,
+    uri: String,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    // space for the fixed-size extensions, the metadata initialize reallocs for the rest
+    let extensions = [ExtensionType::InterestBearingConfig, ExtensionType::MetadataPointer];
+    crate::utils::extensions_compatible(&extensions)?;
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)?;
+
+    crate::utils::validate_token_metadata(&name, &symbol, &uri)?;
+
+    // fund the metadata up front
+    let token_metadata = spl_token_metadata_interface::state::TokenMetadata {
+        update_authority: Some(mint_authority.key()).try_into()?,
+        mint: mint.key(),
+        name: name.clone(),
+        symbol: symbol.clone(),
+        uri: uri.clone(),
+        additional_metadata: vec![],
+    };
+    let metadata_space = token_metadata.tlv_size_of()?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space + metadata_space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init interest bearing config extension
+    let init_interest_bearing_ix = spl_token_2022::instruction::initialize_interest_bearing_mint(
+        &token_program.key(),
+        &mint.key(),
+        rate_authority.as_ref(),
+        rate,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_interest_bearing_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // init metadata pointer extension (pointing to the mint itself)
+    let init_metadata_pointer_ix = spl_token_2022::instruction::initialize_metadata_pointer(
+        &token_program.key(),
+        &mint.key(),
+        Some(&mint_authority.key()),
+        Some(mint.key()),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_pointer_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    // init token metadata
+    let init_metadata_ix = spl_token_metadata_interface::instruction::initialize(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        name,
+        symbol,
+        uri,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_ix,
+        &[
+            mint.to_account_info(),
+            mint_authority.to_account_info(),
+        ],
+    )?;
+
+    ctx.accounts.registry.record(
+        mint.key(),
+        mint_authority.key(),
+        &[
+            ExtensionType::InterestBearingConfig,
+            ExtensionType::MetadataPointer,
+            ExtensionType::TokenMetadata,
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn create_yield_mint_with_fee(
+    ctx: Context<CreateYieldMintWithFee>,
+    rate: i16,
+    rate_authority: Option<Pubkey>,
+    fee_bps: u16,
+    max_fee: u64,
+    fee_authorities: FeeAuthorities,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    if fee_bps > crate::instructions::transfer_fee::MAX_FEE_BASIS_POINTS {
+        return Err(crate::errors::TokenExtensionError::InvalidTransferFeeConfig.into());
+    }
+
+    // space for mint with interest bearing and transfer fee extensions
+    let extensions = [ExtensionType::InterestBearingConfig, ExtensionType::TransferFeeConfig];
+    crate::utils::extensions_compatible(&extensions)?;
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init interest bearing config extension
+    let init_interest_bearing_ix = spl_token_2022::instruction::initialize_interest_bearing_mint(
+        &token_program.key(),
+        &mint.key(),
+        rate_authority.as_ref(),
+        rate,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_interest_bearing_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // transfer fee config extension
+    let init_transfer_fee_ix = spl_token_2022::instruction::initialize_transfer_fee_config(
+        &token_program.key(),
+        &mint.key(),
+        fee_authorities.transfer_fee_config_authority.as_ref(),
+        fee_authorities.withdraw_withheld_authority.as_ref(),
+        fee_bps,
+        max_fee,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_transfer_fee_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    ctx.accounts.registry.record(mint.key(), mint_authority.key(), &extensions)?;
+
+    Ok(())
+}
+
 pub fn update_interest_rate(ctx: Context<UpdateInterestRate>, rate: i16) -> Result<()> {
+    let interest_config = interest_bearing_config(&ctx.accounts.mint.to_account_info())?;
+    let current_rate = i16::from(interest_config.current_rate);
+
+    // the limits pda is always passed so a caller cannot skip it
+    let rate_limits_info = ctx.accounts.rate_limits.to_account_info();
+    if rate_limits_info.data_is_empty() {
+        crate::utils::validate_interest_rate(current_rate, rate, None)?;
+    } else {
+        let rate_limits = RateLimits::try_deserialize(&mut &rate_limits_info.try_borrow_data()?[..])?;
+        crate::utils::validate_interest_rate(current_rate, rate, Some(&rate_limits))?;
+    }
+
+    // value of one whole token right before the new rate takes effect
+    let decimals = ctx.accounts.mint.decimals;
+    let timestamp = Clock::get()?.unix_timestamp;
+    let one_token = 10u64
+        .checked_pow(decimals as u32)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let ui_amount_per_token_before = interest_config
+        .amount_to_ui_amount(one_token, decimals, timestamp)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
     let update_rate_ix = spl_token_2022::instruction::update_rate_interest_bearing_mint(
         &ctx.accounts.token_program.key(),
         &ctx.accounts.mint.key(),
@@ -91,10 +351,291 @@ pub fn update_interest_rate(ctx: Context<UpdateInterestRate>, rate: i16) -> Resu
         ],
         &[],
     )?;
-    
+
+    let updated_config = interest_bearing_config(&ctx.accounts.mint.to_account_info())?;
+
+    emit!(crate::events::RateUpdated {
+        mint: ctx.accounts.mint.key(),
+        old_rate: current_rate,
+        new_rate: i16::from(updated_config.current_rate),
+        timestamp,
+        ui_amount_per_token_before,
+    });
+
     Ok(())
 }
 
+pub fn configure_rate_limits(
+    ctx: Context<ConfigureRateLimits>,
+    min_rate: i16,
+    max_rate: i16,
+    max_change_per_update: u16,
+) -> Result<()> {
+    if min_rate == i16::MIN || min_rate > max_rate {
+        return Err(crate::errors::TokenExtensionError::InvalidInterestRate.into());
+    }
+
+    let interest_config = interest_bearing_config(&ctx.accounts.mint.to_account_info())?;
+    let rate_authority: Option<Pubkey> = interest_config.rate_authority.into();
+    if rate_authority != Some(ctx.accounts.rate_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let rate_limits = &mut ctx.accounts.rate_limits;
+    rate_limits.mint = ctx.accounts.mint.key();
+    rate_limits.min_rate = min_rate;
+    rate_limits.max_rate = max_rate;
+    rate_limits.max_change_per_update = max_change_per_update;
+
+    Ok(())
+}
+
+pub fn set_rate_authority(
+    ctx: Context<SetRateAuthority>,
+    new_authority: Option<Pubkey>,
+    confirm_renounce: bool,
+) -> Result<()> {
+    // without a rate authority the current rate is fixed for good
+    if new_authority.is_none() && !confirm_renounce {
+        return Err(crate::errors::TokenExtensionError::AuthorityRevocationNotConfirmed.into());
+    }
+
+    let interest_config = interest_bearing_config(&ctx.accounts.mint.to_account_info())?;
+    let rate_authority: Option<Pubkey> = interest_config.rate_authority.into();
+    if rate_authority != Some(ctx.accounts.rate_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let set_authority_ix = spl_token_2022::instruction::set_authority(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        new_authority.as_ref(),
+        spl_token_2022::instruction::AuthorityType::InterestRate,
+        &ctx.accounts.rate_authority.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &set_authority_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.rate_authority.to_account_info(),
+        ],
+    )?;
+
+    emit!(crate::events::RateAuthorityChanged {
+        mint: ctx.accounts.mint.key(),
+        old_authority: ctx.accounts.rate_authority.key(),
+        new_authority,
+    });
+
+    Ok(())
+}
+
+pub fn create_interest_bearing_mint_with_timelock(
+    ctx: Context<CreateInterestBearingMintWithTimelock>,
+    rate: i16,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    crate::utils::validate_interest_rate(rate, rate, None)?;
+
+    // program PDA as rate authority so changes only go through the timelock
+    let rate_authority = rate_authority_address(&mint.key());
+
+    // space for mint with interest bearing extension
+    let space = ExtensionType::InterestBearingConfig.try_calculate_account_len::<spl_token_2022::state::Mint>(&[])?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init interest bearing config extension
+    let init_interest_bearing_ix = spl_token_2022::instruction::initialize_interest_bearing_mint(
+        &token_program.key(),
+        &mint.key(),
+        Some(&rate_authority),
+        rate,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_interest_bearing_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn propose_interest_rate(
+    ctx: Context<ProposeInterestRate>,
+    rate: i16,
+    effective_after: i64,
+) -> Result<()> {
+    if effective_after <= Clock::get()?.unix_timestamp {
+        return Err(crate::errors::TokenExtensionError::InvalidEffectiveTime.into());
+    }
+
+    let interest_config = interest_bearing_config(&ctx.accounts.mint.to_account_info())?;
+    let rate_authority: Option<Pubkey> = interest_config.rate_authority.into();
+    if rate_authority != Some(rate_authority_address(&ctx.accounts.mint.key())) {
+        return Err(crate::errors::TokenExtensionError::InterestRateUpdateNotAllowed.into());
+    }
+    crate::utils::validate_interest_rate(i16::from(interest_config.current_rate), rate, None)?;
+
+    let pending_rate = &mut ctx.accounts.pending_rate;
+    pending_rate.mint = ctx.accounts.mint.key();
+    pending_rate.proposer = ctx.accounts.proposer.key();
+    pending_rate.rate = rate;
+    pending_rate.effective_after = effective_after;
+
+    Ok(())
+}
+
+pub fn execute_interest_rate(ctx: Context<ExecuteInterestRate>) -> Result<()> {
+    if Clock::get()?.unix_timestamp < ctx.accounts.pending_rate.effective_after {
+        return Err(crate::errors::TokenExtensionError::RateChangeNotReady.into());
+    }
+
+    let update_rate_ix = spl_token_2022::instruction::update_rate_interest_bearing_mint(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.rate_authority.key(),
+        &[],
+        ctx.accounts.pending_rate.rate,
+    )?;
+
+    let mint_key = ctx.accounts.mint.key();
+    let bump = [ctx.bumps.rate_authority];
+    let signer_seeds: &[&[u8]] = &[b"rate_authority", mint_key.as_ref(), &bump];
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &update_rate_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.rate_authority.to_account_info(),
+        ],
+        &[signer_seeds],
+    )?;
+
+    Ok(())
+}
+
+pub fn cancel_interest_rate(_ctx: Context<CancelInterestRate>) -> Result<()> {
+    // the pending change is closed back to the proposer by the account constraints
+    Ok(())
+}
+
+pub fn get_interest_config(ctx: Context<GetInterestConfig>) -> Result<InterestConfigView> {
+    let interest_config = interest_bearing_config(&ctx.accounts.mint.to_account_info())?;
+
+    Ok(InterestConfigView {
+        current_rate: i16::from(interest_config.current_rate),
+        pre_update_average_rate: i16::from(interest_config.pre_update_average_rate),
+        initialization_timestamp: i64::from(interest_config.initialization_timestamp),
+        last_update_timestamp: i64::from(interest_config.last_update_timestamp),
+        rate_authority: interest_config.rate_authority.into(),
+    })
+}
+
+pub fn amount_to_ui_amount(ctx: Context<ConvertInterestAmount>, amount: u64) -> Result<()> {
+    // the token program applies the accrued interest, including past rate changes
+    let amount_to_ui_amount_ix = spl_token_2022::instruction::amount_to_ui_amount(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        amount,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &amount_to_ui_amount_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+        ],
+    )?;
+
+    forward_token_program_return_data(&ctx.accounts.token_program.key())
+}
+
+pub fn ui_amount_to_amount(ctx: Context<ConvertInterestAmount>, ui_amount: String) -> Result<()> {
+    let ui_amount_to_amount_ix = spl_token_2022::instruction::ui_amount_to_amount(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ui_amount,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &ui_amount_to_amount_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+        ],
+    )?;
+
+    forward_token_program_return_data(&ctx.accounts.token_program.key())
+}
+
+// re-set the conversion result so it is reported under this program's id
+fn forward_token_program_return_data(token_program: &Pubkey) -> Result<()> {
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *token_program => {
+            set_return_data(&data);
+            Ok(())
+        }
+        _ => Err(crate::errors::TokenExtensionError::InvalidExtensionType.into()),
+    }
+}
+
+pub fn rate_authority_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"rate_authority", mint.as_ref()], &crate::ID).0
+}
+
+fn interest_bearing_config(mint_info: &AccountInfo) -> Result<InterestBearingConfig> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)
+        .map_err(|_| crate::errors::TokenExtensionError::InvalidExtensionType)?;
+    let interest_config = mint_state
+        .get_extension::<InterestBearingConfig>()
+        .map_err(|_| crate::errors::TokenExtensionError::InvalidExtensionType)?;
+
+    Ok(*interest_config)
+}
+
 #[derive(Accounts)]
 pub struct CreateInterestBearingMint<'info> {
     #[account(mut)]
@@ -109,10 +650,161 @@ pub struct CreateInterestBearingMint<'info> {
     pub token_program: Program<'info, Token2022>,
 }
 
+#[derive(Accounts)]
+pub struct CreateInterestBearingMintWithMetadata<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TokenExtensionMint::SIZE,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+    )]
+    pub registry: Box<Account<'info, TokenExtensionMint>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateYieldMintWithFee<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TokenExtensionMint::SIZE,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+    )]
+    pub registry: Box<Account<'info, TokenExtensionMint>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateInterestRate<'info> {
     #[account(mut)]
     pub mint: Box<InterfaceAccount<'info, Mint>>,
     pub rate_authority: Signer<'info>,
+    /// CHECK: rate limits pda for the mint, read only when it has been configured
+    #[account(
+        seeds = [b"rate_limits", mint.key().as_ref()],
+        bump,
+    )]
+    pub rate_limits: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRateLimits<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub rate_authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = RateLimits::SIZE,
+        seeds = [b"rate_limits", mint.key().as_ref()],
+        bump,
+    )]
+    pub rate_limits: Box<Account<'info, RateLimits>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateInterestBearingMintWithTimelock<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeInterestRate<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(
+        constraint = mint.mint_authority == COption::Some(proposer.key()) @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = proposer,
+        space = PendingRate::SIZE,
+        seeds = [b"pending_rate", mint.key().as_ref()],
+        bump,
+    )]
+    pub pending_rate: Box<Account<'info, PendingRate>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteInterestRate<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"pending_rate", mint.key().as_ref()],
+        bump,
+    )]
+    pub pending_rate: Box<Account<'info, PendingRate>>,
+    /// CHECK: receives the pending rate rent, must match the stored proposer
+    #[account(mut, address = pending_rate.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    /// CHECK: program PDA set as the mint rate authority
+    #[account(seeds = [b"rate_authority", mint.key().as_ref()], bump)]
+    pub rate_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CancelInterestRate<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"pending_rate", mint.key().as_ref()],
+        bump,
+        constraint = pending_rate.proposer == proposer.key() @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub pending_rate: Box<Account<'info, PendingRate>>,
+}
+
+#[derive(Accounts)]
+pub struct SetRateAuthority<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub rate_authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct GetInterestConfig<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+#[derive(Accounts)]
+pub struct ConvertInterestAmount<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Program<'info, Token2022>,
+}
@@ -1,14 +1,17 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_2022::{self, Token2022};
 use anchor_spl::token_interface::Mint;
+use crate::state::{GroupAccount, MemberAccount};
 use spl_token_2022::extension::{
     group_member_pointer::GroupMemberPointer,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
+use spl_token_group_interface::state::TokenGroupMember;
 
 pub fn create_mint_with_member(
     ctx: Context<CreateMintWithMember>,
-    group: Pubkey,
     decimals: u8,
 ) -> Result<()> {
     let mint = &ctx.accounts.mint;
@@ -16,6 +19,20 @@ pub fn create_mint_with_member(
     let rent = &ctx.accounts.rent;
     let system_program = &ctx.accounts.system_program;
     let token_program = &ctx.accounts.token_program;
+
+    // the group has to live on group_mint itself, a plain mint fails here
+    let group_mint_info = ctx.accounts.group_mint.to_account_info();
+    let group_pointer = crate::instructions::group_pointer::group_pointer(&group_mint_info)
+        .map_err(|_| crate::errors::TokenExtensionError::InvalidGroupConfig)?;
+    if Option::<Pubkey>::from(group_pointer.group_address) != Some(group_mint_info.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidGroupConfig.into());
+    }
+
+    // the token program rejects a full group too, but without saying why
+    let token_group = crate::instructions::group::token_group(&group_mint_info)?;
+    if u64::from(token_group.size) >= u64::from(token_group.max_size) {
+        return Err(crate::errors::TokenExtensionError::GroupSizeLimitExceeded.into());
+    }
     
     // space for mint with group member pointer and token group member extensions
     let extensions = vec![ExtensionType::GroupMemberPointer, ExtensionType::TokenGroupMember];
@@ -79,6 +96,7 @@ pub fn create_mint_with_member(
         &token_program.key(),
         &mint.key(),
         &mint.key(),
+        &mint_authority.key(),
         &ctx.accounts.group_mint.key(),
         &ctx.accounts.group_update_authority.key(),
     );
@@ -87,14 +105,37 @@ pub fn create_mint_with_member(
         &init_member_ix,
         &[
             mint.to_account_info(),
+            mint_authority.to_account_info(),
             ctx.accounts.group_mint.to_account_info(),
             ctx.accounts.group_update_authority.to_account_info(),
         ],
     )?;
+
+    // mirror the sizes and member number the token program just wrote
+    let token_group = crate::instructions::group::token_group(&group_mint_info)?;
+    ctx.accounts.group_account.mirror(&token_group)?;
+    let member_pointer = crate::instructions::member_pointer::member_pointer(&mint.to_account_info())?;
+    let token_group_member = token_group_member(&mint.to_account_info())?;
+    if Option::<Pubkey>::from(member_pointer.member_address) != Some(mint.key())
+        || token_group_member.group != group_mint_info.key()
+    {
+        return Err(crate::errors::TokenExtensionError::InvalidMemberConfig.into());
+    }
+    ctx.accounts.member_account.mirror(&token_group_member)?;
     
     Ok(())
 }
 
+fn token_group_member(mint_info: &AccountInfo) -> Result<TokenGroupMember> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let token_group_member = mint_state
+        .get_extension::<TokenGroupMember>()
+        .map_err(|_| crate::errors::TokenExtensionError::InvalidMemberConfig)?;
+
+    Ok(*token_group_member)
+}
+
 #[derive(Accounts)]
 pub struct CreateMintWithMember<'info> {
     #[account(mut)]
@@ -104,8 +145,25 @@ pub struct CreateMintWithMember<'info> {
     // account initialized by the token program
     pub mint: AccountInfo<'info>,
     pub mint_authority: Signer<'info>,
+    #[account(mut)]
     pub group_mint: Box<InterfaceAccount<'info, Mint>>,
     pub group_update_authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = GroupAccount::SIZE,
+        seeds = [b"group", group_mint.key().as_ref()],
+        bump,
+    )]
+    pub group_account: Box<Account<'info, GroupAccount>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MemberAccount::SIZE,
+        seeds = [b"member", mint.key().as_ref()],
+        bump,
+    )]
+    pub member_account: Box<Account<'info, MemberAccount>>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token2022>,
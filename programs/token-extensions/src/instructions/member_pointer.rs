@@ -3,7 +3,9 @@ use anchor_spl::token_2022::{self, Token2022};
 use anchor_spl::token_interface::Mint;
 use spl_token_2022::extension::{
     group_member_pointer::GroupMemberPointer,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
 
 pub fn create_mint_with_member_pointer(
@@ -74,6 +76,45 @@ pub fn create_mint_with_member_pointer(
     Ok(())
 }
 
+pub fn update_member_pointer(
+    ctx: Context<UpdateMemberPointer>,
+    new_member_address: Option<Pubkey>,
+) -> Result<()> {
+    let member_pointer = member_pointer(&ctx.accounts.mint.to_account_info())?;
+    let pointer_authority: Option<Pubkey> = member_pointer.authority.into();
+    if pointer_authority != Some(ctx.accounts.authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let update_member_pointer_ix = spl_token_2022::extension::group_member_pointer::instruction::update(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.authority.key(),
+        &[],
+        new_member_address,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &update_member_pointer_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub(crate) fn member_pointer(mint_info: &AccountInfo) -> Result<GroupMemberPointer> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let member_pointer = mint_state
+        .get_extension::<GroupMemberPointer>()
+        .map_err(|_| crate::errors::TokenExtensionError::InvalidExtensionType)?;
+
+    Ok(*member_pointer)
+}
+
 #[derive(Accounts)]
 pub struct CreateMintWithMemberPointer<'info> {
     #[account(mut)]
@@ -86,4 +127,12 @@ pub struct CreateMintWithMemberPointer<'info> {
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct UpdateMemberPointer<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
@@ -1,10 +1,17 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{get_return_data, set_return_data};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_2022::{self, Token2022};
 use anchor_spl::token_interface::Mint;
+use crate::state::{MetadataAccount, TokenExtensionMint};
 use spl_token_2022::extension::{
     metadata_pointer::MetadataPointer,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
+use spl_token_metadata_interface::state::{Field, TokenMetadata};
 
 pub fn create_mint_with_metadata(
     ctx: Context<CreateMintWithMetadata>,
@@ -12,25 +19,43 @@ pub fn create_mint_with_metadata(
     symbol: String,
     uri: String,
     decimals: u8,
+    register: bool,
+    immutable: bool,
 ) -> Result<()> {
     let mint = &ctx.accounts.mint;
     let mint_authority = &ctx.accounts.mint_authority;
     let rent = &ctx.accounts.rent;
     let system_program = &ctx.accounts.system_program;
     let token_program = &ctx.accounts.token_program;
+
+    if register && ctx.accounts.registry.is_none() {
+        return Err(crate::errors::TokenExtensionError::RegistryAccountMissing.into());
+    }
+    // a registry passed without register would be created but never recorded
+    if !register && ctx.accounts.registry.is_some() {
+        return Err(crate::errors::TokenExtensionError::UnexpectedRegistryAccount.into());
+    }
+    crate::utils::validate_token_metadata(&name, &symbol, &uri)?;
     
-    // space for mint with metadata pointer and token metadata extensions
-    let extensions = vec![ExtensionType::MetadataPointer, ExtensionType::TokenMetadata];
-    let space = extensions.iter().try_fold(
-        spl_token_2022::state::Mint::LEN,
-        |acc, &ext| ext.try_add_account_len(acc)
-    )?;
+    // space for the metadata pointer, the metadata initialize reallocs for the rest
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::MetadataPointer])?;
+
+    // fund the exact metadata size up front
+    let mut token_metadata = spl_token_metadata_interface::state::TokenMetadata {
+        update_authority: Some(mint_authority.key()).try_into()?,
+        mint: mint.key(),
+        name: name.clone(),
+        symbol: symbol.clone(),
+        uri: uri.clone(),
+        additional_metadata: vec![],
+    };
+    let metadata_space = token_metadata.tlv_size_of()?;
     
     // mint account
     let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
         &ctx.accounts.payer.key(),
         &mint.key(),
-        rent.minimum_balance(space),
+        rent.minimum_balance(space + metadata_space),
         space as u64,
         &token_program.key(),
     );
@@ -95,20 +120,361 @@ pub fn create_mint_with_metadata(
             mint_authority.to_account_info(),
         ],
     )?;
+
+    // dropping the update authority freezes the metadata for good
+    if immutable {
+        let update_authority_ix = spl_token_metadata_interface::instruction::update_authority(
+            &token_program.key(),
+            &mint.key(),
+            &mint_authority.key(),
+            Option::<Pubkey>::None.try_into()?,
+        );
+
+        anchor_lang::solana_program::program::invoke(
+            &update_authority_ix,
+            &[
+                mint.to_account_info(),
+                mint_authority.to_account_info(),
+            ],
+        )?;
+
+        token_metadata.update_authority = Option::<Pubkey>::None.try_into()?;
+    }
+    
+    if let Some(registry) = ctx.accounts.registry.as_mut() {
+        registry.record(mint.key(), mint_authority.key(), &[ExtensionType::MetadataPointer, ExtensionType::TokenMetadata])?;
+    }
+
+    if let Some(metadata_mirror) = ctx.accounts.metadata_mirror.as_mut() {
+        refresh_metadata_mirror(
+            metadata_mirror,
+            &token_metadata,
+            &ctx.accounts.payer.to_account_info(),
+            &system_program.to_account_info(),
+        )?;
+    }
     
     Ok(())
 }
 
+pub fn create_closeable_mint_with_metadata(
+    ctx: Context<CreateCloseableMintWithMetadata>,
+    close_authority: Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    // space for the fixed-size extensions, the metadata initialize reallocs for the rest
+    let extensions = [ExtensionType::MintCloseAuthority, ExtensionType::MetadataPointer];
+    crate::utils::extensions_compatible(&extensions)?;
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)?;
+
+    crate::utils::validate_token_metadata(&name, &symbol, &uri)?;
+
+    // fund the metadata up front so the whole balance is returned on close
+    let token_metadata = spl_token_metadata_interface::state::TokenMetadata {
+        update_authority: Some(mint_authority.key()).try_into()?,
+        mint: mint.key(),
+        name: name.clone(),
+        symbol: symbol.clone(),
+        uri: uri.clone(),
+        additional_metadata: vec![],
+    };
+    let metadata_space = token_metadata.tlv_size_of()?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space + metadata_space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init mint close authority extension
+    let init_close_authority_ix = spl_token_2022::instruction::initialize_mint_close_authority(
+        &token_program.key(),
+        &mint.key(),
+        Some(&close_authority),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_close_authority_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // init metadata pointer extension (pointing to the mint itself)
+    let init_metadata_pointer_ix = spl_token_2022::instruction::initialize_metadata_pointer(
+        &token_program.key(),
+        &mint.key(),
+        Some(&mint_authority.key()),
+        Some(mint.key()),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_pointer_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    // init token metadata
+    let init_metadata_ix = spl_token_metadata_interface::instruction::initialize(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        name,
+        symbol,
+        uri,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_ix,
+        &[
+            mint.to_account_info(),
+            mint_authority.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn create_nft(
+    ctx: Context<CreateNft>,
+    name: String,
+    symbol: String,
+    uri: String,
+    recipient: Pubkey,
+    keep_mint_authority: bool,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let recipient_token_account = &ctx.accounts.recipient_token_account;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    if ctx.accounts.recipient.key() != recipient {
+        return Err(anchor_lang::error::ErrorCode::ConstraintAddress.into());
+    }
+    crate::utils::validate_token_metadata(&name, &symbol, &uri)?;
+
+    // space for the metadata pointer, the metadata initialize reallocs for the rest
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::MetadataPointer])?;
+
+    // fund the metadata up front
+    let token_metadata = spl_token_metadata_interface::state::TokenMetadata {
+        update_authority: Some(mint_authority.key()).try_into()?,
+        mint: mint.key(),
+        name: name.clone(),
+        symbol: symbol.clone(),
+        uri: uri.clone(),
+        additional_metadata: vec![],
+    };
+    let metadata_space = token_metadata.tlv_size_of()?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space + metadata_space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init metadata pointer extension (pointing to the mint itself)
+    let init_metadata_pointer_ix = spl_token_2022::instruction::initialize_metadata_pointer(
+        &token_program.key(),
+        &mint.key(),
+        Some(&mint_authority.key()),
+        Some(mint.key()),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_pointer_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint, an nft has no decimals
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        0,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    // init token metadata
+    let init_metadata_ix = spl_token_metadata_interface::instruction::initialize(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        name,
+        symbol,
+        uri,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_ix,
+        &[
+            mint.to_account_info(),
+            mint_authority.to_account_info(),
+        ],
+    )?;
+
+    let create_ata_ix = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+        &ctx.accounts.payer.key(),
+        &recipient,
+        &mint.key(),
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_ata_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            recipient_token_account.to_account_info(),
+            ctx.accounts.recipient.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+            token_program.to_account_info(),
+            ctx.accounts.associated_token_program.to_account_info(),
+        ],
+    )?;
+
+    let mint_to_ix = spl_token_2022::instruction::mint_to(
+        &token_program.key(),
+        &mint.key(),
+        &recipient_token_account.key(),
+        &mint_authority.key(),
+        &[],
+        1,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &mint_to_ix,
+        &[
+            mint.to_account_info(),
+            recipient_token_account.to_account_info(),
+            mint_authority.to_account_info(),
+        ],
+    )?;
+
+    // fix the supply at one unless editions are minted later
+    if !keep_mint_authority {
+        let revoke_mint_authority_ix = spl_token_2022::instruction::set_authority(
+            &token_program.key(),
+            &mint.key(),
+            None,
+            spl_token_2022::instruction::AuthorityType::MintTokens,
+            &mint_authority.key(),
+            &[],
+        )?;
+
+        anchor_lang::solana_program::program::invoke(
+            &revoke_mint_authority_ix,
+            &[
+                mint.to_account_info(),
+                mint_authority.to_account_info(),
+            ],
+        )?;
+    }
+
+    let registered_extensions = [ExtensionType::MetadataPointer, ExtensionType::TokenMetadata];
+    ctx.accounts.registry.record(mint.key(), mint_authority.key(), &registered_extensions)?;
+
+    emit!(crate::events::MintCreated {
+        mint: mint.key(),
+        extension_types: registered_extensions.iter().map(|&ext| ext as u8).collect(),
+    });
+
+    Ok(())
+}
+
 pub fn update_metadata_field(
     ctx: Context<UpdateMetadataField>,
     field: String,
     value: String,
 ) -> Result<()> {
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let field = metadata_field(&field);
+
+    // the token program reallocs the mint for the new value but can't fund it, so top up the rent first
+    let mut token_metadata = token_metadata(&mint_info)?;
+    require_mutable(&token_metadata)?;
+    let old_metadata_space = token_metadata.tlv_size_of()?;
+    token_metadata.update(field.clone(), value.clone());
+    fund_metadata_realloc(
+        &mint_info,
+        old_metadata_space,
+        &token_metadata,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
     let update_field_ix = spl_token_metadata_interface::instruction::update_field(
         &ctx.accounts.token_program.key(),
         &ctx.accounts.mint.key(),
         &ctx.accounts.update_authority.key(),
-        spl_token_metadata_interface::state::Field::Key(field),
+        field,
         value,
     );
     
@@ -120,16 +486,481 @@ pub fn update_metadata_field(
         ],
         &[],
     )?;
+
+    refresh_initialized_metadata_mirror(
+        &ctx.accounts.metadata_mirror.to_account_info(),
+        &token_metadata,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
     
     Ok(())
 }
 
+// moves a value stored under a reserved key like "name" into the base field it was meant for
+pub fn migrate_metadata_field(ctx: Context<MigrateMetadataField>, key: String) -> Result<()> {
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let base_field = match metadata_field(&key) {
+        Field::Key(_) => return Err(crate::errors::TokenExtensionError::InvalidMetadata.into()),
+        base_field => base_field,
+    };
+
+    let mut token_metadata = token_metadata(&mint_info)?;
+    require_mutable(&token_metadata)?;
+    let value = token_metadata
+        .additional_metadata
+        .iter()
+        .find(|(existing_key, _)| *existing_key == key)
+        .map(|(_, value)| value.clone())
+        .ok_or(crate::errors::TokenExtensionError::MetadataFieldNotFound)?;
+
+    let old_metadata_space = token_metadata.tlv_size_of()?;
+    token_metadata.remove_key(&key);
+    token_metadata.update(base_field.clone(), value.clone());
+    fund_metadata_realloc(
+        &mint_info,
+        old_metadata_space,
+        &token_metadata,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    let remove_key_ix = spl_token_metadata_interface::instruction::remove_key(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.update_authority.key(),
+        key,
+        false,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &remove_key_ix,
+        &[
+            mint_info.clone(),
+            ctx.accounts.update_authority.to_account_info(),
+        ],
+    )?;
+
+    let update_field_ix = spl_token_metadata_interface::instruction::update_field(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.update_authority.key(),
+        base_field,
+        value,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &update_field_ix,
+        &[
+            mint_info,
+            ctx.accounts.update_authority.to_account_info(),
+        ],
+    )?;
+
+    refresh_initialized_metadata_mirror(
+        &ctx.accounts.metadata_mirror.to_account_info(),
+        &token_metadata,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    Ok(())
+}
+
+// the token program only reports a generic authority error on immutable metadata
+fn require_mutable(token_metadata: &TokenMetadata) -> Result<()> {
+    if Option::<Pubkey>::from(token_metadata.update_authority).is_none() {
+        return Err(crate::errors::TokenExtensionError::MetadataImmutable.into());
+    }
+
+    Ok(())
+}
+
+// the base fields are addressed by name, anything else is an additional metadata key
+fn metadata_field(field: &str) -> Field {
+    match field.to_ascii_lowercase().as_str() {
+        "name" => Field::Name,
+        "symbol" => Field::Symbol,
+        "uri" => Field::Uri,
+        _ => Field::Key(field.to_string()),
+    }
+}
+
+// tops up the mint for the metadata size after an update, the token program does the realloc
+fn fund_metadata_realloc<'info>(
+    mint_info: &AccountInfo<'info>,
+    old_metadata_space: usize,
+    token_metadata: &TokenMetadata,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let new_space = mint_info
+        .data_len()
+        .saturating_sub(old_metadata_space)
+        .saturating_add(token_metadata.tlv_size_of()?);
+    let required_lamports = Rent::get()?.minimum_balance(new_space);
+    let current_lamports = mint_info.lamports();
+
+    if required_lamports > current_lamports {
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &payer.key(),
+            &mint_info.key(),
+            required_lamports - current_lamports,
+        );
+
+        anchor_lang::solana_program::program::invoke(
+            &transfer_ix,
+            &[
+                payer.clone(),
+                mint_info.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn init_metadata_mirror(ctx: Context<InitMetadataMirror>) -> Result<()> {
+    let token_metadata = token_metadata(&ctx.accounts.mint.to_account_info())?;
+
+    refresh_metadata_mirror(
+        &mut ctx.accounts.metadata_mirror,
+        &token_metadata,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )
+}
+
+pub fn close_metadata_mirror(ctx: Context<CloseMetadataMirror>) -> Result<()> {
+    // immutable metadata has no update authority left, so the mint authority closes the mirror.
+    // read from the mint rather than the mirror, which may predate the last authority change
+    let token_metadata = token_metadata(&ctx.accounts.mint.to_account_info())?;
+    let authority = match Option::<Pubkey>::from(token_metadata.update_authority) {
+        Some(update_authority) => COption::Some(update_authority),
+        None => ctx.accounts.mint.mint_authority,
+    };
+    if authority != COption::Some(ctx.accounts.update_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    Ok(())
+}
+
+fn refresh_metadata_mirror<'info>(
+    metadata_mirror: &mut Account<'info, MetadataAccount>,
+    token_metadata: &TokenMetadata,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    grow_metadata_mirror(
+        &metadata_mirror.to_account_info(),
+        MetadataAccount::space(token_metadata),
+        payer,
+        system_program,
+    )?;
+    metadata_mirror.mirror(token_metadata);
+
+    Ok(())
+}
+
+// the mirror pda is always passed so a caller cannot leave it stale, it is refreshed once initialized
+fn refresh_initialized_metadata_mirror<'info>(
+    mirror_info: &AccountInfo<'info>,
+    token_metadata: &TokenMetadata,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if mirror_info.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut metadata_mirror = MetadataAccount::try_deserialize(&mut &mirror_info.try_borrow_data()?[..])?;
+    grow_metadata_mirror(mirror_info, MetadataAccount::space(token_metadata), payer, system_program)?;
+    metadata_mirror.mirror(token_metadata);
+
+    let mut mirror_data = mirror_info.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut mirror_data[..];
+    metadata_mirror.try_serialize(&mut writer)?;
+
+    Ok(())
+}
+
+// grows the mirror to fit the metadata, charging the payer for the extra rent
+fn grow_metadata_mirror<'info>(
+    mirror_info: &AccountInfo<'info>,
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+
+    if space > mirror_info.data_len() {
+        let required_lamports = Rent::get()?.minimum_balance(space);
+        let current_lamports = mirror_info.lamports();
+
+        if required_lamports > current_lamports {
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &payer.key(),
+                &mirror_info.key(),
+                required_lamports - current_lamports,
+            );
+
+            anchor_lang::solana_program::program::invoke(
+                &transfer_ix,
+                &[
+                    payer.clone(),
+                    mirror_info.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+
+        mirror_info.resize(space)?;
+    }
+
+    Ok(())
+}
+
+pub fn reclaim_metadata_rent(ctx: Context<ReclaimMetadataRent>) -> Result<()> {
+    // the token program only withdraws the lamports above the mint's rent exemption
+    let withdraw_excess_lamports_ix = spl_token_2022::instruction::withdraw_excess_lamports(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.destination.key(),
+        &ctx.accounts.mint_authority.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &withdraw_excess_lamports_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.mint_authority.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn remove_metadata_field(
+    ctx: Context<RemoveMetadataField>,
+    key: String,
+    idempotent: bool,
+) -> Result<()> {
+    // the token program only reports a missing key as a generic error, so check it here
+    let mut token_metadata = token_metadata(&ctx.accounts.mint.to_account_info())?;
+    require_mutable(&token_metadata)?;
+    let key_exists = token_metadata
+        .additional_metadata
+        .iter()
+        .any(|(existing_key, _)| *existing_key == key);
+    if !key_exists && !idempotent {
+        return Err(crate::errors::TokenExtensionError::MetadataFieldNotFound.into());
+    }
+
+    token_metadata.remove_key(&key);
+
+    let remove_key_ix = spl_token_metadata_interface::instruction::remove_key(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.update_authority.key(),
+        key,
+        idempotent,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &remove_key_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.update_authority.to_account_info(),
+        ],
+    )?;
+
+    refresh_initialized_metadata_mirror(
+        &ctx.accounts.metadata_mirror.to_account_info(),
+        &token_metadata,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    Ok(())
+}
+
+pub fn set_metadata_update_authority(
+    ctx: Context<SetMetadataUpdateAuthority>,
+    new_authority: Option<Pubkey>,
+    confirm_renounce: bool,
+) -> Result<()> {
+    // without an update authority the metadata is immutable for good
+    if new_authority.is_none() && !confirm_renounce {
+        return Err(crate::errors::TokenExtensionError::AuthorityRevocationNotConfirmed.into());
+    }
+
+    let mut token_metadata = token_metadata(&ctx.accounts.mint.to_account_info())?;
+    let update_authority: Option<Pubkey> = token_metadata.update_authority.into();
+    if update_authority != Some(ctx.accounts.update_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+    token_metadata.update_authority = new_authority.try_into()?;
+
+    let update_authority_ix = spl_token_metadata_interface::instruction::update_authority(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.update_authority.key(),
+        new_authority.try_into()?,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &update_authority_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.update_authority.to_account_info(),
+        ],
+    )?;
+
+    refresh_initialized_metadata_mirror(
+        &ctx.accounts.metadata_mirror.to_account_info(),
+        &token_metadata,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    Ok(())
+}
+
+pub fn emit_metadata(
+    ctx: Context<EmitMetadata>,
+    start: Option<u64>,
+    end: Option<u64>,
+) -> Result<()> {
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let metadata_pointer = crate::instructions::metadata_pointer::metadata_pointer(&mint_info)?;
+    let metadata_address = Option::<Pubkey>::from(metadata_pointer.metadata_address)
+        .ok_or(crate::errors::TokenExtensionError::InvalidMetadata)?;
+
+    // metadata on the mint is served by the token program, external metadata by the program owning it
+    let (metadata_program_info, metadata_info) = if metadata_address == mint_info.key() {
+        (ctx.accounts.token_program.to_account_info(), mint_info)
+    } else {
+        let metadata = match ctx.accounts.metadata.as_ref() {
+            Some(metadata) if metadata.key() == metadata_address => metadata,
+            _ => return Err(crate::errors::TokenExtensionError::MetadataAccountMissing.into()),
+        };
+        let metadata_program = match ctx.accounts.metadata_program.as_ref() {
+            Some(metadata_program) if metadata_program.key() == *metadata.owner => metadata_program,
+            _ => return Err(crate::errors::TokenExtensionError::MetadataAccountMissing.into()),
+        };
+
+        (metadata_program.to_account_info(), metadata.to_account_info())
+    };
+
+    let emit_ix = spl_token_metadata_interface::instruction::emit(
+        &metadata_program_info.key(),
+        &metadata_info.key(),
+        start,
+        end,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &emit_ix,
+        &[
+            metadata_info,
+            metadata_program_info.clone(),
+        ],
+    )?;
+
+    // an empty range emits nothing
+    match get_return_data() {
+        Some((program_id, data)) if program_id == metadata_program_info.key() => {
+            set_return_data(&data);
+            Ok(())
+        }
+        Some(_) => Err(crate::errors::TokenExtensionError::InvalidMetadata.into()),
+        None => Ok(()),
+    }
+}
+
+fn token_metadata(mint_info: &AccountInfo) -> Result<TokenMetadata> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let token_metadata = mint_state
+        .get_variable_len_extension::<TokenMetadata>()
+        .map_err(|_| crate::errors::TokenExtensionError::InvalidMetadata)?;
+
+    Ok(token_metadata)
+}
+
 #[derive(Accounts)]
 pub struct CreateMintWithMetadata<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(mut)]
 
+    // account initialized by the token program
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TokenExtensionMint::SIZE,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+    )]
+    pub registry: Option<Box<Account<'info, TokenExtensionMint>>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MetadataAccount::space(&TokenMetadata::default()),
+        seeds = [b"metadata_mirror", mint.key().as_ref()],
+        bump,
+    )]
+    pub metadata_mirror: Option<Box<Account<'info, MetadataAccount>>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateNft<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub mint: Signer<'info>,
+    pub mint_authority: Signer<'info>,
+    /// CHECK: wallet receiving the nft, checked against the recipient argument
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: associated token account of recipient, created by the associated token program if missing
+    #[account(
+        mut,
+        address = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &recipient.key(),
+            &mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub recipient_token_account: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TokenExtensionMint::SIZE,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+    )]
+    pub registry: Box<Account<'info, TokenExtensionMint>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCloseableMintWithMetadata<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+
     // account initialized by the token program
     pub mint: AccountInfo<'info>,
     pub mint_authority: Signer<'info>,
@@ -140,8 +971,127 @@ pub struct CreateMintWithMetadata<'info> {
 
 #[derive(Accounts)]
 pub struct UpdateMetadataField<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub update_authority: Signer<'info>,
+    /// CHECK: metadata mirror pda for the mint, refreshed only when it has been initialized
+    #[account(
+        mut,
+        seeds = [b"metadata_mirror", mint.key().as_ref()],
+        bump,
+    )]
+    pub metadata_mirror: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMetadataField<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub update_authority: Signer<'info>,
+    /// CHECK: metadata mirror pda for the mint, refreshed only when it has been initialized
+    #[account(
+        mut,
+        seeds = [b"metadata_mirror", mint.key().as_ref()],
+        bump,
+    )]
+    pub metadata_mirror: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct InitMetadataMirror<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = payer,
+        space = MetadataAccount::space(&TokenMetadata::default()),
+        seeds = [b"metadata_mirror", mint.key().as_ref()],
+        bump,
+    )]
+    pub metadata_mirror: Box<Account<'info, MetadataAccount>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMetadataMirror<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // update authority, or the mint authority once the metadata is immutable
+    pub update_authority: Signer<'info>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"metadata_mirror", mint.key().as_ref()],
+        bump,
+    )]
+    pub metadata_mirror: Box<Account<'info, MetadataAccount>>,
+    /// CHECK: only receives the mirror's lamports
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimMetadataRent<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_authority: Signer<'info>,
+    /// CHECK: only receives the reclaimed lamports
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+} 
+
+#[derive(Accounts)]
+pub struct RemoveMetadataField<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub update_authority: Signer<'info>,
+    /// CHECK: metadata mirror pda for the mint, refreshed only when it has been initialized
+    #[account(
+        mut,
+        seeds = [b"metadata_mirror", mint.key().as_ref()],
+        bump,
+    )]
+    pub metadata_mirror: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct SetMetadataUpdateAuthority<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(mut)]
     pub mint: Box<InterfaceAccount<'info, Mint>>,
     pub update_authority: Signer<'info>,
+    /// CHECK: metadata mirror pda for the mint, refreshed only when it has been initialized
+    #[account(
+        mut,
+        seeds = [b"metadata_mirror", mint.key().as_ref()],
+        bump,
+    )]
+    pub metadata_mirror: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct EmitMetadata<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: external metadata account, checked against the mint's metadata pointer
+    pub metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: program owning the external metadata account, checked in the handler
+    pub metadata_program: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token2022>,
+}
@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_2022::{self, Token2022};
 use anchor_spl::token_interface::Mint;
+use crate::state::TokenExtensionMint;
 use spl_token_2022::extension::{
     metadata_pointer::MetadataPointer,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
 
 pub fn create_mint_with_metadata_pointer(
@@ -74,6 +77,174 @@ pub fn create_mint_with_metadata_pointer(
     Ok(())
 }
 
+pub const METAPLEX_METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+pub fn create_mint_with_external_metadata(
+    ctx: Context<CreateMintWithExternalMetadata>,
+    authority: Option<Pubkey>,
+    metadata_address: Pubkey,
+    enforce_metaplex: bool,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    let metaplex_metadata = metadata_address == metaplex_metadata_address(&mint.key());
+    if enforce_metaplex && !metaplex_metadata {
+        return Err(crate::errors::TokenExtensionError::MetadataPointerMismatch.into());
+    }
+
+    // space for mint with metadata pointer extension
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::MetadataPointer])?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init metadata pointer extension (pointing to the external metadata)
+    let init_metadata_pointer_ix = spl_token_2022::instruction::initialize_metadata_pointer(
+        &token_program.key(),
+        &mint.key(),
+        authority.as_ref(),
+        Some(metadata_address),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_pointer_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    let registry = &mut ctx.accounts.registry;
+    registry.record(mint.key(), mint_authority.key(), &[ExtensionType::MetadataPointer])?;
+    registry.metaplex_metadata = metaplex_metadata;
+
+    emit!(crate::events::MintCreated {
+        mint: mint.key(),
+        extension_types: vec![ExtensionType::MetadataPointer as u8],
+    });
+
+    Ok(())
+}
+
+pub fn metaplex_metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", METAPLEX_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &METAPLEX_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn update_metadata_pointer(
+    ctx: Context<UpdateMetadataPointer>,
+    new_metadata_address: Option<Pubkey>,
+) -> Result<()> {
+    let metadata_pointer = metadata_pointer(&ctx.accounts.mint.to_account_info())?;
+    let pointer_authority: Option<Pubkey> = metadata_pointer.authority.into();
+    if pointer_authority != Some(ctx.accounts.authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let update_metadata_pointer_ix = spl_token_2022::extension::metadata_pointer::instruction::update(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.authority.key(),
+        &[],
+        new_metadata_address,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &update_metadata_pointer_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn set_metadata_pointer_authority(
+    ctx: Context<SetMetadataPointerAuthority>,
+    new_authority: Option<Pubkey>,
+    confirm_renounce: bool,
+) -> Result<()> {
+    // without a pointer authority the metadata address is fixed for good
+    if new_authority.is_none() && !confirm_renounce {
+        return Err(crate::errors::TokenExtensionError::AuthorityRevocationNotConfirmed.into());
+    }
+
+    let metadata_pointer = metadata_pointer(&ctx.accounts.mint.to_account_info())?;
+    let pointer_authority: Option<Pubkey> = metadata_pointer.authority.into();
+    if pointer_authority != Some(ctx.accounts.authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let set_authority_ix = spl_token_2022::instruction::set_authority(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        new_authority.as_ref(),
+        spl_token_2022::instruction::AuthorityType::MetadataPointer,
+        &ctx.accounts.authority.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &set_authority_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub(crate) fn metadata_pointer(mint_info: &AccountInfo) -> Result<MetadataPointer> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let metadata_pointer = mint_state
+        .get_extension::<MetadataPointer>()
+        .map_err(|_| crate::errors::TokenExtensionError::InvalidExtensionType)?;
+
+    Ok(*metadata_pointer)
+}
+
 #[derive(Accounts)]
 pub struct CreateMintWithMetadataPointer<'info> {
     #[account(mut)]
@@ -86,4 +257,41 @@ pub struct CreateMintWithMetadataPointer<'info> {
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct CreateMintWithExternalMetadata<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub mint: Signer<'info>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TokenExtensionMint::SIZE,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+    )]
+    pub registry: Box<Account<'info, TokenExtensionMint>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadataPointer<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct SetMetadataPointerAuthority<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
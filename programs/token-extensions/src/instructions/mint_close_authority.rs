@@ -1,21 +1,51 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_2022::{self, MintTo, Token2022};
 use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::state::{MintCloseConfig, TokenExtensionMint};
 use spl_token_2022::extension::{
+    confidential_transfer::ConfidentialTransferAccount,
     mint_close_authority::MintCloseAuthority,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
 
 pub fn create_mint_with_close_authority(
     ctx: Context<CreateMintWithCloseAuthority>,
     close_authority: Pubkey,
     decimals: u8,
+    use_pda_close_authority: bool,
+    register: bool,
 ) -> Result<()> {
     let mint = &ctx.accounts.mint;
     let mint_authority = &ctx.accounts.mint_authority;
     let rent = &ctx.accounts.rent;
     let system_program = &ctx.accounts.system_program;
     let token_program = &ctx.accounts.token_program;
+
+    if register && ctx.accounts.registry.is_none() {
+        return Err(crate::errors::TokenExtensionError::RegistryAccountMissing.into());
+    }
+    // a registry passed without register would be created but never recorded
+    if !register && ctx.accounts.registry.is_some() {
+        return Err(crate::errors::TokenExtensionError::UnexpectedRegistryAccount.into());
+    }
+    if use_pda_close_authority && ctx.accounts.close_config.is_none() {
+        return Err(crate::errors::TokenExtensionError::CloseConfigMissing.into());
+    }
+    // likewise a close config passed without the PDA authority would never be written
+    if !use_pda_close_authority && ctx.accounts.close_config.is_some() {
+        return Err(crate::errors::TokenExtensionError::UnexpectedCloseConfig.into());
+    }
+
+    // program PDA as close authority so governance can close without a hot key,
+    // the given close authority is then stored as the governance authority
+    let governance_authority = close_authority;
+    let close_authority = if use_pda_close_authority {
+        mint_close_authority_address(&mint.key())
+    } else {
+        close_authority
+    };
     
     // space for mint with close authority extension
     let space = ExtensionType::MintCloseAuthority.try_calculate_account_len::<spl_token_2022::state::Mint>(&[])?;
@@ -69,31 +99,197 @@ pub fn create_mint_with_close_authority(
         ],
     )?;
     
+    if let Some(registry) = ctx.accounts.registry.as_mut() {
+        registry.record(mint.key(), mint_authority.key(), &[ExtensionType::MintCloseAuthority])?;
+    }
+
+    if let Some(close_config) = ctx.accounts.close_config.as_mut() {
+        close_config.mint = mint.key();
+        close_config.authority = governance_authority;
+    }
+
+    emit!(crate::events::MintCreated {
+        mint: mint.key(),
+        extension_types: vec![ExtensionType::MintCloseAuthority as u8],
+    });
+    
     Ok(())
 }
 
 pub fn close_mint(ctx: Context<CloseMint>) -> Result<()> {
-    let close_mint_ix = spl_token_2022::instruction::close_account(
+    checked_close_mint(
+        &ctx.accounts.mint,
+        &ctx.accounts.destination,
+        &ctx.accounts.close_authority.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        &[],
+    )
+}
+
+pub fn close_mint_and_registry(ctx: Context<CloseMintAndRegistry>) -> Result<()> {
+    // the registry, when present, is closed by anchor after this handler
+    checked_close_mint(
+        &ctx.accounts.mint,
+        &ctx.accounts.destination,
+        &ctx.accounts.close_authority.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        &[],
+    )
+}
+
+pub fn close_mint_with_pda(ctx: Context<CloseMintWithPda>) -> Result<()> {
+    // the PDA signs for anyone, so gate on the governance authority in the close config;
+    // the close config is closed by anchor after this handler
+    let mint_key = ctx.accounts.mint.key();
+    let bump = [ctx.bumps.close_authority];
+    let signer_seeds: &[&[u8]] = &[b"mint_close", mint_key.as_ref(), &bump];
+
+    checked_close_mint(
+        &ctx.accounts.mint,
+        &ctx.accounts.destination,
+        &ctx.accounts.close_authority.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        &[signer_seeds],
+    )
+}
+
+pub fn set_mint_close_config(ctx: Context<SetMintCloseConfig>, new_authority: Pubkey) -> Result<()> {
+    ctx.accounts.close_config.authority = new_authority;
+
+    Ok(())
+}
+
+pub fn set_close_authority(
+    ctx: Context<SetCloseAuthority>,
+    new_authority: Option<Pubkey>,
+    confirm_revoke: bool,
+) -> Result<()> {
+    // without a close authority the mint can never be closed
+    if new_authority.is_none() && !confirm_revoke {
+        return Err(crate::errors::TokenExtensionError::AuthorityRevocationNotConfirmed.into());
+    }
+
+    let close_authority = configured_close_authority(&ctx.accounts.mint.to_account_info())?;
+    if close_authority != Some(ctx.accounts.close_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidCloseAuthority.into());
+    }
+
+    let set_authority_ix = spl_token_2022::instruction::set_authority(
         &ctx.accounts.token_program.key(),
         &ctx.accounts.mint.key(),
-        &ctx.accounts.destination.key(),
+        new_authority.as_ref(),
+        spl_token_2022::instruction::AuthorityType::CloseMint,
         &ctx.accounts.close_authority.key(),
         &[],
     )?;
-    
-    anchor_lang::solana_program::program::invoke_signed(
-        &close_mint_ix,
+
+    anchor_lang::solana_program::program::invoke(
+        &set_authority_ix,
         &[
             ctx.accounts.mint.to_account_info(),
-            ctx.accounts.destination.to_account_info(),
             ctx.accounts.close_authority.to_account_info(),
         ],
+    )?;
+
+    Ok(())
+}
+
+pub fn close_token_account(ctx: Context<CloseTokenAccount>) -> Result<()> {
+    if ctx.accounts.token_account.amount > 0 {
+        return Err(crate::errors::TokenExtensionError::AccountNotEmpty.into());
+    }
+
+    // confidential balances are encrypted so the plain amount can read zero while funds remain
+    {
+        let account_info = ctx.accounts.token_account.to_account_info();
+        let account_data = account_info.try_borrow_data()?;
+        let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+        if let Ok(confidential_account) = account_state.get_extension::<ConfidentialTransferAccount>() {
+            if confidential_account.closable().is_err() {
+                return Err(crate::errors::TokenExtensionError::ConfidentialBalanceNotEmpty.into());
+            }
+        }
+    }
+
+    let close_account_ix = spl_token_2022::instruction::close_account(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.token_account.key(),
+        &ctx.accounts.destination.key(),
+        &ctx.accounts.owner.key(),
         &[],
     )?;
-    
+
+    anchor_lang::solana_program::program::invoke(
+        &close_account_ix,
+        &[
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn mint_close_authority_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"mint_close", mint.as_ref()], &crate::ID).0
+}
+
+fn checked_close_mint<'info>(
+    mint: &InterfaceAccount<'info, Mint>,
+    destination: &AccountInfo<'info>,
+    close_authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if mint.supply > 0 {
+        return Err(crate::errors::TokenExtensionError::MintSupplyNotZero.into());
+    }
+
+    // signer must be the close authority configured on the mint
+    let configured_authority = configured_close_authority(&mint.to_account_info())?;
+    if configured_authority != Some(close_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidCloseAuthority.into());
+    }
+
+    let close_mint_ix = spl_token_2022::instruction::close_account(
+        &token_program.key(),
+        &mint.key(),
+        &destination.key(),
+        &close_authority.key(),
+        &[],
+    )?;
+
+    let lamports_before = destination.lamports();
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &close_mint_ix,
+        &[
+            mint.to_account_info(),
+            destination.clone(),
+            close_authority.clone(),
+        ],
+        signer_seeds,
+    )?;
+
+    emit!(crate::events::MintClosed {
+        mint: mint.key(),
+        destination: destination.key(),
+        lamports_reclaimed: destination.lamports().saturating_sub(lamports_before),
+        close_authority: close_authority.key(),
+    });
+
     Ok(())
 }
 
+fn configured_close_authority(mint_info: &AccountInfo) -> Result<Option<Pubkey>> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let mint_close_authority = mint_state.get_extension::<MintCloseAuthority>()?;
+
+    Ok(mint_close_authority.close_authority.into())
+}
+
 #[derive(Accounts)]
 pub struct CreateMintWithCloseAuthority<'info> {
     #[account(mut)]
@@ -103,6 +299,22 @@ pub struct CreateMintWithCloseAuthority<'info> {
     // account initialized by the token program
     pub mint: AccountInfo<'info>,
     pub mint_authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TokenExtensionMint::SIZE,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+    )]
+    pub registry: Option<Box<Account<'info, TokenExtensionMint>>>,
+    #[account(
+        init,
+        payer = payer,
+        space = MintCloseConfig::SIZE,
+        seeds = [b"mint_close_config", mint.key().as_ref()],
+        bump,
+    )]
+    pub close_config: Option<Box<Account<'info, MintCloseConfig>>>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token2022>,
@@ -118,4 +330,80 @@ pub struct CloseMint<'info> {
     pub destination: AccountInfo<'info>,
     pub close_authority: Signer<'info>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+} 
+
+#[derive(Accounts)]
+pub struct CloseMintWithPda<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+
+    // account to receive the lamports
+    pub destination: AccountInfo<'info>,
+    /// CHECK: program PDA set as the mint close authority
+    #[account(seeds = [b"mint_close", mint.key().as_ref()], bump)]
+    pub close_authority: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"mint_close_config", mint.key().as_ref()],
+        bump,
+        constraint = close_config.authority == authority.key() @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub close_config: Box<Account<'info, MintCloseConfig>>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct SetMintCloseConfig<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"mint_close_config", mint.key().as_ref()],
+        bump,
+        constraint = close_config.authority == authority.key() @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub close_config: Box<Account<'info, MintCloseConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMintAndRegistry<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+
+    // account to receive the lamports of both the mint and the registry
+    pub destination: AccountInfo<'info>,
+    pub close_authority: Signer<'info>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+        constraint = registry.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub registry: Option<Box<Account<'info, TokenExtensionMint>>>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct SetCloseAuthority<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub close_authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTokenAccount<'info> {
+    #[account(mut)]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+
+    // account to receive the lamports
+    pub destination: AccountInfo<'info>,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
@@ -1,20 +1,36 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_2022::{self, Token2022};
-use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::state::{IssuerRegistry, SbtMeta};
 use spl_token_2022::extension::{
     non_transferable::NonTransferable,
+    permanent_delegate::PermanentDelegate,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
 
 pub fn create_non_transferable_mint(
     ctx: Context<CreateNonTransferableMint>,
     decimals: u8,
+    freeze_authority: Option<Pubkey>,
+    label: Option<String>,
 ) -> Result<()> {
     let mint = &ctx.accounts.mint;
     let mint_authority = &ctx.accounts.mint_authority;
     let rent = &ctx.accounts.rent;
     let system_program = &ctx.accounts.system_program;
     let token_program = &ctx.accounts.token_program;
+
+    if label.is_some() && ctx.accounts.sbt_meta.is_none() {
+        return Err(crate::errors::TokenExtensionError::SbtMetaAccountMissing.into());
+    }
+    let label = label.unwrap_or_default();
+    if label.len() > SbtMeta::MAX_LABEL_LEN {
+        return Err(crate::errors::TokenExtensionError::LabelTooLong.into());
+    }
     
     // space for mint with non-transferable extension
     let space = ExtensionType::NonTransferable.try_calculate_account_len::<spl_token_2022::state::Mint>(&[])?;
@@ -55,7 +71,7 @@ pub fn create_non_transferable_mint(
         &token_program.key(),
         &mint.key(),
         &mint_authority.key(),
-        None,
+        freeze_authority.as_ref(),
         decimals,
     )?;
     
@@ -66,7 +82,560 @@ pub fn create_non_transferable_mint(
             rent.to_account_info(),
         ],
     )?;
+
+    if let Some(sbt_meta) = ctx.accounts.sbt_meta.as_mut() {
+        sbt_meta.mint = mint.key();
+        sbt_meta.payer = ctx.accounts.payer.key();
+        sbt_meta.issuer = mint_authority.key();
+        sbt_meta.label = label;
+        sbt_meta.created_at = Clock::get()?.unix_timestamp;
+    }
     
+    if let Some(issuer_registry) = ctx.accounts.issuer_registry.as_mut() {
+        issuer_registry.append(mint.key())?;
+    }
+
+    Ok(())
+}
+
+pub fn close_soulbound_account(ctx: Context<CloseSoulboundAccount>) -> Result<()> {
+    // neither burn nor close goes through on a frozen account
+    if ctx.accounts.token_account.is_frozen() {
+        return Err(crate::errors::TokenExtensionError::AccountFrozen.into());
+    }
+
+    let amount = ctx.accounts.token_account.amount;
+    if amount > 0 {
+        let burn_ix = spl_token_2022::instruction::burn_checked(
+            &ctx.accounts.token_program.key(),
+            &ctx.accounts.token_account.key(),
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.owner.key(),
+            &[],
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        anchor_lang::solana_program::program::invoke(
+            &burn_ix,
+            &[
+                ctx.accounts.token_account.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.owner.to_account_info(),
+            ],
+        )?;
+    }
+
+    let close_account_ix = spl_token_2022::instruction::close_account(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.token_account.key(),
+        &ctx.accounts.destination.key(),
+        &ctx.accounts.owner.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &close_account_ix,
+        &[
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn revoke_and_close_soulbound_account(ctx: Context<RevokeAndCloseSoulboundAccount>) -> Result<()> {
+    if ctx.accounts.token_account.is_frozen() {
+        return Err(crate::errors::TokenExtensionError::AccountFrozen.into());
+    }
+
+    let permanent_delegate: Option<Pubkey> = {
+        let mint_info = ctx.accounts.mint.to_account_info();
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        match mint_state.get_extension::<PermanentDelegate>() {
+            Ok(permanent_delegate) => permanent_delegate.delegate.into(),
+            Err(_) => None,
+        }
+    };
+
+    if permanent_delegate != Some(ctx.accounts.permanent_delegate.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidDelegate.into());
+    }
+
+    // the token program only lets the owner or the close authority close, so the holder
+    // must have handed the close authority to the permanent delegate when accepting the credential
+    if ctx.accounts.token_account.close_authority != COption::Some(ctx.accounts.permanent_delegate.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidCloseAuthority.into());
+    }
+
+    let amount = ctx.accounts.token_account.amount;
+    if amount > 0 {
+        let burn_ix = spl_token_2022::instruction::burn_checked(
+            &ctx.accounts.token_program.key(),
+            &ctx.accounts.token_account.key(),
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.permanent_delegate.key(),
+            &[],
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        anchor_lang::solana_program::program::invoke(
+            &burn_ix,
+            &[
+                ctx.accounts.token_account.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.permanent_delegate.to_account_info(),
+            ],
+        )?;
+    }
+
+    // rent goes back to the payer recorded in sbt_meta
+    let close_account_ix = spl_token_2022::instruction::close_account(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.token_account.key(),
+        &ctx.accounts.original_payer.key(),
+        &ctx.accounts.permanent_delegate.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &close_account_ix,
+        &[
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.original_payer.to_account_info(),
+            ctx.accounts.permanent_delegate.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn init_issuer_registry(ctx: Context<InitIssuerRegistry>, capacity: u16) -> Result<()> {
+    let issuer_registry = &mut ctx.accounts.issuer_registry;
+    issuer_registry.authority = ctx.accounts.authority.key();
+    issuer_registry.capacity = capacity;
+    issuer_registry.mints = Vec::new();
+
+    Ok(())
+}
+
+pub fn grow_issuer_registry(ctx: Context<GrowIssuerRegistry>, capacity: u16) -> Result<()> {
+    // anchor reallocs the account and charges the authority for the extra rent
+    if capacity < ctx.accounts.issuer_registry.capacity {
+        return Err(crate::errors::TokenExtensionError::InvalidRegistryCapacity.into());
+    }
+    ctx.accounts.issuer_registry.capacity = capacity;
+
+    Ok(())
+}
+
+pub fn get_sbt_label(ctx: Context<GetSbtLabel>) -> Result<String> {
+    Ok(ctx.accounts.sbt_meta.label.clone())
+}
+
+pub fn create_revocable_soulbound_mint(
+    ctx: Context<CreateRevocableSoulboundMint>,
+    permanent_delegate: Pubkey,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    // space for mint with non-transferable and permanent delegate extensions
+    let extensions = [ExtensionType::NonTransferable, ExtensionType::PermanentDelegate];
+    crate::utils::extensions_compatible(&extensions)?;
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // non-transferable extension
+    let init_non_transferable_ix = spl_token_2022::instruction::initialize_non_transferable_mint(
+        &token_program.key(),
+        &mint.key(),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_non_transferable_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // permanent delegate extension, the issuer revokes through it
+    let init_permanent_delegate_ix = spl_token_2022::instruction::initialize_permanent_delegate(
+        &token_program.key(),
+        &mint.key(),
+        &permanent_delegate,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_permanent_delegate_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    // record the payer and issuer for revoke_and_close_soulbound_account
+    let sbt_meta = &mut ctx.accounts.sbt_meta;
+    sbt_meta.mint = mint.key();
+    sbt_meta.payer = ctx.accounts.payer.key();
+    sbt_meta.issuer = mint_authority.key();
+    sbt_meta.created_at = Clock::get()?.unix_timestamp;
+
+    if let Some(issuer_registry) = ctx.accounts.issuer_registry.as_mut() {
+        issuer_registry.append(mint.key())?;
+    }
+
+    Ok(())
+}
+
+pub fn revoke_soulbound(ctx: Context<RevokeSoulbound>, amount: u64) -> Result<()> {
+    let permanent_delegate: Option<Pubkey> = {
+        let mint_info = ctx.accounts.mint.to_account_info();
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        match mint_state.get_extension::<PermanentDelegate>() {
+            Ok(permanent_delegate) => permanent_delegate.delegate.into(),
+            Err(_) => None,
+        }
+    };
+
+    if permanent_delegate != Some(ctx.accounts.permanent_delegate.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidDelegate.into());
+    }
+
+    // burn from the holder's account without the holder's signature
+    let burn_ix = spl_token_2022::instruction::burn_checked(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.token_account.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.permanent_delegate.key(),
+        &[],
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &burn_ix,
+        &[
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.permanent_delegate.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn create_soulbound_nft(
+    ctx: Context<CreateSoulboundNft>,
+    name: String,
+    symbol: String,
+    uri: String,
+    mint_to_recipient: bool,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    let (recipient, recipient_token_account, associated_token_program) = if mint_to_recipient {
+        match (
+            ctx.accounts.recipient.as_ref(),
+            ctx.accounts.recipient_token_account.as_ref(),
+            ctx.accounts.associated_token_program.as_ref(),
+        ) {
+            (Some(recipient), Some(recipient_token_account), Some(associated_token_program)) => {
+                let expected_token_account = spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &recipient.key(),
+                    &mint.key(),
+                    &token_program.key(),
+                );
+                if recipient_token_account.key() != expected_token_account {
+                    return Err(ProgramError::InvalidSeeds.into());
+                }
+                (Some(recipient), Some(recipient_token_account), Some(associated_token_program))
+            }
+            _ => return Err(crate::errors::TokenExtensionError::RecipientAccountMissing.into()),
+        }
+    } else {
+        (None, None, None)
+    };
+
+    // space for the fixed-size extensions, the metadata initialize reallocs for the rest
+    let extensions = [ExtensionType::NonTransferable, ExtensionType::MetadataPointer];
+    crate::utils::extensions_compatible(&extensions)?;
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)?;
+
+    crate::utils::validate_token_metadata(&name, &symbol, &uri)?;
+
+    // fund the metadata up front
+    let token_metadata = spl_token_metadata_interface::state::TokenMetadata {
+        update_authority: Some(mint_authority.key()).try_into()?,
+        mint: mint.key(),
+        name: name.clone(),
+        symbol: symbol.clone(),
+        uri: uri.clone(),
+        additional_metadata: vec![],
+    };
+    let metadata_space = token_metadata.tlv_size_of()?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space + metadata_space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // non-transferable extension
+    let init_non_transferable_ix = spl_token_2022::instruction::initialize_non_transferable_mint(
+        &token_program.key(),
+        &mint.key(),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_non_transferable_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // init metadata pointer extension (pointing to the mint itself)
+    let init_metadata_pointer_ix = spl_token_2022::instruction::initialize_metadata_pointer(
+        &token_program.key(),
+        &mint.key(),
+        Some(&mint_authority.key()),
+        Some(mint.key()),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_pointer_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint, an nft has no decimals
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        0,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    // init token metadata
+    let init_metadata_ix = spl_token_metadata_interface::instruction::initialize(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        name,
+        symbol,
+        uri,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_ix,
+        &[
+            mint.to_account_info(),
+            mint_authority.to_account_info(),
+        ],
+    )?;
+
+    if let (Some(recipient), Some(recipient_token_account), Some(associated_token_program)) =
+        (recipient, recipient_token_account, associated_token_program)
+    {
+        let create_ata_ix = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            &ctx.accounts.payer.key(),
+            &recipient.key(),
+            &mint.key(),
+            &token_program.key(),
+        );
+
+        anchor_lang::solana_program::program::invoke(
+            &create_ata_ix,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                recipient_token_account.to_account_info(),
+                recipient.to_account_info(),
+                mint.to_account_info(),
+                system_program.to_account_info(),
+                token_program.to_account_info(),
+                associated_token_program.to_account_info(),
+            ],
+        )?;
+
+        let mint_to_ix = spl_token_2022::instruction::mint_to(
+            &token_program.key(),
+            &mint.key(),
+            &recipient_token_account.key(),
+            &mint_authority.key(),
+            &[],
+            1,
+        )?;
+
+        anchor_lang::solana_program::program::invoke(
+            &mint_to_ix,
+            &[
+                mint.to_account_info(),
+                recipient_token_account.to_account_info(),
+                mint_authority.to_account_info(),
+            ],
+        )?;
+
+        // fix the supply at one
+        let revoke_mint_authority_ix = spl_token_2022::instruction::set_authority(
+            &token_program.key(),
+            &mint.key(),
+            None,
+            spl_token_2022::instruction::AuthorityType::MintTokens,
+            &mint_authority.key(),
+            &[],
+        )?;
+
+        anchor_lang::solana_program::program::invoke(
+            &revoke_mint_authority_ix,
+            &[
+                mint.to_account_info(),
+                mint_authority.to_account_info(),
+            ],
+        )?;
+    }
+
+    if let Some(issuer_registry) = ctx.accounts.issuer_registry.as_mut() {
+        issuer_registry.append(mint.key())?;
+    }
+
+    Ok(())
+}
+
+pub fn mint_soulbound_to(ctx: Context<MintSoulboundTo>, amount: u64) -> Result<()> {
+    let token_program = &ctx.accounts.token_program;
+
+    // the associated token program sizes the account for the non-transferable and immutable owner extensions
+    let create_ata_ix = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+        &ctx.accounts.payer.key(),
+        &ctx.accounts.recipient.key(),
+        &ctx.accounts.mint.key(),
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_ata_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.recipient_token_account.to_account_info(),
+            ctx.accounts.recipient.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            token_program.to_account_info(),
+            ctx.accounts.associated_token_program.to_account_info(),
+        ],
+    )?;
+
+    let mint_to_ix = spl_token_2022::instruction::mint_to(
+        &token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.recipient_token_account.key(),
+        &ctx.accounts.mint_authority.key(),
+        &[],
+        amount,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &mint_to_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.recipient_token_account.to_account_info(),
+            ctx.accounts.mint_authority.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn burn_soulbound(ctx: Context<BurnSoulbound>, amount: u64) -> Result<()> {
+    let burn_ix = spl_token_2022::instruction::burn_checked(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.token_account.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.owner.key(),
+        &[],
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &burn_ix,
+        &[
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+        ],
+    )?;
+
     Ok(())
 }
 
@@ -79,7 +648,205 @@ pub struct CreateNonTransferableMint<'info> {
     // account initialized by the token program
     pub mint: AccountInfo<'info>,
     pub mint_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"issuer", mint_authority.key().as_ref()],
+        bump,
+    )]
+    pub issuer_registry: Option<Box<Account<'info, IssuerRegistry>>>,
+    #[account(
+        init,
+        payer = payer,
+        space = SbtMeta::SIZE,
+        seeds = [b"sbt_meta", mint.key().as_ref()],
+        bump,
+    )]
+    pub sbt_meta: Option<Box<Account<'info, SbtMeta>>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRevocableSoulboundMint<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+
+    // account initialized by the token program
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"issuer", mint_authority.key().as_ref()],
+        bump,
+    )]
+    pub issuer_registry: Option<Box<Account<'info, IssuerRegistry>>>,
+    #[account(
+        init,
+        payer = payer,
+        space = SbtMeta::SIZE,
+        seeds = [b"sbt_meta", mint.key().as_ref()],
+        bump,
+    )]
+    pub sbt_meta: Box<Account<'info, SbtMeta>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSoulbound<'info> {
+    #[account(
+        mut,
+        constraint = token_account.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub permanent_delegate: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSoulboundNft<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+
+    // account initialized by the token program
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"issuer", mint_authority.key().as_ref()],
+        bump,
+    )]
+    pub issuer_registry: Option<Box<Account<'info, IssuerRegistry>>>,
+    /// CHECK: wallet receiving the nft when mint_to_recipient is set
+    pub recipient: Option<UncheckedAccount<'info>>,
+    /// CHECK: associated token account of recipient, checked in the handler
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+}
+
+#[derive(Accounts)]
+pub struct MintSoulboundTo<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_authority: Signer<'info>,
+    /// CHECK: wallet receiving the soulbound tokens
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: associated token account of recipient, created by the associated token program if missing
+    #[account(
+        mut,
+        address = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &recipient.key(),
+            &mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub recipient_token_account: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BurnSoulbound<'info> {
+    #[account(
+        mut,
+        constraint = token_account.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct GetSbtLabel<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(seeds = [b"sbt_meta", mint.key().as_ref()], bump)]
+    pub sbt_meta: Box<Account<'info, SbtMeta>>,
+}
+
+#[derive(Accounts)]
+#[instruction(capacity: u16)]
+pub struct InitIssuerRegistry<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = IssuerRegistry::space(capacity),
+        seeds = [b"issuer", authority.key().as_ref()],
+        bump,
+    )]
+    pub issuer_registry: Box<Account<'info, IssuerRegistry>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(capacity: u16)]
+pub struct GrowIssuerRegistry<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"issuer", authority.key().as_ref()],
+        bump,
+        realloc = IssuerRegistry::space(capacity),
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub issuer_registry: Box<Account<'info, IssuerRegistry>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSoulboundAccount<'info> {
+    #[account(
+        mut,
+        constraint = token_account.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub owner: Signer<'info>,
+    #[account(mut)]
+
+    // account to receive the lamports
+    pub destination: AccountInfo<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAndCloseSoulboundAccount<'info> {
+    #[account(
+        mut,
+        constraint = token_account.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub permanent_delegate: Signer<'info>,
+    #[account(seeds = [b"sbt_meta", mint.key().as_ref()], bump)]
+    pub sbt_meta: Box<Account<'info, SbtMeta>>,
+    #[account(address = sbt_meta.issuer @ crate::errors::TokenExtensionError::InvalidAuthority)]
+    pub issuer: Signer<'info>,
+    /// CHECK: payer recorded in sbt_meta, receives the rent
+    #[account(
+        mut,
+        address = sbt_meta.payer,
+    )]
+    pub original_payer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
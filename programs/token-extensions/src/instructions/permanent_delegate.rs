@@ -1,11 +1,27 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_2022::{self, Token2022};
-use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::state::{ClawbackEntry, ClawbackLog, DelegateConfig, TokenExtensionMint};
 use spl_token_2022::extension::{
+    default_account_state::DefaultAccountState,
     permanent_delegate::PermanentDelegate,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
 
+pub const MAX_REASON_LEN: usize = 256;
+
+// bits returned by has_risky_extensions
+#[constant]
+pub const RISK_PERMANENT_DELEGATE: u8 = 1 << 0;
+#[constant]
+pub const RISK_TRANSFER_HOOK: u8 = 1 << 1;
+#[constant]
+pub const RISK_PAUSABLE: u8 = 1 << 2;
+#[constant]
+pub const RISK_DEFAULT_FROZEN: u8 = 1 << 3;
+
 pub fn create_mint_with_permanent_delegate(
     ctx: Context<CreateMintWithPermanentDelegate>,
     delegate: Pubkey,
@@ -72,6 +88,388 @@ pub fn create_mint_with_permanent_delegate(
     Ok(())
 }
 
+pub fn create_permissioned_mint(
+    ctx: Context<CreatePermissionedMint>,
+    delegate: Pubkey,
+    freeze_authority: Pubkey,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    // space for mint with default account state and permanent delegate extensions
+    let extensions = [ExtensionType::DefaultAccountState, ExtensionType::PermanentDelegate];
+    crate::utils::extensions_compatible(&extensions)?;
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // new accounts start frozen until the freeze authority thaws them
+    let init_default_state_ix = spl_token_2022::instruction::initialize_default_account_state(
+        &token_program.key(),
+        &mint.key(),
+        &spl_token_2022::state::AccountState::Frozen,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_default_state_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // permanent delegate extension
+    let init_permanent_delegate_ix = spl_token_2022::instruction::initialize_permanent_delegate(
+        &token_program.key(),
+        &mint.key(),
+        &delegate,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_permanent_delegate_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        Some(&freeze_authority),
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    ctx.accounts.registry.record(mint.key(), mint_authority.key(), &extensions)?;
+
+    emit!(crate::events::MintCreated {
+        mint: mint.key(),
+        extension_types: extensions.iter().map(|&ext| ext as u8).collect(),
+    });
+
+    Ok(())
+}
+
+pub fn delegated_transfer(ctx: Context<DelegatedTransfer>, amount: u64, reason: String) -> Result<()> {
+    let permanent_delegate = configured_permanent_delegate(&ctx.accounts.mint.to_account_info())?;
+    if permanent_delegate != Some(ctx.accounts.permanent_delegate.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidDelegate.into());
+    }
+
+    log_clawback_reason(
+        &mut ctx.accounts.clawback_log,
+        &ctx.accounts.memo_program.to_account_info(),
+        ctx.accounts.source.key(),
+        amount,
+        &reason,
+    )?;
+
+    // move tokens out of the holder's account without the holder's signature
+    let transfer_ix = spl_token_2022::instruction::transfer_checked(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.source.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.destination.key(),
+        &ctx.accounts.permanent_delegate.key(),
+        &[],
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &transfer_ix,
+        &[
+            ctx.accounts.source.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.permanent_delegate.to_account_info(),
+        ],
+    )?;
+
+    emit!(crate::events::Clawback {
+        mint: ctx.accounts.mint.key(),
+        source: ctx.accounts.source.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+        delegate: ctx.accounts.permanent_delegate.key(),
+    });
+
+    Ok(())
+}
+
+pub fn delegated_burn(ctx: Context<DelegatedBurn>, amount: u64, reason: String) -> Result<()> {
+    let permanent_delegate = configured_permanent_delegate(&ctx.accounts.mint.to_account_info())?;
+    if permanent_delegate != Some(ctx.accounts.permanent_delegate.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidDelegate.into());
+    }
+
+    if amount > ctx.accounts.source.amount {
+        return Err(crate::errors::TokenExtensionError::InsufficientFunds.into());
+    }
+
+    log_clawback_reason(
+        &mut ctx.accounts.clawback_log,
+        &ctx.accounts.memo_program.to_account_info(),
+        ctx.accounts.source.key(),
+        amount,
+        &reason,
+    )?;
+
+    // burn from the holder's account without the holder's signature
+    let burn_ix = spl_token_2022::instruction::burn_checked(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.source.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.permanent_delegate.key(),
+        &[],
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &burn_ix,
+        &[
+            ctx.accounts.source.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.permanent_delegate.to_account_info(),
+        ],
+    )?;
+
+    emit!(crate::events::DelegatedBurnEvent {
+        mint: ctx.accounts.mint.key(),
+        source: ctx.accounts.source.key(),
+        amount,
+        delegate: ctx.accounts.permanent_delegate.key(),
+    });
+
+    Ok(())
+}
+
+pub fn init_clawback_log(ctx: Context<InitClawbackLog>) -> Result<()> {
+    ctx.accounts.clawback_log.mint = ctx.accounts.mint.key();
+
+    Ok(())
+}
+
+// the reason goes out as a memo and its hash into the per-mint log
+fn log_clawback_reason(
+    clawback_log: &mut ClawbackLog,
+    memo_program: &AccountInfo,
+    source: Pubkey,
+    amount: u64,
+    reason: &str,
+) -> Result<()> {
+    if reason.is_empty() || reason.len() > MAX_REASON_LEN {
+        return Err(crate::errors::TokenExtensionError::InvalidMemo.into());
+    }
+
+    let memo_ix = spl_memo::build_memo(reason.as_bytes(), &[]);
+
+    anchor_lang::solana_program::program::invoke(
+        &memo_ix,
+        &[
+            memo_program.clone(),
+        ],
+    )?;
+
+    clawback_log.record(ClawbackEntry {
+        timestamp: Clock::get()?.unix_timestamp,
+        source,
+        amount,
+        reason_hash: anchor_lang::solana_program::hash::hash(reason.as_bytes()).to_bytes(),
+    });
+
+    Ok(())
+}
+
+pub fn create_mint_with_pda_delegate(
+    ctx: Context<CreateMintWithPdaDelegate>,
+    authority: Pubkey,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    // program PDA as permanent delegate so clawbacks only go through governed_clawback
+    let delegate = delegate_address(&mint.key());
+
+    // space for mint with permanent delegate extension
+    let space = ExtensionType::PermanentDelegate.try_calculate_account_len::<spl_token_2022::state::Mint>(&[])?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // permanent delegate extension
+    let init_permanent_delegate_ix = spl_token_2022::instruction::initialize_permanent_delegate(
+        &token_program.key(),
+        &mint.key(),
+        &delegate,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_permanent_delegate_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    let delegate_config = &mut ctx.accounts.delegate_config;
+    delegate_config.mint = mint.key();
+    delegate_config.authority = authority;
+
+    Ok(())
+}
+
+pub fn set_delegate_config(ctx: Context<SetDelegateConfig>, new_authority: Pubkey) -> Result<()> {
+    ctx.accounts.delegate_config.authority = new_authority;
+
+    Ok(())
+}
+
+pub fn governed_clawback(ctx: Context<GovernedClawback>, amount: u64) -> Result<()> {
+    let transfer_ix = spl_token_2022::instruction::transfer_checked(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.source.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.destination.key(),
+        &ctx.accounts.delegate.key(),
+        &[],
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    let mint_key = ctx.accounts.mint.key();
+    let bump = [ctx.bumps.delegate];
+    let signer_seeds: &[&[u8]] = &[b"delegate", mint_key.as_ref(), &bump];
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &transfer_ix,
+        &[
+            ctx.accounts.source.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.delegate.to_account_info(),
+        ],
+        &[signer_seeds],
+    )?;
+
+    emit!(crate::events::Clawback {
+        mint: mint_key,
+        source: ctx.accounts.source.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+        delegate: ctx.accounts.delegate.key(),
+    });
+
+    Ok(())
+}
+
+pub fn delegate_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"delegate", mint.as_ref()], &crate::ID).0
+}
+
+pub fn get_permanent_delegate(ctx: Context<InspectMint>) -> Result<Option<Pubkey>> {
+    configured_permanent_delegate(&ctx.accounts.mint.to_account_info())
+}
+
+pub fn has_risky_extensions(ctx: Context<InspectMint>) -> Result<u8> {
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let extension_types = mint_state.get_extension_types()?;
+
+    let mut risk = 0u8;
+    if extension_types.contains(&ExtensionType::PermanentDelegate) {
+        risk |= RISK_PERMANENT_DELEGATE;
+    }
+    if extension_types.contains(&ExtensionType::TransferHook) {
+        risk |= RISK_TRANSFER_HOOK;
+    }
+    if extension_types.contains(&ExtensionType::Pausable) {
+        risk |= RISK_PAUSABLE;
+    }
+    // only a frozen default state locks new holders out
+    if let Ok(default_account_state) = mint_state.get_extension::<DefaultAccountState>() {
+        if default_account_state.state == spl_token_2022::state::AccountState::Frozen as u8 {
+            risk |= RISK_DEFAULT_FROZEN;
+        }
+    }
+
+    Ok(risk)
+}
+
+fn configured_permanent_delegate(mint_info: &AccountInfo) -> Result<Option<Pubkey>> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    match mint_state.get_extension::<PermanentDelegate>() {
+        Ok(permanent_delegate) => Ok(permanent_delegate.delegate.into()),
+        Err(_) => Ok(None),
+    }
+}
+
 #[derive(Accounts)]
 pub struct CreateMintWithPermanentDelegate<'info> {
     #[account(mut)]
@@ -84,4 +482,164 @@ pub struct CreateMintWithPermanentDelegate<'info> {
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct CreatePermissionedMint<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TokenExtensionMint::SIZE,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+    )]
+    pub registry: Box<Account<'info, TokenExtensionMint>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct DelegatedTransfer<'info> {
+    #[account(
+        mut,
+        constraint = source.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub source: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = destination.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub permanent_delegate: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"clawback_log", mint.key().as_ref()],
+        bump,
+        realloc = ClawbackLog::space(clawback_log.entries.len() + 1),
+        realloc::payer = permanent_delegate,
+        realloc::zero = false,
+    )]
+    pub clawback_log: Box<Account<'info, ClawbackLog>>,
+    /// CHECK: spl memo program, checked by address
+    #[account(address = spl_memo::id())]
+    pub memo_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct DelegatedBurn<'info> {
+    #[account(
+        mut,
+        constraint = source.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub source: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub permanent_delegate: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"clawback_log", mint.key().as_ref()],
+        bump,
+        realloc = ClawbackLog::space(clawback_log.entries.len() + 1),
+        realloc::payer = permanent_delegate,
+        realloc::zero = false,
+    )]
+    pub clawback_log: Box<Account<'info, ClawbackLog>>,
+    /// CHECK: spl memo program, checked by address
+    #[account(address = spl_memo::id())]
+    pub memo_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct InitClawbackLog<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = payer,
+        space = ClawbackLog::space(0),
+        seeds = [b"clawback_log", mint.key().as_ref()],
+        bump,
+    )]
+    pub clawback_log: Box<Account<'info, ClawbackLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMintWithPdaDelegate<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = DelegateConfig::SIZE,
+        seeds = [b"delegate_config", mint.key().as_ref()],
+        bump,
+    )]
+    pub delegate_config: Box<Account<'info, DelegateConfig>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct SetDelegateConfig<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"delegate_config", mint.key().as_ref()],
+        bump,
+        constraint = delegate_config.authority == authority.key() @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub delegate_config: Box<Account<'info, DelegateConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct GovernedClawback<'info> {
+    #[account(
+        mut,
+        constraint = source.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub source: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = destination.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"delegate_config", mint.key().as_ref()],
+        bump,
+        constraint = delegate_config.authority == authority.key() @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub delegate_config: Box<Account<'info, DelegateConfig>>,
+    /// CHECK: program PDA set as the mint permanent delegate
+    #[account(seeds = [b"delegate", mint.key().as_ref()], bump)]
+    pub delegate: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct InspectMint<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+}
@@ -1,13 +1,18 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token_2022::{self, Token2022};
 use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::state::{AccountRegistry, MemoPolicy, MemoPolicyRule};
 use spl_token_2022::extension::{
     memo_transfer::RequiredMemoTransfers,
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
 
 pub fn create_account_with_required_memo(
     ctx: Context<CreateAccountWithRequiredMemo>,
+    require_incoming: bool,
 ) -> Result<()> {
     let token_account = &ctx.accounts.token_account;
     let mint = &ctx.accounts.mint;
@@ -43,8 +48,8 @@ pub fn create_account_with_required_memo(
         &token_account.key(),
         &mint.key(),
         &owner.key(),
-        false,
-        //use true, for outgoing transfer memos
+        require_incoming,
+        // the token program only enforces memos on incoming transfers
     )?;
     
     anchor_lang::solana_program::program::invoke(
@@ -76,10 +81,53 @@ pub fn enable_required_memo_transfers(ctx: Context<EnableRequiredMemoTransfers>)
         ],
         &[],
     )?;
+
+    if let Some(account_registry) = ctx.accounts.account_registry.as_mut() {
+        account_registry.memo_required_incoming = true;
+    }
     
     Ok(())
 }
 
+pub fn reallocate_and_enable_memo(ctx: Context<ReallocateAndEnableMemo>) -> Result<()> {
+    // grows the account for the memo extension, a no-op when the space already exists
+    let reallocate_ix = spl_token_2022::instruction::reallocate(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.token_account.key(),
+        &ctx.accounts.payer.key(),
+        &ctx.accounts.owner.key(),
+        &[],
+        &[ExtensionType::MemoTransfer],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &reallocate_ix,
+        &[
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+        ],
+    )?;
+
+    let enable_memo_ix = spl_token_2022::instruction::enable_required_transfer_memos(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.token_account.key(),
+        &ctx.accounts.owner.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &enable_memo_ix,
+        &[
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
 pub fn disable_required_memo_transfers(ctx: Context<DisableRequiredMemoTransfers>) -> Result<()> {
     let disable_memo_ix = spl_token_2022::instruction::disable_required_transfer_memos(
         &ctx.accounts.token_program.key(),
@@ -97,6 +145,237 @@ pub fn disable_required_memo_transfers(ctx: Context<DisableRequiredMemoTransfers
         &[],
     )?;
     
+    if let Some(account_registry) = ctx.accounts.account_registry.as_mut() {
+        account_registry.memo_required_incoming = false;
+    }
+
+    Ok(())
+}
+
+pub fn enable_required_memo_transfers_cpi(ctx: Context<RequiredMemoTransfersCpi>) -> Result<()> {
+    set_required_memo_transfers_cpi(ctx, true)
+}
+
+pub fn disable_required_memo_transfers_cpi(ctx: Context<RequiredMemoTransfersCpi>) -> Result<()> {
+    set_required_memo_transfers_cpi(ctx, false)
+}
+
+// the owner may be a PDA of the calling program, its signature reaches us through invoke_signed
+// and is passed on to the token program
+fn set_required_memo_transfers_cpi(ctx: Context<RequiredMemoTransfersCpi>, enable: bool) -> Result<()> {
+    if !ctx.accounts.owner.is_signer {
+        return Err(anchor_lang::error::ErrorCode::AccountNotSigner.into());
+    }
+
+    let toggle_memo_ix = if enable {
+        spl_token_2022::instruction::enable_required_transfer_memos(
+            &ctx.accounts.token_program.key(),
+            &ctx.accounts.token_account.key(),
+            &ctx.accounts.owner.key(),
+            &[],
+        )?
+    } else {
+        spl_token_2022::instruction::disable_required_transfer_memos(
+            &ctx.accounts.token_program.key(),
+            &ctx.accounts.token_account.key(),
+            &ctx.accounts.owner.key(),
+            &[],
+        )?
+    };
+
+    anchor_lang::solana_program::program::invoke(
+        &toggle_memo_ix,
+        &[
+            ctx.accounts.token_account.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+        ],
+    )?;
+
+    if let Some(account_registry) = ctx.accounts.account_registry.as_mut() {
+        account_registry.memo_required_incoming = enable;
+    }
+
+    Ok(())
+}
+
+// each toggle is one token program cpi, keep a call well inside the compute budget
+#[constant]
+pub const MAX_BATCH_MEMO_ACCOUNTS: u8 = 16;
+
+pub fn batch_set_required_memo<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchSetRequiredMemo<'info>>,
+    enable: bool,
+) -> Result<()> {
+    if ctx.remaining_accounts.len() > MAX_BATCH_MEMO_ACCOUNTS as usize {
+        return Err(crate::errors::TokenExtensionError::InvalidBatchSize.into());
+    }
+
+    let owner_key = ctx.accounts.owner.key();
+    let mut changed: u32 = 0;
+    let mut skipped: u32 = 0;
+
+    for token_account in ctx.remaining_accounts.iter() {
+        if token_account.owner != &spl_token_2022::id() {
+            return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+        }
+
+        let memo_required = {
+            let account_data = token_account.try_borrow_data()?;
+            let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+            // any account of another owner fails the whole batch
+            if account_state.base.owner != owner_key {
+                return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+            }
+            match account_state.get_extension::<RequiredMemoTransfers>() {
+                Ok(memo_transfer) => bool::from(memo_transfer.require_incoming_transfer_memos),
+                Err(_) => false,
+            }
+        };
+
+        if memo_required == enable {
+            skipped += 1;
+            continue;
+        }
+
+        let toggle_memo_ix = if enable {
+            spl_token_2022::instruction::enable_required_transfer_memos(
+                &ctx.accounts.token_program.key(),
+                &token_account.key(),
+                &owner_key,
+                &[],
+            )?
+        } else {
+            spl_token_2022::instruction::disable_required_transfer_memos(
+                &ctx.accounts.token_program.key(),
+                &token_account.key(),
+                &owner_key,
+                &[],
+            )?
+        };
+
+        anchor_lang::solana_program::program::invoke(
+            &toggle_memo_ix,
+            &[
+                token_account.clone(),
+                ctx.accounts.owner.to_account_info(),
+            ],
+        )?;
+
+        changed += 1;
+    }
+
+    emit!(crate::events::RequiredMemoBatchUpdated {
+        owner: owner_key,
+        enabled: enable,
+        changed,
+        skipped,
+    });
+
+    Ok(())
+}
+
+pub fn require_memo_status(ctx: Context<RequireMemoStatus>, expected: bool) -> Result<()> {
+    let memo_required = {
+        let account_info = ctx.accounts.token_account.to_account_info();
+        let account_data = account_info.try_borrow_data()?;
+        let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+        match account_state.get_extension::<RequiredMemoTransfers>() {
+            Ok(memo_transfer) => bool::from(memo_transfer.require_incoming_transfer_memos),
+            Err(_) => false,
+        }
+    };
+
+    // fails the whole transaction when the status drifted
+    if memo_required != expected {
+        return Err(crate::errors::TokenExtensionError::MemoStatusMismatch.into());
+    }
+
+    Ok(())
+}
+
+pub fn get_memo_requirements(ctx: Context<GetMemoRequirements>) -> Result<bool> {
+    let account_info = ctx.accounts.token_account.to_account_info();
+    let account_data = account_info.try_borrow_data()?;
+    let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+
+    // accounts without the extension never require a memo
+    let require_incoming = match account_state.get_extension::<RequiredMemoTransfers>() {
+        Ok(memo_transfer) => bool::from(memo_transfer.require_incoming_transfer_memos),
+        Err(_) => false,
+    };
+
+    Ok(require_incoming)
+}
+
+pub fn set_memo_policy(ctx: Context<SetMemoPolicy>, rule: MemoPolicyRule) -> Result<()> {
+    match &rule {
+        MemoPolicyRule::AnyUtf8 => {}
+        MemoPolicyRule::Numeric { min_len, max_len } => {
+            if min_len > max_len || *max_len as usize >= MAX_MEMO_LEN {
+                return Err(crate::errors::TokenExtensionError::InvalidMemo.into());
+            }
+        }
+        MemoPolicyRule::Prefix { prefix } => {
+            if prefix.is_empty() || prefix.len() > MemoPolicy::MAX_PREFIX_LEN {
+                return Err(crate::errors::TokenExtensionError::InvalidMemo.into());
+            }
+        }
+    }
+
+    let memo_policy = &mut ctx.accounts.memo_policy;
+    memo_policy.mint = ctx.accounts.mint.key();
+    memo_policy.rule = rule;
+
+    Ok(())
+}
+
+pub const MAX_MEMO_LEN: usize = 566;
+
+pub fn transfer_with_memo(ctx: Context<TransferWithMemo>, amount: u64, memo: String) -> Result<()> {
+    if memo.is_empty() || memo.len() >= MAX_MEMO_LEN {
+        return Err(crate::errors::TokenExtensionError::InvalidMemo.into());
+    }
+
+    // the policy pda is always passed so a caller cannot skip it, mints without a policy accept any memo
+    let memo_policy_info = ctx.accounts.memo_policy.to_account_info();
+    if !memo_policy_info.data_is_empty() {
+        let memo_policy = MemoPolicy::try_deserialize(&mut &memo_policy_info.try_borrow_data()?[..])?;
+        if !memo_policy.allows(&memo) {
+            return Err(crate::errors::TokenExtensionError::InvalidMemo.into());
+        }
+    }
+
+    // the memo has to be the instruction right before the transfer
+    let memo_ix = spl_memo::build_memo(memo.as_bytes(), &[]);
+
+    anchor_lang::solana_program::program::invoke(
+        &memo_ix,
+        &[
+            ctx.accounts.memo_program.to_account_info(),
+        ],
+    )?;
+
+    let transfer_ix = spl_token_2022::instruction::transfer_checked(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.source.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.destination.key(),
+        &ctx.accounts.authority.key(),
+        &[],
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &transfer_ix,
+        &[
+            ctx.accounts.source.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+        ],
+    )?;
+
     Ok(())
 }
 
@@ -122,6 +401,23 @@ pub struct EnableRequiredMemoTransfers<'info> {
     #[account(mut)]
     pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"account_registry", token_account.key().as_ref()],
+        bump,
+    )]
+    pub account_registry: Option<Box<Account<'info, AccountRegistry>>>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct ReallocateAndEnableMemo<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token2022>,
 }
 
@@ -130,5 +426,88 @@ pub struct DisableRequiredMemoTransfers<'info> {
     #[account(mut)]
     pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"account_registry", token_account.key().as_ref()],
+        bump,
+    )]
+    pub account_registry: Option<Box<Account<'info, AccountRegistry>>>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RequiredMemoTransfersCpi<'info> {
+    #[account(
+        mut,
+        constraint = token_account.owner == owner.key() @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: owner of the token account, a PDA signed for by the calling program, checked in the handler
+    pub owner: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"account_registry", token_account.key().as_ref()],
+        bump,
+    )]
+    pub account_registry: Option<Box<Account<'info, AccountRegistry>>>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct TransferWithMemo<'info> {
+    #[account(mut)]
+    pub source: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = destination.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub authority: Signer<'info>,
+    /// CHECK: memo policy pda for the mint, read only when it has been set
+    #[account(
+        seeds = [b"memo_policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub memo_policy: UncheckedAccount<'info>,
+    /// CHECK: spl memo program, checked by address
+    #[account(address = spl_memo::id())]
+    pub memo_program: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct SetMemoPolicy<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        constraint = mint.mint_authority == COption::Some(mint_authority.key()) @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MemoPolicy::SIZE,
+        seeds = [b"memo_policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub memo_policy: Box<Account<'info, MemoPolicy>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BatchSetRequiredMemo<'info> {
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RequireMemoStatus<'info> {
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct GetMemoRequirements<'info> {
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+}
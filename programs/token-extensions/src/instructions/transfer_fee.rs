@@ -1,11 +1,25 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
 use anchor_spl::token_2022::{self, Token2022, TransferChecked};
+use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::state::{FeeEscrow, FeeSchedule, FeeStats, TokenExtensionMint};
 use spl_token_2022::extension::{
-    transfer_fee::TransferFeeConfig,
+    transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
 
+// 100% expressed in basis points
+pub const MAX_FEE_BASIS_POINTS: u16 = 10_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TransferFeeAuthorityType {
+    TransferFeeConfig,
+    WithheldWithdraw,
+}
+
 pub fn create_mint_with_transfer_fee(
     ctx: Context<CreateMintWithTransferFee>,
     transfer_fee_config_authority: Option<Pubkey>,
@@ -13,12 +27,29 @@ pub fn create_mint_with_transfer_fee(
     transfer_fee_basis_points: u16,
     maximum_fee: u64,
     decimals: u8,
+    use_pda_withdraw_authority: bool,
+    register: bool,
 ) -> Result<()> {
     let mint = &ctx.accounts.mint;
     let mint_authority = &ctx.accounts.mint_authority;
     let rent = &ctx.accounts.rent;
     let system_program = &ctx.accounts.system_program;
     let token_program = &ctx.accounts.token_program;
+
+    if register && ctx.accounts.registry.is_none() {
+        return Err(crate::errors::TokenExtensionError::RegistryAccountMissing.into());
+    }
+    // a registry passed without register would be created but never recorded
+    if !register && ctx.accounts.registry.is_some() {
+        return Err(crate::errors::TokenExtensionError::UnexpectedRegistryAccount.into());
+    }
+
+    // program PDA as withdraw authority so fees can only be swept into the configured escrow
+    let withdraw_withheld_authority = if use_pda_withdraw_authority {
+        Some(fee_withdraw_authority_address(&mint.key()))
+    } else {
+        withdraw_withheld_authority
+    };
     
     // space for mint with transfer fee extension
     let space = ExtensionType::TransferFeeConfig.try_calculate_account_len::<spl_token_2022::state::Mint>(&[])?;
@@ -75,64 +106,862 @@ pub fn create_mint_with_transfer_fee(
         ],
     )?;
     
+    if let Some(registry) = ctx.accounts.registry.as_mut() {
+        registry.record(mint.key(), mint_authority.key(), &[ExtensionType::TransferFeeConfig])?;
+    }
+    
+    Ok(())
+}
+
+pub fn create_mint_with_fee_and_hook(
+    ctx: Context<CreateMintWithFeeAndHook>,
+    transfer_fee_config_authority: Option<Pubkey>,
+    withdraw_withheld_authority: Option<Pubkey>,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+    hook_authority: Option<Pubkey>,
+    hook_program_id: Option<Pubkey>,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    if transfer_fee_basis_points > MAX_FEE_BASIS_POINTS {
+        return Err(crate::errors::TokenExtensionError::InvalidTransferFeeConfig.into());
+    }
+
+    // space for mint with transfer fee and transfer hook extensions
+    let extensions = [ExtensionType::TransferFeeConfig, ExtensionType::TransferHook];
+    crate::utils::extensions_compatible(&extensions)?;
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // transfer fee config extension
+    let init_transfer_fee_ix = spl_token_2022::instruction::initialize_transfer_fee_config(
+        &token_program.key(),
+        &mint.key(),
+        transfer_fee_config_authority.as_ref(),
+        withdraw_withheld_authority.as_ref(),
+        transfer_fee_basis_points,
+        maximum_fee,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_transfer_fee_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // transfer hook extension
+    let init_transfer_hook_ix = spl_token_2022::instruction::initialize_transfer_hook(
+        &token_program.key(),
+        &mint.key(),
+        hook_authority.as_ref(),
+        hook_program_id,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_transfer_hook_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn create_compliance_mint(
+    ctx: Context<CreateComplianceMint>,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+    delegate: Pubkey,
+    freeze_authority: Option<Pubkey>,
+    decimals: u8,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    if transfer_fee_basis_points > MAX_FEE_BASIS_POINTS {
+        return Err(crate::errors::TokenExtensionError::InvalidTransferFeeConfig.into());
+    }
+
+    // space for mint with transfer fee and permanent delegate extensions
+    let extensions = [ExtensionType::TransferFeeConfig, ExtensionType::PermanentDelegate];
+    crate::utils::extensions_compatible(&extensions)?;
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // transfer fee config extension, the issuer controls both fee authorities
+    let init_transfer_fee_ix = spl_token_2022::instruction::initialize_transfer_fee_config(
+        &token_program.key(),
+        &mint.key(),
+        Some(&mint_authority.key()),
+        Some(&mint_authority.key()),
+        transfer_fee_basis_points,
+        maximum_fee,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_transfer_fee_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // permanent delegate extension
+    let init_permanent_delegate_ix = spl_token_2022::instruction::initialize_permanent_delegate(
+        &token_program.key(),
+        &mint.key(),
+        &delegate,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_permanent_delegate_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        freeze_authority.as_ref(),
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    ctx.accounts.registry.record(mint.key(), mint_authority.key(), &extensions)?;
+
     Ok(())
 }
 
-pub fn transfer_with_fee(
-    ctx: Context<TransferWithFee>,
+pub fn transfer_with_fee<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TransferWithFee<'info>>,
     amount: u64,
-    expected_fee: u64,
+    max_fee_tolerance: Option<u64>,
 ) -> Result<()> {
-    let transfer_ix = spl_token_2022::instruction::transfer_checked_with_fee(
-        &ctx.accounts.token_program.key(),
-        &ctx.accounts.source.key(),
+    // multisig signers are passed through remaining accounts
+    let authority = ctx.accounts.authority.to_account_info();
+    let signers = authority_signers(&authority, ctx.remaining_accounts)?;
+
+    crate::utils::forward_required_memo(
+        &ctx.accounts.destination.to_account_info(),
+        &ctx.accounts.instructions.to_account_info(),
+        &ctx.accounts.memo_program.to_account_info(),
+    )?;
+
+    transfer_checked_with_computed_fee(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.source.to_account_info(),
+        &ctx.accounts.mint.to_account_info(),
+        &ctx.accounts.destination.to_account_info(),
+        &authority,
+        &signers,
+        amount,
+        ctx.accounts.mint.decimals,
+        max_fee_tolerance,
+    )
+}
+
+pub fn transfer_with_fee_to_owner(
+    ctx: Context<TransferWithFeeToOwner>,
+    amount: u64,
+    max_fee_tolerance: Option<u64>,
+) -> Result<()> {
+    let token_program = &ctx.accounts.token_program;
+
+    // destination associated token account, no-op when it already exists
+    let create_ata_ix = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+        &ctx.accounts.payer.key(),
+        &ctx.accounts.destination_owner.key(),
         &ctx.accounts.mint.key(),
-        &ctx.accounts.destination.key(),
-        &ctx.accounts.authority.key(),
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_ata_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.destination_owner.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            token_program.to_account_info(),
+            ctx.accounts.associated_token_program.to_account_info(),
+        ],
+    )?;
+
+    crate::utils::forward_required_memo(
+        &ctx.accounts.destination.to_account_info(),
+        &ctx.accounts.instructions.to_account_info(),
+        &ctx.accounts.memo_program.to_account_info(),
+    )?;
+
+    transfer_checked_with_computed_fee(
+        &token_program.to_account_info(),
+        &ctx.accounts.source.to_account_info(),
+        &ctx.accounts.mint.to_account_info(),
+        &ctx.accounts.destination.to_account_info(),
+        &ctx.accounts.authority.to_account_info(),
         &[],
         amount,
         ctx.accounts.mint.decimals,
-        expected_fee,
+        max_fee_tolerance,
+    )
+}
+
+pub fn withdraw_withheld_tokens<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawWithheldTokens<'info>>,
+) -> Result<()> {
+    let mint_key = ctx.accounts.mint.key();
+
+    // signing remaining accounts are multisig signers, the rest are sources
+    let (signer_accounts, source_accounts): (Vec<AccountInfo<'info>>, Vec<AccountInfo<'info>>) =
+        ctx.remaining_accounts.iter().cloned().partition(|account| account.is_signer);
+    let withdraw_withheld_authority = ctx.accounts.withdraw_withheld_authority.to_account_info();
+    let signers = authority_signers(&withdraw_withheld_authority, &signer_accounts)?;
+    let signer_keys: Vec<&Pubkey> = signers.iter().map(|account| account.key).collect();
+
+    let sources = withdraw_sources(&source_accounts, &mint_key)?;
+    let source_keys: Vec<&Pubkey> = sources.iter().map(|account| account.key).collect();
+
+    let withdraw_ix = spl_token_2022::instruction::withdraw_withheld_tokens_from_accounts(
+        &ctx.accounts.token_program.key(),
+        &mint_key,
+        &ctx.accounts.destination.key(),
+        &withdraw_withheld_authority.key(),
+        &signer_keys,
+        &source_keys,
     )?;
-    
-    anchor_lang::solana_program::program::invoke_signed(
-        &transfer_ix,
+
+    let accounts_swept = sources.len() as u32;
+    let mut account_infos = vec![
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.destination.to_account_info(),
+        withdraw_withheld_authority.clone(),
+    ];
+    account_infos.extend(signers);
+    account_infos.extend(sources);
+
+    let balance_before = ctx.accounts.destination.amount;
+    anchor_lang::solana_program::program::invoke(&withdraw_ix, &account_infos)?;
+
+    if let Some(fee_stats) = ctx.accounts.fee_stats.as_mut() {
+        ctx.accounts.destination.reload()?;
+        let withdrawn = ctx.accounts.destination.amount.saturating_sub(balance_before);
+        fee_stats.record_withdrawal(withdrawn, Clock::get()?.unix_timestamp)?;
+    }
+
+    emit!(crate::events::WithheldTokensWithdrawn {
+        mint: mint_key,
+        destination: ctx.accounts.destination.key(),
+        accounts_swept,
+    });
+
+    Ok(())
+}
+
+pub fn harvest_withheld_tokens_to_mint<'info>(
+    ctx: Context<'_, '_, 'info, 'info, HarvestWithheldTokensToMint<'info>>,
+) -> Result<()> {
+    let mint_key = ctx.accounts.mint.key();
+
+    // skip anything that isn't a token account of this mint
+    let sources: Vec<AccountInfo<'info>> = ctx
+        .remaining_accounts
+        .iter()
+        .filter(|account| is_token_account_of_mint(account, &mint_key))
+        .cloned()
+        .collect();
+
+    if sources.is_empty() {
+        return Err(crate::errors::TokenExtensionError::NoValidSourceAccounts.into());
+    }
+
+    let source_keys: Vec<&Pubkey> = sources.iter().map(|account| account.key).collect();
+
+    let harvest_ix = spl_token_2022::instruction::harvest_withheld_tokens_to_mint(
+        &ctx.accounts.token_program.key(),
+        &mint_key,
+        &source_keys,
+    )?;
+
+    let mut account_infos = vec![ctx.accounts.mint.to_account_info()];
+    account_infos.extend(sources);
+
+    anchor_lang::solana_program::program::invoke(&harvest_ix, &account_infos)?;
+
+    Ok(())
+}
+
+pub fn withdraw_withheld_tokens_from_mint(ctx: Context<WithdrawWithheldFromMint>) -> Result<()> {
+    // signer must be the withdraw authority configured on the mint
+    let withdraw_withheld_authority = configured_withdraw_withheld_authority(&ctx.accounts.mint.to_account_info())?;
+    if withdraw_withheld_authority != Some(ctx.accounts.withdraw_withheld_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let withdraw_ix = spl_token_2022::instruction::withdraw_withheld_tokens_from_mint(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.destination.key(),
+        &ctx.accounts.withdraw_withheld_authority.key(),
+        &[],
+    )?;
+
+    let balance_before = ctx.accounts.destination.amount;
+    anchor_lang::solana_program::program::invoke(
+        &withdraw_ix,
         &[
-            ctx.accounts.source.to_account_info(),
             ctx.accounts.mint.to_account_info(),
             ctx.accounts.destination.to_account_info(),
-            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.withdraw_withheld_authority.to_account_info(),
         ],
-        &[],
     )?;
-    
+
+    if let Some(fee_stats) = ctx.accounts.fee_stats.as_mut() {
+        ctx.accounts.destination.reload()?;
+        let withdrawn = ctx.accounts.destination.amount.saturating_sub(balance_before);
+        fee_stats.record_withdrawal(withdrawn, Clock::get()?.unix_timestamp)?;
+    }
+
+    Ok(())
+}
+
+pub fn configure_fee_escrow(ctx: Context<ConfigureFeeEscrow>) -> Result<()> {
+    // the fee config authority decides where withheld fees may go
+    let transfer_fee_config_authority: Option<Pubkey> = {
+        let mint_info = ctx.accounts.mint.to_account_info();
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        let transfer_fee_config = mint_state.get_extension::<TransferFeeConfig>()?;
+        transfer_fee_config.transfer_fee_config_authority.into()
+    };
+
+    if transfer_fee_config_authority != Some(ctx.accounts.authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let fee_escrow = &mut ctx.accounts.fee_escrow;
+    fee_escrow.mint = ctx.accounts.mint.key();
+    fee_escrow.escrow = ctx.accounts.escrow.key();
+
     Ok(())
 }
 
-pub fn withdraw_withheld_tokens(ctx: Context<WithdrawWithheldTokens>) -> Result<()> {
+pub fn withdraw_withheld_to_escrow<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawWithheldToEscrow<'info>>,
+) -> Result<()> {
+    let mint_key = ctx.accounts.mint.key();
+    let sources = withdraw_sources(ctx.remaining_accounts, &mint_key)?;
+    let source_keys: Vec<&Pubkey> = sources.iter().map(|account| account.key).collect();
+
     let withdraw_ix = spl_token_2022::instruction::withdraw_withheld_tokens_from_accounts(
         &ctx.accounts.token_program.key(),
+        &mint_key,
         &ctx.accounts.destination.key(),
-        &ctx.accounts.mint.key(),
-        &ctx.accounts.withdraw_withheld_authority.key(),
+        &ctx.accounts.withdraw_authority.key(),
         &[],
-        &[&ctx.accounts.source.key()],
+        &source_keys,
     )?;
-    
+
+    let accounts_swept = sources.len() as u32;
+    let mut account_infos = vec![
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.destination.to_account_info(),
+        ctx.accounts.withdraw_authority.to_account_info(),
+    ];
+    account_infos.extend(sources);
+
+    let bump = [ctx.bumps.withdraw_authority];
+    let signer_seeds: &[&[u8]] = &[b"fee_withdraw_authority", mint_key.as_ref(), &bump];
+
     anchor_lang::solana_program::program::invoke_signed(
         &withdraw_ix,
+        &account_infos,
+        &[signer_seeds],
+    )?;
+
+    emit!(crate::events::WithheldTokensWithdrawn {
+        mint: mint_key,
+        destination: ctx.accounts.destination.key(),
+        accounts_swept,
+    });
+
+    Ok(())
+}
+
+pub fn init_fee_stats(ctx: Context<InitFeeStats>) -> Result<()> {
+    let fee_stats = &mut ctx.accounts.fee_stats;
+    fee_stats.mint = ctx.accounts.mint.key();
+    fee_stats.total_fees_collected = 0;
+    fee_stats.last_withdraw_ts = 0;
+
+    Ok(())
+}
+
+pub fn close_fee_stats(ctx: Context<CloseFeeStats>) -> Result<()> {
+    // only the withdraw authority of the mint can discard its history
+    let withdraw_withheld_authority = configured_withdraw_withheld_authority(&ctx.accounts.mint.to_account_info())?;
+    if withdraw_withheld_authority != Some(ctx.accounts.withdraw_withheld_authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    Ok(())
+}
+
+pub fn set_transfer_fee(
+    ctx: Context<SetTransferFee>,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+) -> Result<()> {
+    if transfer_fee_basis_points > MAX_FEE_BASIS_POINTS {
+        return Err(crate::errors::TokenExtensionError::InvalidTransferFeeConfig.into());
+    }
+
+    // current schedule, the newer fee is the one being replaced
+    let (old_transfer_fee_basis_points, old_maximum_fee) = {
+        let mint_info = ctx.accounts.mint.to_account_info();
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        let transfer_fee_config = mint_state.get_extension::<TransferFeeConfig>()?;
+        (
+            u16::from(transfer_fee_config.newer_transfer_fee.transfer_fee_basis_points),
+            u64::from(transfer_fee_config.newer_transfer_fee.maximum_fee),
+        )
+    };
+
+    let set_transfer_fee_ix = spl_token_2022::instruction::set_transfer_fee(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.transfer_fee_config_authority.key(),
+        &[],
+        transfer_fee_basis_points,
+        maximum_fee,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &set_transfer_fee_ix,
         &[
-            ctx.accounts.destination.to_account_info(),
             ctx.accounts.mint.to_account_info(),
-            ctx.accounts.source.to_account_info(),
-            ctx.accounts.withdraw_withheld_authority.to_account_info(),
+            ctx.accounts.transfer_fee_config_authority.to_account_info(),
         ],
+    )?;
+
+    emit!(crate::events::TransferFeeUpdated {
+        mint: ctx.accounts.mint.key(),
+        old_transfer_fee_basis_points,
+        old_maximum_fee,
+        new_transfer_fee_basis_points: transfer_fee_basis_points,
+        new_maximum_fee: maximum_fee,
+        epoch: Clock::get()?.epoch,
+    });
+
+    Ok(())
+}
+
+pub fn rotate_transfer_fee_authority(
+    ctx: Context<RotateTransferFeeAuthority>,
+    authority_type: TransferFeeAuthorityType,
+    new_authority: Option<Pubkey>,
+    confirm_renounce: bool,
+) -> Result<()> {
+    // renouncing the withdraw authority strands every future fee
+    if authority_type == TransferFeeAuthorityType::WithheldWithdraw
+        && new_authority.is_none()
+        && !confirm_renounce
+    {
+        return Err(crate::errors::TokenExtensionError::AuthorityRevocationNotConfirmed.into());
+    }
+
+    // signer must be the authority currently configured on the mint
+    {
+        let mint_info = ctx.accounts.mint.to_account_info();
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        let transfer_fee_config = mint_state.get_extension::<TransferFeeConfig>()?;
+        let current_authority: Option<Pubkey> = match authority_type {
+            TransferFeeAuthorityType::TransferFeeConfig => {
+                transfer_fee_config.transfer_fee_config_authority.into()
+            }
+            TransferFeeAuthorityType::WithheldWithdraw => {
+                transfer_fee_config.withdraw_withheld_authority.into()
+            }
+        };
+
+        if current_authority != Some(ctx.accounts.current_authority.key()) {
+            return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+        }
+    }
+
+    let spl_authority_type = match authority_type {
+        TransferFeeAuthorityType::TransferFeeConfig => {
+            spl_token_2022::instruction::AuthorityType::TransferFeeConfig
+        }
+        TransferFeeAuthorityType::WithheldWithdraw => {
+            spl_token_2022::instruction::AuthorityType::WithheldWithdraw
+        }
+    };
+
+    let set_authority_ix = spl_token_2022::instruction::set_authority(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        new_authority.as_ref(),
+        spl_authority_type,
+        &ctx.accounts.current_authority.key(),
         &[],
     )?;
-    
+
+    anchor_lang::solana_program::program::invoke(
+        &set_authority_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.current_authority.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn get_transfer_fee(ctx: Context<GetTransferFee>, amount: u64) -> Result<u64> {
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+
+    // mints without the extension never charge a fee
+    let transfer_fee_config = match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => transfer_fee_config,
+        Err(_) => return Ok(0),
+    };
+
+    // picks the older or newer fee depending on whether the newer one is in effect yet
+    transfer_fee_config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or_else(|| crate::errors::TokenExtensionError::TransferFeeCalculationError.into())
+}
+
+pub fn get_fee_schedule(ctx: Context<GetFeeSchedule>) -> Result<FeeSchedule> {
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let transfer_fee_config = mint_state.get_extension::<TransferFeeConfig>()?;
+
+    let older = &transfer_fee_config.older_transfer_fee;
+    let newer = &transfer_fee_config.newer_transfer_fee;
+
+    Ok(FeeSchedule {
+        older_transfer_fee_basis_points: u16::from(older.transfer_fee_basis_points),
+        older_maximum_fee: u64::from(older.maximum_fee),
+        older_epoch: u64::from(older.epoch),
+        newer_transfer_fee_basis_points: u16::from(newer.transfer_fee_basis_points),
+        newer_maximum_fee: u64::from(newer.maximum_fee),
+        newer_epoch: u64::from(newer.epoch),
+    })
+}
+
+pub fn get_withheld_amount(ctx: Context<GetWithheldAmount>) -> Result<u64> {
+    if ctx.accounts.token_account.mint != ctx.accounts.mint.key() {
+        return Err(crate::errors::TokenExtensionError::MintMismatch.into());
+    }
+
+    let account_info = ctx.accounts.token_account.to_account_info();
+    let account_data = account_info.try_borrow_data()?;
+    let account_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+
+    // accounts without the extension have nothing withheld
+    match account_state.get_extension::<TransferFeeAmount>() {
+        Ok(transfer_fee_amount) => Ok(u64::from(transfer_fee_amount.withheld_amount)),
+        Err(_) => Ok(0),
+    }
+}
+
+pub fn close_fee_account(ctx: Context<CloseFeeAccount>) -> Result<()> {
+    let token_account = &ctx.accounts.token_account;
+    let mint = &ctx.accounts.mint;
+    let token_program = &ctx.accounts.token_program;
+
+    // fail before harvesting so nothing is partially executed
+    if token_account.amount != 0 {
+        return Err(crate::errors::TokenExtensionError::AccountNotEmpty.into());
+    }
+
+    // move withheld fees to the mint, otherwise the token program refuses to close
+    let harvest_ix = spl_token_2022::instruction::harvest_withheld_tokens_to_mint(
+        &token_program.key(),
+        &mint.key(),
+        &[&token_account.key()],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &harvest_ix,
+        &[
+            mint.to_account_info(),
+            token_account.to_account_info(),
+        ],
+    )?;
+
+    let close_account_ix = spl_token_2022::instruction::close_account(
+        &token_program.key(),
+        &token_account.key(),
+        &ctx.accounts.destination.key(),
+        &ctx.accounts.owner.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &close_account_ix,
+        &[
+            token_account.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn transfer_checked_with_computed_fee<'info>(
+    token_program: &AccountInfo<'info>,
+    source: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    signers: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+    max_fee_tolerance: Option<u64>,
+) -> Result<()> {
+    let epoch = Clock::get()?.epoch;
+    let (fee, pending_fee_change) = calculate_transfer_fee(mint, epoch, amount)?;
+
+    // caller can still bound the fee they are willing to pay
+    if let Some(max_fee_tolerance) = max_fee_tolerance {
+        if fee > max_fee_tolerance {
+            return Err(crate::errors::TokenExtensionError::TransferFeeCalculationError.into());
+        }
+    }
+
+    // surface the common token program failures with our own error codes
+    {
+        let source_data = source.try_borrow_data()?;
+        let source_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&source_data)?;
+        let destination_data = destination.try_borrow_data()?;
+        let destination_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&destination_data)?;
+
+        if source_state.base.mint != mint.key() || destination_state.base.mint != mint.key() {
+            return Err(crate::errors::TokenExtensionError::MintMismatch.into());
+        }
+
+        if source_state.base.is_frozen() || destination_state.base.is_frozen() {
+            return Err(crate::errors::TokenExtensionError::AccountFrozen.into());
+        }
+
+        // the fee comes out of amount, so the source only ever needs amount
+        if source_state.base.amount < amount {
+            return Err(crate::errors::TokenExtensionError::InsufficientFunds.into());
+        }
+    }
+
+    let signer_keys: Vec<&Pubkey> = signers.iter().map(|account| account.key).collect();
+    let transfer_ix = spl_token_2022::instruction::transfer_checked_with_fee(
+        &token_program.key(),
+        &source.key(),
+        &mint.key(),
+        &destination.key(),
+        &authority.key(),
+        &signer_keys,
+        amount,
+        decimals,
+        fee,
+    )?;
+
+    let mut account_infos = vec![
+        source.clone(),
+        mint.clone(),
+        destination.clone(),
+        authority.clone(),
+    ];
+    account_infos.extend_from_slice(signers);
+
+    anchor_lang::solana_program::program::invoke(&transfer_ix, &account_infos)?;
+
+    emit!(crate::events::TransferWithFeeEvent {
+        mint: mint.key(),
+        source: source.key(),
+        destination: destination.key(),
+        amount,
+        fee,
+        net: amount.saturating_sub(fee),
+        epoch,
+        pending_fee_change,
+    });
+
     Ok(())
 }
 
+// fee charged for `amount` at `epoch`, and whether a newer fee is scheduled but not yet in effect
+fn calculate_transfer_fee(mint_info: &AccountInfo, epoch: u64, amount: u64) -> Result<(u64, bool)> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let transfer_fee_config = mint_state.get_extension::<TransferFeeConfig>()?;
+
+    let fee = transfer_fee_config
+        .calculate_epoch_fee(epoch, amount)
+        .ok_or(crate::errors::TokenExtensionError::TransferFeeCalculationError)?;
+    let pending_fee_change = epoch < u64::from(transfer_fee_config.newer_transfer_fee.epoch);
+
+    Ok((fee, pending_fee_change))
+}
+
+fn configured_withdraw_withheld_authority(mint_info: &AccountInfo) -> Result<Option<Pubkey>> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let transfer_fee_config = mint_state.get_extension::<TransferFeeConfig>()?;
+
+    Ok(transfer_fee_config.withdraw_withheld_authority.into())
+}
+
+// signers backing `authority`: none for a plain signer, at least m of n for an SPL multisig
+fn authority_signers<'info>(
+    authority: &AccountInfo<'info>,
+    candidates: &[AccountInfo<'info>],
+) -> Result<Vec<AccountInfo<'info>>> {
+    let is_multisig = authority.owner == &spl_token_2022::id()
+        && authority.data_len() == spl_token_2022::state::Multisig::LEN;
+
+    if !is_multisig {
+        if !authority.is_signer {
+            return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+        }
+        return Ok(Vec::new());
+    }
+
+    let multisig = spl_token_2022::state::Multisig::unpack(&authority.try_borrow_data()?)?;
+    let multisig_signers = &multisig.signers[..multisig.n as usize];
+
+    let mut signers: Vec<AccountInfo<'info>> = Vec::new();
+    for candidate in candidates.iter() {
+        if candidate.is_signer
+            && multisig_signers.contains(candidate.key)
+            && !signers.iter().any(|signer| signer.key == candidate.key)
+        {
+            signers.push(candidate.clone());
+        }
+    }
+
+    if signers.len() < multisig.m as usize {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    Ok(signers)
+}
+
+pub fn fee_withdraw_authority_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_withdraw_authority", mint.as_ref()], &crate::ID).0
+}
+
+// every source must be a token account of this mint, repeated keys are swept once
+fn withdraw_sources<'info>(accounts: &[AccountInfo<'info>], mint: &Pubkey) -> Result<Vec<AccountInfo<'info>>> {
+    let mut sources: Vec<AccountInfo<'info>> = Vec::with_capacity(accounts.len());
+    for account in accounts.iter() {
+        if !is_token_account_of_mint(account, mint) {
+            return Err(crate::errors::TokenExtensionError::MintMismatch.into());
+        }
+        if !sources.iter().any(|source| source.key == account.key) {
+            sources.push(account.clone());
+        }
+    }
+
+    if sources.is_empty() {
+        return Err(crate::errors::TokenExtensionError::NoValidSourceAccounts.into());
+    }
+
+    Ok(sources)
+}
+
+fn is_token_account_of_mint(account: &AccountInfo, mint: &Pubkey) -> bool {
+    if account.owner != &spl_token_2022::id() {
+        return false;
+    }
+
+    let data = match account.try_borrow_data() {
+        Ok(data) => data,
+        Err(_) => return false,
+    };
+
+    match StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data) {
+        Ok(state) => state.base.mint == *mint,
+        Err(_) => false,
+    }
+}
+
 #[derive(Accounts)]
 pub struct CreateMintWithTransferFee<'info> {
     #[account(mut)]
@@ -142,6 +971,50 @@ pub struct CreateMintWithTransferFee<'info> {
     // account initialized by the token program
     pub mint: AccountInfo<'info>,
     pub mint_authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TokenExtensionMint::SIZE,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+    )]
+    pub registry: Option<Box<Account<'info, TokenExtensionMint>>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMintWithFeeAndHook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+
+    // account initialized by the token program
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateComplianceMint<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+
+    // account initialized by the token program
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = TokenExtensionMint::SIZE,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+    )]
+    pub registry: Box<Account<'info, TokenExtensionMint>>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token2022>,
@@ -154,17 +1027,201 @@ pub struct TransferWithFee<'info> {
     #[account(mut)]
     pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
     pub mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: keypair, CPI-signed PDA or SPL multisig owning the source, validated in the handler
+    pub authority: UncheckedAccount<'info>,
+    /// CHECK: instructions sysvar, checked by address
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    /// CHECK: spl memo program, checked by address
+    #[account(address = spl_memo::id())]
+    pub memo_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct TransferWithFeeToOwner<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub source: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: associated token account of destination_owner, created by the associated token program if missing
+    #[account(
+        mut,
+        address = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &destination_owner.key(),
+            &mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub destination: UncheckedAccount<'info>,
+    /// CHECK: wallet that owns the destination token account
+    pub destination_owner: UncheckedAccount<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
     pub authority: Signer<'info>,
+    /// CHECK: instructions sysvar, checked by address
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    /// CHECK: spl memo program, checked by address
+    #[account(address = spl_memo::id())]
+    pub memo_program: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct WithdrawWithheldTokens<'info> {
     #[account(mut)]
-    pub source: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: signer or SPL multisig, validated in the handler
+    pub withdraw_withheld_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+
+    // optional lifetime fee totals
+    #[account(mut, seeds = [b"fee_stats", mint.key().as_ref()], bump)]
+    pub fee_stats: Option<Box<Account<'info, FeeStats>>>,
+} 
+
+#[derive(Accounts)]
+pub struct HarvestWithheldTokensToMint<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawWithheldFromMint<'info> {
     #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = destination.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
     pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub withdraw_withheld_authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+
+    // optional lifetime fee totals
+    #[account(mut, seeds = [b"fee_stats", mint.key().as_ref()], bump)]
+    pub fee_stats: Option<Box<Account<'info, FeeStats>>>,
+}
+
+#[derive(Accounts)]
+pub struct InitFeeStats<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = payer,
+        space = FeeStats::SIZE,
+        seeds = [b"fee_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub fee_stats: Box<Account<'info, FeeStats>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseFeeStats<'info> {
     pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        close = destination,
+        seeds = [b"fee_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub fee_stats: Box<Account<'info, FeeStats>>,
     pub withdraw_withheld_authority: Signer<'info>,
+    #[account(mut)]
+
+    // account to receive the lamports
+    pub destination: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTransferFee<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub transfer_fee_config_authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RotateTransferFeeAuthority<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub current_authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct GetTransferFee<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseFeeAccount<'info> {
+    #[account(
+        mut,
+        constraint = token_account.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub owner: Signer<'info>,
+    #[account(mut)]
+
+    // account to receive the lamports
+    pub destination: AccountInfo<'info>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct GetWithheldAmount<'info> {
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureFeeEscrow<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = escrow.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = FeeEscrow::SIZE,
+        seeds = [b"fee_escrow", mint.key().as_ref()],
+        bump,
+    )]
+    pub fee_escrow: Box<Account<'info, FeeEscrow>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawWithheldToEscrow<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = destination.key() == fee_escrow.escrow @ crate::errors::TokenExtensionError::InvalidFeeEscrow,
+    )]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(seeds = [b"fee_escrow", mint.key().as_ref()], bump)]
+    pub fee_escrow: Box<Account<'info, FeeEscrow>>,
+    /// CHECK: PDA set as the mint's withdraw withheld authority at creation
+    #[account(seeds = [b"fee_withdraw_authority", mint.key().as_ref()], bump)]
+    pub withdraw_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct GetFeeSchedule<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+}
@@ -1,22 +1,110 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token_2022::{self, Token2022};
-use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta,
+    pubkey_data::PubkeyData,
+    seeds::Seed,
+    state::ExtraAccountMetaList,
+};
+use crate::state::{ResolvedAccountMeta, TokenExtensionMint, TransferStats};
 use spl_token_2022::extension::{
-    transfer_hook::TransferHook,
+    transfer_hook::{TransferHook, TransferHookAccount},
+    BaseStateWithExtensions,
     ExtensionType,
+    StateWithExtensions,
 };
+use spl_discriminator::SplDiscriminate;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use spl_type_length_value::state::TlvStateBorrowed;
+
+// keeps a hooked transfer within the transaction account limit
+#[constant]
+pub const MAX_EXTRA_ACCOUNT_METAS: u8 = 10;
+
+/// Borsh mirror of `spl_tlv_account_resolution::seeds::Seed`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum SeedArg {
+    /// Hard-coded bytes.
+    Literal { bytes: Vec<u8> },
+    /// Bytes taken from the execute instruction data.
+    InstructionData { index: u8, length: u8 },
+    /// Key of an account in the execute account list.
+    AccountKey { index: u8 },
+    /// Bytes taken from the data of an account in the execute account list.
+    AccountData { account_index: u8, data_index: u8, length: u8 },
+}
+
+impl From<&SeedArg> for Seed {
+    fn from(seed: &SeedArg) -> Self {
+        match seed {
+            SeedArg::Literal { bytes } => Seed::Literal { bytes: bytes.clone() },
+            SeedArg::InstructionData { index, length } => Seed::InstructionData {
+                index: *index,
+                length: *length,
+            },
+            SeedArg::AccountKey { index } => Seed::AccountKey { index: *index },
+            SeedArg::AccountData { account_index, data_index, length } => Seed::AccountData {
+                account_index: *account_index,
+                data_index: *data_index,
+                length: *length,
+            },
+        }
+    }
+}
+
+/// Borsh mirror of `spl_tlv_account_resolution::account::ExtraAccountMeta`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ExtraAccountMetaArg {
+    /// A fixed address.
+    Literal { pubkey: Pubkey, is_signer: bool, is_writable: bool },
+    /// A PDA of the hook program derived from the given seeds.
+    Pda { seeds: Vec<SeedArg>, is_signer: bool, is_writable: bool },
+    /// An address read from the data of an account in the execute account list.
+    AccountData { account_index: u8, data_index: u8, is_signer: bool, is_writable: bool },
+}
+
+impl ExtraAccountMetaArg {
+    pub fn to_extra_account_meta(&self) -> Result<ExtraAccountMeta> {
+        let extra_account_meta = match self {
+            ExtraAccountMetaArg::Literal { pubkey, is_signer, is_writable } => {
+                ExtraAccountMeta::new_with_pubkey(pubkey, *is_signer, *is_writable)
+            }
+            ExtraAccountMetaArg::Pda { seeds, is_signer, is_writable } => {
+                let seeds: Vec<Seed> = seeds.iter().map(Seed::from).collect();
+                ExtraAccountMeta::new_with_seeds(&seeds, *is_signer, *is_writable)
+            }
+            ExtraAccountMetaArg::AccountData { account_index, data_index, is_signer, is_writable } => {
+                ExtraAccountMeta::new_with_pubkey_data(
+                    &PubkeyData::AccountData {
+                        account_index: *account_index,
+                        data_index: *data_index,
+                    },
+                    *is_signer,
+                    *is_writable,
+                )
+            }
+        };
+
+        extra_account_meta.map_err(|_| crate::errors::TokenExtensionError::InvalidExtraAccountMeta.into())
+    }
+}
 
 pub fn create_mint_with_transfer_hook(
     ctx: Context<CreateMintWithTransferHook>,
     authority: Option<Pubkey>,
     program_id: Option<Pubkey>,
     decimals: u8,
+    self_hook: bool,
 ) -> Result<()> {
     let mint = &ctx.accounts.mint;
     let mint_authority = &ctx.accounts.mint_authority;
     let rent = &ctx.accounts.rent;
     let system_program = &ctx.accounts.system_program;
     let token_program = &ctx.accounts.token_program;
+
+    validate_hook_program(program_id, ctx.accounts.hook_program.as_ref(), self_hook)?;
     
     // space for mint with transfer hook extension
     let space = ExtensionType::TransferHook.try_calculate_account_len::<spl_token_2022::state::Mint>(&[])?;
@@ -77,7 +165,15 @@ pub fn create_mint_with_transfer_hook(
 pub fn update_transfer_hook_program(
     ctx: Context<UpdateTransferHookProgram>,
     program_id: Option<Pubkey>,
+    self_hook: bool,
 ) -> Result<()> {
+    let hook_authority = transfer_hook_authority(&ctx.accounts.mint.to_account_info())?;
+    if hook_authority != Some(ctx.accounts.authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    validate_hook_program(program_id, ctx.accounts.hook_program.as_ref(), self_hook)?;
+
     let update_transfer_hook_ix = spl_token_2022::instruction::update_transfer_hook(
         &ctx.accounts.token_program.key(),
         &ctx.accounts.mint.key(),
@@ -94,10 +190,592 @@ pub fn update_transfer_hook_program(
         ],
         &[],
     )?;
-    
+
+    if program_id.is_none() {
+        emit!(crate::events::HookRemoved {
+            mint: ctx.accounts.mint.key(),
+            authority: ctx.accounts.authority.key(),
+        });
+    }
+
+    Ok(())
+}
+
+pub fn set_transfer_hook_authority(
+    ctx: Context<SetTransferHookAuthority>,
+    new_authority: Option<Pubkey>,
+) -> Result<()> {
+    let hook_authority = transfer_hook_authority(&ctx.accounts.mint.to_account_info())?;
+    if hook_authority != Some(ctx.accounts.authority.key()) {
+        return Err(crate::errors::TokenExtensionError::InvalidAuthority.into());
+    }
+
+    let set_authority_ix = spl_token_2022::instruction::set_authority(
+        &ctx.accounts.token_program.key(),
+        &ctx.accounts.mint.key(),
+        new_authority.as_ref(),
+        spl_token_2022::instruction::AuthorityType::TransferHookProgramId,
+        &ctx.accounts.authority.key(),
+        &[],
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &set_authority_ix,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn initialize_extra_account_meta_list(
+    ctx: Context<InitializeExtraAccountMetaList>,
+    metas: Vec<ExtraAccountMetaArg>,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let extra_account_meta_list = &ctx.accounts.extra_account_meta_list;
+
+    let extra_account_metas = extra_account_metas(&metas)?;
+
+    let hook_program_id = match transfer_hook_program_id(&mint.to_account_info())? {
+        Some(hook_program_id) => hook_program_id,
+        None => return Err(crate::errors::TokenExtensionError::TransferHookProgramNotFound.into()),
+    };
+
+    let (extra_account_meta_list_address, bump) =
+        spl_transfer_hook_interface::get_extra_account_metas_address_and_bump_seed(&mint.key(), &hook_program_id);
+    if extra_account_meta_list.key() != extra_account_meta_list_address {
+        return Err(ProgramError::InvalidSeeds.into());
+    }
+
+    if hook_program_id != crate::ID {
+        if ctx.accounts.hook_program.key() != hook_program_id {
+            return Err(crate::errors::TokenExtensionError::TransferHookProgramNotFound.into());
+        }
+
+        // a foreign hook owns its list, so it creates it through the interface instruction
+        return invoke_hook_extra_account_meta_list_init(
+            &ctx.accounts.hook_program.to_account_info(),
+            &extra_account_meta_list.to_account_info(),
+            &mint.to_account_info(),
+            &ctx.accounts.mint_authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &extra_account_metas,
+        );
+    }
+
+    // this program is the hook, so the list is our own pda
+    create_own_extra_account_meta_list(
+        &ctx.accounts.payer.to_account_info(),
+        &extra_account_meta_list.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.rent,
+        &mint.key(),
+        bump,
+        &extra_account_metas,
+    )
+}
+
+pub fn create_hooked_nft_mint(
+    ctx: Context<CreateHookedNftMint>,
+    hook_program: Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+    metas: Vec<ExtraAccountMetaArg>,
+) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    validate_hook_program(Some(hook_program), Some(&ctx.accounts.hook_program), hook_program == crate::ID)?;
+    let extra_account_metas = extra_account_metas(&metas)?;
+
+    // space for the fixed-size extensions, the metadata initialize reallocs for the rest
+    let extensions = [ExtensionType::TransferHook, ExtensionType::MetadataPointer];
+    crate::utils::extensions_compatible(&extensions)?;
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)?;
+
+    crate::utils::validate_token_metadata(&name, &symbol, &uri)?;
+
+    // fund the metadata up front
+    let token_metadata = spl_token_metadata_interface::state::TokenMetadata {
+        update_authority: Some(mint_authority.key()).try_into()?,
+        mint: mint.key(),
+        name: name.clone(),
+        symbol: symbol.clone(),
+        uri: uri.clone(),
+        additional_metadata: vec![],
+    };
+    let metadata_space = token_metadata.tlv_size_of()?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space + metadata_space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init transfer hook extension
+    let init_transfer_hook_ix = spl_token_2022::instruction::initialize_transfer_hook(
+        &token_program.key(),
+        &mint.key(),
+        Some(&mint_authority.key()),
+        Some(hook_program),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_transfer_hook_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // init metadata pointer extension (pointing to the mint itself)
+    let init_metadata_pointer_ix = spl_token_2022::instruction::initialize_metadata_pointer(
+        &token_program.key(),
+        &mint.key(),
+        Some(&mint_authority.key()),
+        Some(mint.key()),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_pointer_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        0,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    // init token metadata
+    let init_metadata_ix = spl_token_metadata_interface::instruction::initialize(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        name,
+        symbol,
+        uri,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &init_metadata_ix,
+        &[
+            mint.to_account_info(),
+            mint_authority.to_account_info(),
+        ],
+    )?;
+
+    // the extra account meta list is only needed when the hook reads extra accounts
+    if !extra_account_metas.is_empty() {
+        let extra_account_meta_list = match ctx.accounts.extra_account_meta_list.as_ref() {
+            Some(extra_account_meta_list) => extra_account_meta_list,
+            None => return Err(crate::errors::TokenExtensionError::InvalidExtraAccountMeta.into()),
+        };
+
+        let (extra_account_meta_list_address, bump) =
+            spl_transfer_hook_interface::get_extra_account_metas_address_and_bump_seed(&mint.key(), &hook_program);
+        if extra_account_meta_list.key() != extra_account_meta_list_address {
+            return Err(ProgramError::InvalidSeeds.into());
+        }
+
+        if hook_program == crate::ID {
+            create_own_extra_account_meta_list(
+                &ctx.accounts.payer.to_account_info(),
+                &extra_account_meta_list.to_account_info(),
+                &system_program.to_account_info(),
+                rent,
+                &mint.key(),
+                bump,
+                &extra_account_metas,
+            )?;
+        } else {
+            invoke_hook_extra_account_meta_list_init(
+                &ctx.accounts.hook_program.to_account_info(),
+                &extra_account_meta_list.to_account_info(),
+                &mint.to_account_info(),
+                &mint_authority.to_account_info(),
+                &system_program.to_account_info(),
+                &extra_account_metas,
+            )?;
+        }
+    }
+
+    let registered_extensions = [
+        ExtensionType::TransferHook,
+        ExtensionType::MetadataPointer,
+        ExtensionType::TokenMetadata,
+    ];
+    ctx.accounts.registry.record(mint.key(), mint_authority.key(), &registered_extensions)?;
+
+    emit!(crate::events::MintCreated {
+        mint: mint.key(),
+        extension_types: registered_extensions.iter().map(|&ext| ext as u8).collect(),
+    });
+
     Ok(())
 }
 
+pub fn create_mint_with_self_hook(ctx: Context<CreateMintWithSelfHook>, decimals: u8) -> Result<()> {
+    let mint = &ctx.accounts.mint;
+    let mint_authority = &ctx.accounts.mint_authority;
+    let rent = &ctx.accounts.rent;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+
+    // space for mint with transfer hook extension
+    let space = ExtensionType::TransferHook.try_calculate_account_len::<spl_token_2022::state::Mint>(&[])?;
+
+    // mint account
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &ctx.accounts.payer.key(),
+        &mint.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &create_account_ix,
+        &[
+            ctx.accounts.payer.to_account_info(),
+            mint.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // init transfer hook extension with this program as the hook
+    let init_transfer_hook_ix = spl_token_2022::instruction::initialize_transfer_hook(
+        &token_program.key(),
+        &mint.key(),
+        Some(&mint_authority.key()),
+        Some(crate::ID),
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_transfer_hook_ix,
+        &[
+            mint.to_account_info(),
+        ],
+    )?;
+
+    // mint
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        None,
+        decimals,
+    )?;
+
+    anchor_lang::solana_program::program::invoke(
+        &init_mint_ix,
+        &[
+            mint.to_account_info(),
+            rent.to_account_info(),
+        ],
+    )?;
+
+    // execute resolves the stats pda from the mint, the second account of the transfer
+    let extra_account_metas = [ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal { bytes: b"transfer_stats".to_vec() },
+            Seed::AccountKey { index: 1 },
+        ],
+        false,
+        true,
+    )?];
+
+    create_own_extra_account_meta_list(
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.extra_account_meta_list.to_account_info(),
+        &system_program.to_account_info(),
+        rent,
+        &mint.key(),
+        ctx.bumps.extra_account_meta_list,
+        &extra_account_metas,
+    )?;
+
+    ctx.accounts.transfer_stats.mint = mint.key();
+
+    Ok(())
+}
+
+// called by the token program after every transfer of a mint whose hook is this program
+pub fn transfer_hook_execute(ctx: Context<TransferHookExecute>, amount: u64) -> Result<()> {
+    // only count real transfers, not direct calls to the interface instruction
+    let transferring = {
+        let source_info = ctx.accounts.source.to_account_info();
+        let source_data = source_info.try_borrow_data()?;
+        let source_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&source_data)?;
+        match source_state.get_extension::<TransferHookAccount>() {
+            Ok(transfer_hook_account) => bool::from(transfer_hook_account.transferring),
+            Err(_) => false,
+        }
+    };
+    if !transferring {
+        return Err(crate::errors::TokenExtensionError::NotTransferring.into());
+    }
+
+    let transfer_stats = &mut ctx.accounts.transfer_stats;
+    transfer_stats.count = transfer_stats
+        .count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    transfer_stats.volume = transfer_stats
+        .volume
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    Ok(())
+}
+
+// remaining_accounts may carry accounts whose data a meta reads, e.g. the source token account
+pub fn get_hook_extra_accounts<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GetHookExtraAccounts<'info>>,
+    source: Pubkey,
+    destination: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+) -> Result<Vec<ResolvedAccountMeta>> {
+    let mint = &ctx.accounts.mint;
+    let extra_account_meta_list = &ctx.accounts.extra_account_meta_list;
+
+    let hook_program_id = match transfer_hook_program_id(&mint.to_account_info())? {
+        Some(hook_program_id) => hook_program_id,
+        None => return Ok(vec![]),
+    };
+    if extra_account_meta_list.key()
+        != spl_transfer_hook_interface::get_extra_account_metas_address(&mint.key(), &hook_program_id)
+    {
+        return Err(ProgramError::InvalidSeeds.into());
+    }
+
+    let account_data = |key: &Pubkey| -> Result<Option<Vec<u8>>> {
+        if *key == mint.key() {
+            return Ok(Some(mint.to_account_info().try_borrow_data()?.to_vec()));
+        }
+        match ctx.remaining_accounts.iter().find(|account| account.key == key) {
+            Some(account) => Ok(Some(account.try_borrow_data()?.to_vec())),
+            None => Ok(None),
+        }
+    };
+
+    // execute instruction data and account list as the token program builds them
+    let mut instruction_data = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE.to_vec();
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+
+    let mut accounts: Vec<(Pubkey, Option<Vec<u8>>)> = vec![
+        (source, account_data(&source)?),
+        (mint.key(), account_data(&mint.key())?),
+        (destination, account_data(&destination)?),
+        (owner, account_data(&owner)?),
+        (extra_account_meta_list.key(), Some(extra_account_meta_list.try_borrow_data()?.to_vec())),
+    ];
+
+    let list_data = extra_account_meta_list.try_borrow_data()?;
+    let tlv_state = TlvStateBorrowed::unpack(&list_data)?;
+    let extra_account_metas = ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&tlv_state)?;
+
+    let mut resolved = Vec::with_capacity(extra_account_metas.data().len());
+    for extra_account_meta in extra_account_metas.data() {
+        let account_meta = extra_account_meta.resolve(&instruction_data, &hook_program_id, |index| {
+            accounts
+                .get(index)
+                .map(|(pubkey, data)| (pubkey, data.as_deref()))
+        })?;
+
+        let data = account_data(&account_meta.pubkey)?;
+        accounts.push((account_meta.pubkey, data));
+        resolved.push(ResolvedAccountMeta {
+            pubkey: account_meta.pubkey,
+            is_writable: account_meta.is_writable,
+            is_signer: account_meta.is_signer,
+        });
+    }
+
+    Ok(resolved)
+}
+
+fn extra_account_metas(metas: &[ExtraAccountMetaArg]) -> Result<Vec<ExtraAccountMeta>> {
+    if metas.len() > MAX_EXTRA_ACCOUNT_METAS as usize {
+        return Err(crate::errors::TokenExtensionError::InvalidExtraAccountMeta.into());
+    }
+
+    metas
+        .iter()
+        .map(ExtraAccountMetaArg::to_extra_account_meta)
+        .collect()
+}
+
+fn invoke_hook_extra_account_meta_list_init<'info>(
+    hook_program: &AccountInfo<'info>,
+    extra_account_meta_list: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    mint_authority: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    extra_account_metas: &[ExtraAccountMeta],
+) -> Result<()> {
+    let init_extra_account_metas_ix = spl_transfer_hook_interface::instruction::initialize_extra_account_meta_list(
+        &hook_program.key(),
+        &extra_account_meta_list.key(),
+        &mint.key(),
+        &mint_authority.key(),
+        extra_account_metas,
+    );
+
+    anchor_lang::solana_program::program::invoke(
+        &init_extra_account_metas_ix,
+        &[
+            extra_account_meta_list.clone(),
+            mint.clone(),
+            mint_authority.clone(),
+            system_program.clone(),
+            hook_program.clone(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn create_own_extra_account_meta_list<'info>(
+    payer: &AccountInfo<'info>,
+    extra_account_meta_list: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    rent: &Rent,
+    mint: &Pubkey,
+    bump: u8,
+    extra_account_metas: &[ExtraAccountMeta],
+) -> Result<()> {
+    let space = ExtraAccountMetaList::size_of(extra_account_metas.len())?;
+
+    let create_account_ix = anchor_lang::solana_program::system_instruction::create_account(
+        &payer.key(),
+        &extra_account_meta_list.key(),
+        rent.minimum_balance(space),
+        space as u64,
+        &crate::ID,
+    );
+
+    let bump = [bump];
+    let signer_seeds: &[&[u8]] = &[b"extra-account-metas", mint.as_ref(), &bump];
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &create_account_ix,
+        &[
+            payer.clone(),
+            extra_account_meta_list.clone(),
+            system_program.clone(),
+        ],
+        &[signer_seeds],
+    )?;
+
+    ExtraAccountMetaList::init::<ExecuteInstruction>(
+        &mut extra_account_meta_list.try_borrow_mut_data()?,
+        extra_account_metas,
+    )?;
+
+    Ok(())
+}
+
+// remaining_accounts carry the hook program, the extra account meta list and the accounts it
+// resolves to; the token program helper picks what it needs and ignores them for unhooked mints
+pub fn transfer_checked_with_hook<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TransferCheckedWithHook<'info>>,
+    amount: u64,
+) -> Result<()> {
+    spl_token_2022::onchain::invoke_transfer_checked(
+        &ctx.accounts.token_program.key(),
+        ctx.accounts.source.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.destination.to_account_info(),
+        ctx.accounts.authority.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        ctx.accounts.mint.decimals,
+        &[],
+    )?;
+
+    Ok(())
+}
+
+// a hook that is not an executable program bricks every transfer of the mint
+fn validate_hook_program(
+    program_id: Option<Pubkey>,
+    hook_program: Option<&UncheckedAccount>,
+    self_hook: bool,
+) -> Result<()> {
+    let program_id = match program_id {
+        Some(program_id) => program_id,
+        None => return Ok(()),
+    };
+
+    let hook_program = match hook_program {
+        Some(hook_program) if hook_program.key() == program_id => hook_program,
+        _ => return Err(crate::errors::TokenExtensionError::TransferHookProgramNotFound.into()),
+    };
+
+    if !hook_program.executable
+        || program_id == spl_token_2022::id()
+        || (program_id == crate::ID && !self_hook)
+    {
+        return Err(crate::errors::TokenExtensionError::TransferHookProgramNotFound.into());
+    }
+
+    Ok(())
+}
+
+fn transfer_hook_authority(mint_info: &AccountInfo) -> Result<Option<Pubkey>> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let transfer_hook = mint_state
+        .get_extension::<TransferHook>()
+        .map_err(|_| crate::errors::TokenExtensionError::InvalidExtensionType)?;
+
+    Ok(transfer_hook.authority.into())
+}
+
+fn transfer_hook_program_id(mint_info: &AccountInfo) -> Result<Option<Pubkey>> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    match mint_state.get_extension::<TransferHook>() {
+        Ok(transfer_hook) => Ok(transfer_hook.program_id.into()),
+        Err(_) => Ok(None),
+    }
+}
+
 #[derive(Accounts)]
 pub struct CreateMintWithTransferHook<'info> {
     #[account(mut)]
@@ -107,6 +785,8 @@ pub struct CreateMintWithTransferHook<'info> {
     // account initialized by the token program
     pub mint: AccountInfo<'info>,
     pub mint_authority: Signer<'info>,
+    /// CHECK: required when a hook program id is set, checked in the handler
+    pub hook_program: Option<UncheckedAccount<'info>>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token2022>,
@@ -114,8 +794,131 @@ pub struct CreateMintWithTransferHook<'info> {
 
 #[derive(Accounts)]
 pub struct UpdateTransferHookProgram<'info> {
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub authority: Signer<'info>,
+    /// CHECK: required when a hook program id is set, checked in the handler
+    pub hook_program: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct SetTransferHookAuthority<'info> {
     #[account(mut)]
     pub mint: Box<InterfaceAccount<'info, Mint>>,
     pub authority: Signer<'info>,
     pub token_program: Program<'info, Token2022>,
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        constraint = mint.mint_authority == COption::Some(mint_authority.key()) @ crate::errors::TokenExtensionError::InvalidAuthority,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_authority: Signer<'info>,
+    /// CHECK: the ["extra-account-metas", mint] pda of the hook program, checked in the handler
+    #[account(mut)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    /// CHECK: the mint's transfer hook program, checked in the handler
+    pub hook_program: UncheckedAccount<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferCheckedWithHook<'info> {
+    #[account(
+        mut,
+        constraint = source.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub source: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = destination.mint == mint.key() @ crate::errors::TokenExtensionError::MintMismatch,
+    )]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateHookedNftMint<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub mint: Signer<'info>,
+    pub mint_authority: Signer<'info>,
+    /// CHECK: the hook program, checked in the handler
+    pub hook_program: UncheckedAccount<'info>,
+    /// CHECK: the hook program's extra account meta list for the mint, checked in the handler
+    #[account(mut)]
+    pub extra_account_meta_list: Option<UncheckedAccount<'info>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TokenExtensionMint::SIZE,
+        seeds = [b"registry", mint.key().as_ref()],
+        bump,
+    )]
+    pub registry: Box<Account<'info, TokenExtensionMint>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMintWithSelfHook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    // account initialized by the token program
+    pub mint: Signer<'info>,
+    pub mint_authority: Signer<'info>,
+    /// CHECK: created here as this program's extra account meta list pda
+    #[account(mut, seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = TransferStats::SIZE,
+        seeds = [b"transfer_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub transfer_stats: Box<Account<'info, TransferStats>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct GetHookExtraAccounts<'info> {
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: the hook program's extra account meta list for the mint, checked in the handler
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+}
+
+// account order fixed by the transfer hook interface
+#[derive(Accounts)]
+pub struct TransferHookExecute<'info> {
+    #[account(token::mint = mint)]
+    pub source: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(token::mint = mint)]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: source owner or delegate, verified by the token program
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: this program's extra account meta list for the mint
+    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"transfer_stats", mint.key().as_ref()],
+        bump,
+    )]
+    pub transfer_stats: Box<Account<'info, TransferStats>>,
+}
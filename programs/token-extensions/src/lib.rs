@@ -17,16 +17,21 @@ use spl_token_2022::extension::{
     group_member_pointer::GroupMemberPointer,
     ExtensionType,
 };
+use spl_discriminator::SplDiscriminate;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
 
 declare_id!("HYThq3CykDNuJzJVu2Xx7LVhQ2xTmAVhJgULmJwu9ZNu");
 
 pub mod instructions;
 pub mod state;
 pub mod errors;
+pub mod events;
+pub mod utils;
 
 pub use instructions::*;
 pub use state::*;
 pub use errors::*;
+pub use events::*;
 
 #[program]
 pub mod token_extensions {
@@ -36,11 +41,15 @@ pub mod token_extensions {
         ctx: Context<CreateMintWithCloseAuthority>,
         close_authority: Pubkey,
         decimals: u8,
+        use_pda_close_authority: bool,
+        register: bool,
     ) -> Result<()> {
         instructions::mint_close_authority::create_mint_with_close_authority(
             ctx,
             close_authority,
             decimals,
+            use_pda_close_authority,
+            register,
         )
     }
 
@@ -48,6 +57,30 @@ pub mod token_extensions {
         instructions::mint_close_authority::close_mint(ctx)
     }
 
+    pub fn close_mint_and_registry(ctx: Context<CloseMintAndRegistry>) -> Result<()> {
+        instructions::mint_close_authority::close_mint_and_registry(ctx)
+    }
+
+    pub fn close_mint_with_pda(ctx: Context<CloseMintWithPda>) -> Result<()> {
+        instructions::mint_close_authority::close_mint_with_pda(ctx)
+    }
+
+    pub fn set_mint_close_config(ctx: Context<SetMintCloseConfig>, new_authority: Pubkey) -> Result<()> {
+        instructions::mint_close_authority::set_mint_close_config(ctx, new_authority)
+    }
+
+    pub fn set_close_authority(
+        ctx: Context<SetCloseAuthority>,
+        new_authority: Option<Pubkey>,
+        confirm_revoke: bool,
+    ) -> Result<()> {
+        instructions::mint_close_authority::set_close_authority(ctx, new_authority, confirm_revoke)
+    }
+
+    pub fn close_token_account(ctx: Context<CloseTokenAccount>) -> Result<()> {
+        instructions::mint_close_authority::close_token_account(ctx)
+    }
+
     pub fn create_mint_with_transfer_fee(
         ctx: Context<CreateMintWithTransferFee>,
         transfer_fee_config_authority: Option<Pubkey>,
@@ -55,6 +88,8 @@ pub mod token_extensions {
         transfer_fee_basis_points: u16,
         maximum_fee: u64,
         decimals: u8,
+        use_pda_withdraw_authority: bool,
+        register: bool,
     ) -> Result<()> {
         instructions::transfer_fee::create_mint_with_transfer_fee(
             ctx,
@@ -63,31 +98,151 @@ pub mod token_extensions {
             transfer_fee_basis_points,
             maximum_fee,
             decimals,
+            use_pda_withdraw_authority,
+            register,
         )
     }
 
-    pub fn transfer_with_fee(
-        ctx: Context<TransferWithFee>,
+    pub fn create_mint_with_fee_and_hook(
+        ctx: Context<CreateMintWithFeeAndHook>,
+        transfer_fee_config_authority: Option<Pubkey>,
+        withdraw_withheld_authority: Option<Pubkey>,
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64,
+        hook_authority: Option<Pubkey>,
+        hook_program_id: Option<Pubkey>,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::transfer_fee::create_mint_with_fee_and_hook(
+            ctx,
+            transfer_fee_config_authority,
+            withdraw_withheld_authority,
+            transfer_fee_basis_points,
+            maximum_fee,
+            hook_authority,
+            hook_program_id,
+            decimals,
+        )
+    }
+
+    pub fn create_compliance_mint(
+        ctx: Context<CreateComplianceMint>,
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64,
+        delegate: Pubkey,
+        freeze_authority: Option<Pubkey>,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::transfer_fee::create_compliance_mint(
+            ctx,
+            transfer_fee_basis_points,
+            maximum_fee,
+            delegate,
+            freeze_authority,
+            decimals,
+        )
+    }
+
+    pub fn transfer_with_fee<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TransferWithFee<'info>>,
+        amount: u64,
+        max_fee_tolerance: Option<u64>,
+    ) -> Result<()> {
+        instructions::transfer_fee::transfer_with_fee(ctx, amount, max_fee_tolerance)
+    }
+
+    pub fn transfer_with_fee_to_owner(
+        ctx: Context<TransferWithFeeToOwner>,
         amount: u64,
-        expected_fee: u64,
+        max_fee_tolerance: Option<u64>,
     ) -> Result<()> {
-        instructions::transfer_fee::transfer_with_fee(ctx, amount, expected_fee)
+        instructions::transfer_fee::transfer_with_fee_to_owner(ctx, amount, max_fee_tolerance)
     }
 
-    pub fn withdraw_withheld_tokens(
-        ctx: Context<WithdrawWithheldTokens>,
+    pub fn withdraw_withheld_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawWithheldTokens<'info>>,
     ) -> Result<()> {
         instructions::transfer_fee::withdraw_withheld_tokens(ctx)
     }
 
+    pub fn harvest_withheld_tokens_to_mint<'info>(
+        ctx: Context<'_, '_, 'info, 'info, HarvestWithheldTokensToMint<'info>>,
+    ) -> Result<()> {
+        instructions::transfer_fee::harvest_withheld_tokens_to_mint(ctx)
+    }
+
+    pub fn withdraw_withheld_tokens_from_mint(
+        ctx: Context<WithdrawWithheldFromMint>,
+    ) -> Result<()> {
+        instructions::transfer_fee::withdraw_withheld_tokens_from_mint(ctx)
+    }
+
+    pub fn configure_fee_escrow(ctx: Context<ConfigureFeeEscrow>) -> Result<()> {
+        instructions::transfer_fee::configure_fee_escrow(ctx)
+    }
+
+    pub fn withdraw_withheld_to_escrow<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawWithheldToEscrow<'info>>,
+    ) -> Result<()> {
+        instructions::transfer_fee::withdraw_withheld_to_escrow(ctx)
+    }
+
+    pub fn init_fee_stats(ctx: Context<InitFeeStats>) -> Result<()> {
+        instructions::transfer_fee::init_fee_stats(ctx)
+    }
+
+    pub fn close_fee_stats(ctx: Context<CloseFeeStats>) -> Result<()> {
+        instructions::transfer_fee::close_fee_stats(ctx)
+    }
+
+    pub fn set_transfer_fee(
+        ctx: Context<SetTransferFee>,
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64,
+    ) -> Result<()> {
+        instructions::transfer_fee::set_transfer_fee(ctx, transfer_fee_basis_points, maximum_fee)
+    }
+
+    pub fn rotate_transfer_fee_authority(
+        ctx: Context<RotateTransferFeeAuthority>,
+        authority_type: TransferFeeAuthorityType,
+        new_authority: Option<Pubkey>,
+        confirm_renounce: bool,
+    ) -> Result<()> {
+        instructions::transfer_fee::rotate_transfer_fee_authority(
+            ctx,
+            authority_type,
+            new_authority,
+            confirm_renounce,
+        )
+    }
+
+    pub fn get_transfer_fee(ctx: Context<GetTransferFee>, amount: u64) -> Result<u64> {
+        instructions::transfer_fee::get_transfer_fee(ctx, amount)
+    }
+
+    pub fn get_fee_schedule(ctx: Context<GetFeeSchedule>) -> Result<FeeSchedule> {
+        instructions::transfer_fee::get_fee_schedule(ctx)
+    }
+
+    pub fn get_withheld_amount(ctx: Context<GetWithheldAmount>) -> Result<u64> {
+        instructions::transfer_fee::get_withheld_amount(ctx)
+    }
+
+    pub fn close_fee_account(ctx: Context<CloseFeeAccount>) -> Result<()> {
+        instructions::transfer_fee::close_fee_account(ctx)
+    }
+
     pub fn create_mint_with_default_state(
         ctx: Context<CreateMintWithDefaultState>,
-        default_state: u8,
+        default_state: DefaultState,
+        freeze_authority: Option<Pubkey>,
         decimals: u8,
     ) -> Result<()> {
         instructions::default_account_state::create_mint_with_default_state(
             ctx,
             default_state,
+            freeze_authority,
             decimals,
         )
     }
@@ -95,29 +250,178 @@ pub mod token_extensions {
     // default account state extension
     pub fn update_default_account_state(
         ctx: Context<UpdateDefaultAccountState>,
-        default_state: u8,
+        default_state: DefaultState,
     ) -> Result<()> {
         instructions::default_account_state::update_default_account_state(ctx, default_state)
     }
 
+    pub fn init_state_history(ctx: Context<InitStateHistory>) -> Result<()> {
+        instructions::default_account_state::init_state_history(ctx)
+    }
+
+    pub fn close_state_history(ctx: Context<CloseStateHistory>) -> Result<()> {
+        instructions::default_account_state::close_state_history(ctx)
+    }
+
+    pub fn thaw_account(ctx: Context<ThawAccount>) -> Result<()> {
+        instructions::default_account_state::thaw_account(ctx)
+    }
+
+    pub fn freeze_account(ctx: Context<FreezeAccount>) -> Result<()> {
+        instructions::default_account_state::freeze_account(ctx)
+    }
+
+    pub fn batch_set_account_state<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchSetAccountState<'info>>,
+        freeze: bool,
+    ) -> Result<u32> {
+        instructions::default_account_state::batch_set_account_state(ctx, freeze)
+    }
+
+    pub fn create_account_for_default_state_mint(
+        ctx: Context<CreateAccountForDefaultStateMint>,
+    ) -> Result<()> {
+        instructions::default_account_state::create_account_for_default_state_mint(ctx)
+    }
+
+    pub fn create_allowlisted_mint(
+        ctx: Context<CreateAllowlistedMint>,
+        list_authority: Pubkey,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::default_account_state::create_allowlisted_mint(ctx, list_authority, decimals)
+    }
+
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlist>, owner: Pubkey) -> Result<()> {
+        instructions::default_account_state::add_to_allowlist(ctx, owner)
+    }
+
+    pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+        instructions::default_account_state::remove_from_allowlist(ctx)
+    }
+
+    pub fn thaw_if_allowlisted(ctx: Context<ThawIfAllowlisted>) -> Result<()> {
+        instructions::default_account_state::thaw_if_allowlisted(ctx)
+    }
+
     pub fn create_account_with_immutable_owner(
         ctx: Context<CreateAccountWithImmutableOwner>,
     ) -> Result<()> {
         instructions::immutable_owner::create_account_with_immutable_owner(ctx)
     }
 
+    pub fn create_accounts_with_immutable_owner_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateAccountsWithImmutableOwnerBatch<'info>>,
+        owners: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::immutable_owner::create_accounts_with_immutable_owner_batch(ctx, owners)
+    }
+
+    pub fn create_account_with_immutable_owner_sponsored(
+        ctx: Context<CreateAccountWithImmutableOwnerSponsored>,
+    ) -> Result<()> {
+        instructions::immutable_owner::create_account_with_immutable_owner_sponsored(ctx)
+    }
+
+    pub fn fund_sponsor(ctx: Context<FundSponsor>, amount: u64) -> Result<()> {
+        instructions::immutable_owner::fund_sponsor(ctx, amount)
+    }
+
+    pub fn defund_sponsor(ctx: Context<DefundSponsor>, amount: u64) -> Result<()> {
+        instructions::immutable_owner::defund_sponsor(ctx, amount)
+    }
+
+    // the owner's enable_cpi_guard must follow, see utils::require_cpi_guard_enable_follows
+    pub fn create_locked_account(ctx: Context<CreateLockedAccount>) -> Result<()> {
+        instructions::immutable_owner::create_locked_account(ctx)
+    }
+
+    pub fn create_exchange_deposit_account(ctx: Context<CreateExchangeDepositAccount>) -> Result<()> {
+        instructions::immutable_owner::create_exchange_deposit_account(ctx)
+    }
+
+    pub fn create_ata_with_extensions(ctx: Context<CreateAtaWithExtensions>) -> Result<Pubkey> {
+        instructions::immutable_owner::create_ata_with_extensions(ctx)
+    }
+
+    pub fn verify_account_extensions(
+        ctx: Context<VerifyAccountExtensions>,
+        expected: Vec<u8>,
+    ) -> Result<()> {
+        instructions::immutable_owner::verify_account_extensions(ctx, expected)
+    }
+
+    pub fn is_owner_immutable(ctx: Context<IsOwnerImmutable>) -> Result<bool> {
+        instructions::immutable_owner::is_owner_immutable(ctx)
+    }
+
     pub fn create_non_transferable_mint(
         ctx: Context<CreateNonTransferableMint>,
         decimals: u8,
+        freeze_authority: Option<Pubkey>,
+        label: Option<String>,
+    ) -> Result<()> {
+        instructions::non_transferable::create_non_transferable_mint(ctx, decimals, freeze_authority, label)
+    }
+
+    pub fn close_soulbound_account(ctx: Context<CloseSoulboundAccount>) -> Result<()> {
+        instructions::non_transferable::close_soulbound_account(ctx)
+    }
+
+    pub fn revoke_and_close_soulbound_account(
+        ctx: Context<RevokeAndCloseSoulboundAccount>,
+    ) -> Result<()> {
+        instructions::non_transferable::revoke_and_close_soulbound_account(ctx)
+    }
+
+    pub fn init_issuer_registry(ctx: Context<InitIssuerRegistry>, capacity: u16) -> Result<()> {
+        instructions::non_transferable::init_issuer_registry(ctx, capacity)
+    }
+
+    pub fn grow_issuer_registry(ctx: Context<GrowIssuerRegistry>, capacity: u16) -> Result<()> {
+        instructions::non_transferable::grow_issuer_registry(ctx, capacity)
+    }
+
+    pub fn get_sbt_label(ctx: Context<GetSbtLabel>) -> Result<String> {
+        instructions::non_transferable::get_sbt_label(ctx)
+    }
+
+    pub fn create_revocable_soulbound_mint(
+        ctx: Context<CreateRevocableSoulboundMint>,
+        permanent_delegate: Pubkey,
+        decimals: u8,
     ) -> Result<()> {
-        instructions::non_transferable::create_non_transferable_mint(ctx, decimals)
+        instructions::non_transferable::create_revocable_soulbound_mint(ctx, permanent_delegate, decimals)
+    }
+
+    pub fn revoke_soulbound(ctx: Context<RevokeSoulbound>, amount: u64) -> Result<()> {
+        instructions::non_transferable::revoke_soulbound(ctx, amount)
+    }
+
+    pub fn create_soulbound_nft(
+        ctx: Context<CreateSoulboundNft>,
+        name: String,
+        symbol: String,
+        uri: String,
+        mint_to_recipient: bool,
+    ) -> Result<()> {
+        instructions::non_transferable::create_soulbound_nft(ctx, name, symbol, uri, mint_to_recipient)
+    }
+
+    pub fn mint_soulbound_to(ctx: Context<MintSoulboundTo>, amount: u64) -> Result<()> {
+        instructions::non_transferable::mint_soulbound_to(ctx, amount)
+    }
+
+    pub fn burn_soulbound(ctx: Context<BurnSoulbound>, amount: u64) -> Result<()> {
+        instructions::non_transferable::burn_soulbound(ctx, amount)
     }
 
     // required memo extension
     pub fn create_account_with_required_memo(
         ctx: Context<CreateAccountWithRequiredMemo>,
+        require_incoming: bool,
     ) -> Result<()> {
-        instructions::required_memo::create_account_with_required_memo(ctx)
+        instructions::required_memo::create_account_with_required_memo(ctx, require_incoming)
     }
 
     pub fn enable_required_memo_transfers(
@@ -126,12 +430,52 @@ pub mod token_extensions {
         instructions::required_memo::enable_required_memo_transfers(ctx)
     }
 
+    pub fn reallocate_and_enable_memo(ctx: Context<ReallocateAndEnableMemo>) -> Result<()> {
+        instructions::required_memo::reallocate_and_enable_memo(ctx)
+    }
+
     pub fn disable_required_memo_transfers(
         ctx: Context<DisableRequiredMemoTransfers>,
     ) -> Result<()> {
         instructions::required_memo::disable_required_memo_transfers(ctx)
     }
 
+    /// Enables required memo transfers on a token account whose owner is a PDA of the calling
+    /// program. The caller signs for the owner through `invoke_signed`, e.g. via
+    /// `token_extensions::cpi::enable_required_memo_transfers_cpi` with the `cpi` feature.
+    pub fn enable_required_memo_transfers_cpi(ctx: Context<RequiredMemoTransfersCpi>) -> Result<()> {
+        instructions::required_memo::enable_required_memo_transfers_cpi(ctx)
+    }
+
+    /// Disables required memo transfers on a token account whose owner is a PDA of the calling
+    /// program, see `enable_required_memo_transfers_cpi`.
+    pub fn disable_required_memo_transfers_cpi(ctx: Context<RequiredMemoTransfersCpi>) -> Result<()> {
+        instructions::required_memo::disable_required_memo_transfers_cpi(ctx)
+    }
+
+    pub fn batch_set_required_memo<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchSetRequiredMemo<'info>>,
+        enable: bool,
+    ) -> Result<()> {
+        instructions::required_memo::batch_set_required_memo(ctx, enable)
+    }
+
+    pub fn require_memo_status(ctx: Context<RequireMemoStatus>, expected: bool) -> Result<()> {
+        instructions::required_memo::require_memo_status(ctx, expected)
+    }
+
+    pub fn get_memo_requirements(ctx: Context<GetMemoRequirements>) -> Result<bool> {
+        instructions::required_memo::get_memo_requirements(ctx)
+    }
+
+    pub fn set_memo_policy(ctx: Context<SetMemoPolicy>, rule: MemoPolicyRule) -> Result<()> {
+        instructions::required_memo::set_memo_policy(ctx, rule)
+    }
+
+    pub fn transfer_with_memo(ctx: Context<TransferWithMemo>, amount: u64, memo: String) -> Result<()> {
+        instructions::required_memo::transfer_with_memo(ctx, amount, memo)
+    }
+
     // interest bearing extension
     pub fn create_interest_bearing_mint(
         ctx: Context<CreateInterestBearingMint>,
@@ -147,6 +491,46 @@ pub mod token_extensions {
         )
     }
 
+    pub fn create_interest_bearing_mint_with_metadata(
+        ctx: Context<CreateInterestBearingMintWithMetadata>,
+        rate_authority: Option<Pubkey>,
+        rate: i16,
+        name: String,
+        symbol: String,
+        uri: String,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::interest_bearing::create_interest_bearing_mint_with_metadata(
+            ctx,
+            rate_authority,
+            rate,
+            name,
+            symbol,
+            uri,
+            decimals,
+        )
+    }
+
+    pub fn create_yield_mint_with_fee(
+        ctx: Context<CreateYieldMintWithFee>,
+        rate: i16,
+        rate_authority: Option<Pubkey>,
+        fee_bps: u16,
+        max_fee: u64,
+        fee_authorities: FeeAuthorities,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::interest_bearing::create_yield_mint_with_fee(
+            ctx,
+            rate,
+            rate_authority,
+            fee_bps,
+            max_fee,
+            fee_authorities,
+            decimals,
+        )
+    }
+
     pub fn update_interest_rate(
         ctx: Context<UpdateInterestRate>,
         rate: i16,
@@ -154,6 +538,59 @@ pub mod token_extensions {
         instructions::interest_bearing::update_interest_rate(ctx, rate)
     }
 
+    pub fn configure_rate_limits(
+        ctx: Context<ConfigureRateLimits>,
+        min_rate: i16,
+        max_rate: i16,
+        max_change_per_update: u16,
+    ) -> Result<()> {
+        instructions::interest_bearing::configure_rate_limits(ctx, min_rate, max_rate, max_change_per_update)
+    }
+
+    pub fn set_rate_authority(
+        ctx: Context<SetRateAuthority>,
+        new_authority: Option<Pubkey>,
+        confirm_renounce: bool,
+    ) -> Result<()> {
+        instructions::interest_bearing::set_rate_authority(ctx, new_authority, confirm_renounce)
+    }
+
+    pub fn create_interest_bearing_mint_with_timelock(
+        ctx: Context<CreateInterestBearingMintWithTimelock>,
+        rate: i16,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::interest_bearing::create_interest_bearing_mint_with_timelock(ctx, rate, decimals)
+    }
+
+    pub fn propose_interest_rate(
+        ctx: Context<ProposeInterestRate>,
+        rate: i16,
+        effective_after: i64,
+    ) -> Result<()> {
+        instructions::interest_bearing::propose_interest_rate(ctx, rate, effective_after)
+    }
+
+    pub fn execute_interest_rate(ctx: Context<ExecuteInterestRate>) -> Result<()> {
+        instructions::interest_bearing::execute_interest_rate(ctx)
+    }
+
+    pub fn cancel_interest_rate(ctx: Context<CancelInterestRate>) -> Result<()> {
+        instructions::interest_bearing::cancel_interest_rate(ctx)
+    }
+
+    pub fn get_interest_config(ctx: Context<GetInterestConfig>) -> Result<InterestConfigView> {
+        instructions::interest_bearing::get_interest_config(ctx)
+    }
+
+    pub fn amount_to_ui_amount(ctx: Context<ConvertInterestAmount>, amount: u64) -> Result<()> {
+        instructions::interest_bearing::amount_to_ui_amount(ctx, amount)
+    }
+
+    pub fn ui_amount_to_amount(ctx: Context<ConvertInterestAmount>, ui_amount: String) -> Result<()> {
+        instructions::interest_bearing::ui_amount_to_amount(ctx, ui_amount)
+    }
+
     // permanent delegate extension
     pub fn create_mint_with_permanent_delegate(
         ctx: Context<CreateMintWithPermanentDelegate>,
@@ -167,7 +604,57 @@ pub mod token_extensions {
         )
     }
 
+    pub fn get_permanent_delegate(ctx: Context<InspectMint>) -> Result<Option<Pubkey>> {
+        instructions::permanent_delegate::get_permanent_delegate(ctx)
+    }
+
+    pub fn has_risky_extensions(ctx: Context<InspectMint>) -> Result<u8> {
+        instructions::permanent_delegate::has_risky_extensions(ctx)
+    }
+
+    pub fn create_permissioned_mint(
+        ctx: Context<CreatePermissionedMint>,
+        delegate: Pubkey,
+        freeze_authority: Pubkey,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::permanent_delegate::create_permissioned_mint(ctx, delegate, freeze_authority, decimals)
+    }
+
+    pub fn init_clawback_log(ctx: Context<InitClawbackLog>) -> Result<()> {
+        instructions::permanent_delegate::init_clawback_log(ctx)
+    }
+
+    pub fn delegated_transfer(ctx: Context<DelegatedTransfer>, amount: u64, reason: String) -> Result<()> {
+        instructions::permanent_delegate::delegated_transfer(ctx, amount, reason)
+    }
+
+    pub fn delegated_burn(ctx: Context<DelegatedBurn>, amount: u64, reason: String) -> Result<()> {
+        instructions::permanent_delegate::delegated_burn(ctx, amount, reason)
+    }
+
+    pub fn create_mint_with_pda_delegate(
+        ctx: Context<CreateMintWithPdaDelegate>,
+        authority: Pubkey,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::permanent_delegate::create_mint_with_pda_delegate(ctx, authority, decimals)
+    }
+
+    pub fn set_delegate_config(ctx: Context<SetDelegateConfig>, new_authority: Pubkey) -> Result<()> {
+        instructions::permanent_delegate::set_delegate_config(ctx, new_authority)
+    }
+
+    pub fn governed_clawback(ctx: Context<GovernedClawback>, amount: u64) -> Result<()> {
+        instructions::permanent_delegate::governed_clawback(ctx, amount)
+    }
+
     // cpi guard extension
+    // the owner's enable_cpi_guard must follow, see utils::require_cpi_guard_enable_follows
+    pub fn create_account_with_cpi_guard(ctx: Context<CreateAccountWithCpiGuard>) -> Result<()> {
+        instructions::cpi_guard::create_account_with_cpi_guard(ctx)
+    }
+
     pub fn enable_cpi_guard(ctx: Context<EnableCpiGuard>) -> Result<()> {
         instructions::cpi_guard::enable_cpi_guard(ctx)
     }
@@ -176,26 +663,100 @@ pub mod token_extensions {
         instructions::cpi_guard::disable_cpi_guard(ctx)
     }
 
+    // enable_cpi_guard must follow unless already on, see utils::require_cpi_guard_enable_follows
+    pub fn reallocate_for_cpi_guard(ctx: Context<ReallocateForCpiGuard>) -> Result<()> {
+        instructions::cpi_guard::reallocate_for_cpi_guard(ctx)
+    }
+
+    pub fn batch_set_cpi_guard<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchSetCpiGuard<'info>>,
+        enable: bool,
+    ) -> Result<()> {
+        instructions::cpi_guard::batch_set_cpi_guard(ctx, enable)
+    }
+
+    pub fn get_cpi_guard_status(ctx: Context<CpiGuardStatus>) -> Result<bool> {
+        instructions::cpi_guard::get_cpi_guard_status(ctx)
+    }
+
+    pub fn assert_cpi_guard(ctx: Context<CpiGuardStatus>, expected: bool) -> Result<()> {
+        instructions::cpi_guard::assert_cpi_guard(ctx, expected)
+    }
+
     //transfer hook extension
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+        metas: Vec<ExtraAccountMetaArg>,
+    ) -> Result<()> {
+        instructions::transfer_hook::initialize_extra_account_meta_list(ctx, metas)
+    }
+
+    pub fn create_hooked_nft_mint(
+        ctx: Context<CreateHookedNftMint>,
+        hook_program: Pubkey,
+        name: String,
+        symbol: String,
+        uri: String,
+        metas: Vec<ExtraAccountMetaArg>,
+    ) -> Result<()> {
+        instructions::transfer_hook::create_hooked_nft_mint(ctx, hook_program, name, symbol, uri, metas)
+    }
+
+    pub fn create_mint_with_self_hook(ctx: Context<CreateMintWithSelfHook>, decimals: u8) -> Result<()> {
+        instructions::transfer_hook::create_mint_with_self_hook(ctx, decimals)
+    }
+
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook_execute(ctx: Context<TransferHookExecute>, amount: u64) -> Result<()> {
+        instructions::transfer_hook::transfer_hook_execute(ctx, amount)
+    }
+
+    pub fn get_hook_extra_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetHookExtraAccounts<'info>>,
+        source: Pubkey,
+        destination: Pubkey,
+        owner: Pubkey,
+        amount: u64,
+    ) -> Result<Vec<ResolvedAccountMeta>> {
+        instructions::transfer_hook::get_hook_extra_accounts(ctx, source, destination, owner, amount)
+    }
+
+    pub fn transfer_checked_with_hook<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TransferCheckedWithHook<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::transfer_hook::transfer_checked_with_hook(ctx, amount)
+    }
+
     pub fn create_mint_with_transfer_hook(
         ctx: Context<CreateMintWithTransferHook>,
         authority: Option<Pubkey>,
         program_id: Option<Pubkey>,
         decimals: u8,
+        self_hook: bool,
     ) -> Result<()> {
         instructions::transfer_hook::create_mint_with_transfer_hook(
             ctx,
             authority,
             program_id,
             decimals,
+            self_hook,
         )
     }
 
     pub fn update_transfer_hook_program(
         ctx: Context<UpdateTransferHookProgram>,
         program_id: Option<Pubkey>,
+        self_hook: bool,
+    ) -> Result<()> {
+        instructions::transfer_hook::update_transfer_hook_program(ctx, program_id, self_hook)
+    }
+
+    pub fn set_transfer_hook_authority(
+        ctx: Context<SetTransferHookAuthority>,
+        new_authority: Option<Pubkey>,
     ) -> Result<()> {
-        instructions::transfer_hook::update_transfer_hook_program(ctx, program_id)
+        instructions::transfer_hook::set_transfer_hook_authority(ctx, new_authority)
     }
 
     // metadata pointer
@@ -213,6 +774,37 @@ pub mod token_extensions {
         )
     }
 
+    pub fn create_mint_with_external_metadata(
+        ctx: Context<CreateMintWithExternalMetadata>,
+        authority: Option<Pubkey>,
+        metadata_address: Pubkey,
+        enforce_metaplex: bool,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::metadata_pointer::create_mint_with_external_metadata(
+            ctx,
+            authority,
+            metadata_address,
+            enforce_metaplex,
+            decimals,
+        )
+    }
+
+    pub fn update_metadata_pointer(
+        ctx: Context<UpdateMetadataPointer>,
+        new_metadata_address: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::metadata_pointer::update_metadata_pointer(ctx, new_metadata_address)
+    }
+
+    pub fn set_metadata_pointer_authority(
+        ctx: Context<SetMetadataPointerAuthority>,
+        new_authority: Option<Pubkey>,
+        confirm_renounce: bool,
+    ) -> Result<()> {
+        instructions::metadata_pointer::set_metadata_pointer_authority(ctx, new_authority, confirm_renounce)
+    }
+
     // metadata extension
     pub fn create_mint_with_metadata(
         ctx: Context<CreateMintWithMetadata>,
@@ -220,8 +812,39 @@ pub mod token_extensions {
         symbol: String,
         uri: String,
         decimals: u8,
+        register: bool,
+        immutable: bool,
+    ) -> Result<()> {
+        instructions::metadata::create_mint_with_metadata(ctx, name, symbol, uri, decimals, register, immutable)
+    }
+
+    pub fn create_closeable_mint_with_metadata(
+        ctx: Context<CreateCloseableMintWithMetadata>,
+        close_authority: Pubkey,
+        name: String,
+        symbol: String,
+        uri: String,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::metadata::create_closeable_mint_with_metadata(
+            ctx,
+            close_authority,
+            name,
+            symbol,
+            uri,
+            decimals,
+        )
+    }
+
+    pub fn create_nft(
+        ctx: Context<CreateNft>,
+        name: String,
+        symbol: String,
+        uri: String,
+        recipient: Pubkey,
+        keep_mint_authority: bool,
     ) -> Result<()> {
-        instructions::metadata::create_mint_with_metadata(ctx, name, symbol, uri, decimals)
+        instructions::metadata::create_nft(ctx, name, symbol, uri, recipient, keep_mint_authority)
     }
 
     pub fn update_metadata_field(
@@ -232,6 +855,46 @@ pub mod token_extensions {
         instructions::metadata::update_metadata_field(ctx, field, value)
     }
 
+    pub fn migrate_metadata_field(ctx: Context<MigrateMetadataField>, key: String) -> Result<()> {
+        instructions::metadata::migrate_metadata_field(ctx, key)
+    }
+
+    pub fn init_metadata_mirror(ctx: Context<InitMetadataMirror>) -> Result<()> {
+        instructions::metadata::init_metadata_mirror(ctx)
+    }
+
+    pub fn close_metadata_mirror(ctx: Context<CloseMetadataMirror>) -> Result<()> {
+        instructions::metadata::close_metadata_mirror(ctx)
+    }
+
+    pub fn reclaim_metadata_rent(ctx: Context<ReclaimMetadataRent>) -> Result<()> {
+        instructions::metadata::reclaim_metadata_rent(ctx)
+    }
+
+    pub fn set_metadata_update_authority(
+        ctx: Context<SetMetadataUpdateAuthority>,
+        new_authority: Option<Pubkey>,
+        confirm_renounce: bool,
+    ) -> Result<()> {
+        instructions::metadata::set_metadata_update_authority(ctx, new_authority, confirm_renounce)
+    }
+
+    pub fn emit_metadata(
+        ctx: Context<EmitMetadata>,
+        start: Option<u64>,
+        end: Option<u64>,
+    ) -> Result<()> {
+        instructions::metadata::emit_metadata(ctx, start, end)
+    }
+
+    pub fn remove_metadata_field(
+        ctx: Context<RemoveMetadataField>,
+        key: String,
+        idempotent: bool,
+    ) -> Result<()> {
+        instructions::metadata::remove_metadata_field(ctx, key, idempotent)
+    }
+
     // group pointer extensionn
     pub fn create_mint_with_group_pointer(
         ctx: Context<CreateMintWithGroupPointer>,
@@ -247,6 +910,13 @@ pub mod token_extensions {
         )
     }
 
+    pub fn update_group_pointer(
+        ctx: Context<UpdateGroupPointer>,
+        new_group_address: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::group_pointer::update_group_pointer(ctx, new_group_address)
+    }
+
     // group extension
     pub fn create_mint_with_group(
         ctx: Context<CreateMintWithGroup>,
@@ -257,6 +927,16 @@ pub mod token_extensions {
         instructions::group::create_mint_with_group(ctx, update_authority, max_size, decimals)
     }
 
+    pub fn create_collection(
+        ctx: Context<CreateCollection>,
+        name: String,
+        symbol: String,
+        uri: String,
+        max_size: u32,
+    ) -> Result<()> {
+        instructions::group::create_collection(ctx, name, symbol, uri, max_size)
+    }
+
     pub fn update_group_max_size(
         ctx: Context<UpdateGroupMaxSize>,
         max_size: u32,
@@ -264,6 +944,14 @@ pub mod token_extensions {
         instructions::group::update_group_max_size(ctx, max_size)
     }
 
+    pub fn set_group_update_authority(
+        ctx: Context<SetGroupUpdateAuthority>,
+        new_authority: Option<Pubkey>,
+        confirm_renounce: bool,
+    ) -> Result<()> {
+        instructions::group::set_group_update_authority(ctx, new_authority, confirm_renounce)
+    }
+
     // member pointer
     pub fn create_mint_with_member_pointer(
         ctx: Context<CreateMintWithMemberPointer>,
@@ -279,13 +967,19 @@ pub mod token_extensions {
         )
     }
 
+    pub fn update_member_pointer(
+        ctx: Context<UpdateMemberPointer>,
+        new_member_address: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::member_pointer::update_member_pointer(ctx, new_member_address)
+    }
+
     // member extension
     pub fn create_mint_with_member(
         ctx: Context<CreateMintWithMember>,
-        group: Pubkey,
         decimals: u8,
     ) -> Result<()> {
-        instructions::member::create_mint_with_member(ctx, group, decimals)
+        instructions::member::create_mint_with_member(ctx, decimals)
     }
 
     // scaled ui amount
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use spl_token_2022::extension::ExtensionType;
+use spl_token_group_interface::state::{TokenGroup, TokenGroupMember};
+use spl_token_metadata_interface::state::TokenMetadata;
 
 #[account]
 #[derive(Default)]
@@ -7,13 +10,22 @@ pub struct TokenExtensionMint {
     pub authority: Pubkey,
     pub extension_types: Vec<u8>,
     pub created_at: i64,
+    pub metaplex_metadata: bool,
 }
 
 impl TokenExtensionMint {
 
     // size for Token-2022 mint structure
-    // structure: discriminator(8) + mint(32) + authority(32) + extension_types_vec(4+10) + created_at(8)
-    pub const SIZE: usize = 8 + 32 + 32 + 4 + 10 + 8;
+    // structure: discriminator(8) + mint(32) + authority(32) + extension_types_vec(4+10) + created_at(8) + metaplex_metadata(1)
+    pub const SIZE: usize = 8 + 32 + 32 + 4 + 10 + 8 + 1;
+
+    pub fn record(&mut self, mint: Pubkey, authority: Pubkey, extension_types: &[ExtensionType]) -> Result<()> {
+        self.mint = mint;
+        self.authority = authority;
+        self.extension_types = extension_types.iter().map(|&ext| ext as u8).collect();
+        self.created_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
 }
 
 #[account]
@@ -25,13 +37,48 @@ pub struct MetadataAccount {
     pub uri: String,
     pub update_authority: Pubkey,
     pub additional_metadata: Vec<(String, String)>,
+    pub is_mutable: bool,
 }
 
 impl MetadataAccount {
 
+    // limits enforced on token metadata created through this program
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_SYMBOL_LEN: usize = 10;
+    pub const MAX_URI_LEN: usize = 200;
+    pub const URI_SCHEMES: [&'static str; 3] = ["https://", "ipfs://", "ar://"];
+
     // this is the max size for the metadata account based on Token-2022 specification
     // structure: discriminator(8) + mint(32) + update_authority(32) + key(1) + primary_sale_happened(1) + is_mutable(1) + edition_nonce(1) + data fields
-pub const MAX_SIZE: usize = 8 + 32 + 32 + 1 + 1 + 1 + 1 + (4 + 32) + (4 + 10) + (4 + 200) + 2 + (4 + 10 * (4 + 32 + 4 + 100));}
+pub const MAX_SIZE: usize = 8 + 32 + 32 + 1 + 1 + 1 + 1 + (4 + 32) + (4 + 10) + (4 + 200) + 2 + (4 + 10 * (4 + 32 + 4 + 100));
+
+    // structure: discriminator(8) + mint(32) + name(4+n) + symbol(4+s) + uri(4+u) + update_authority(32) + additional_metadata_vec(4 + pairs) + is_mutable(1)
+    pub fn space(token_metadata: &TokenMetadata) -> usize {
+        8 + 32
+            + 4 + token_metadata.name.len()
+            + 4 + token_metadata.symbol.len()
+            + 4 + token_metadata.uri.len()
+            + 32
+            + 4
+            + token_metadata
+                .additional_metadata
+                .iter()
+                .map(|(key, value)| 4 + key.len() + 4 + value.len())
+                .sum::<usize>()
+            + 1
+    }
+
+    pub fn mirror(&mut self, token_metadata: &TokenMetadata) {
+        self.mint = token_metadata.mint;
+        self.name = token_metadata.name.clone();
+        self.symbol = token_metadata.symbol.clone();
+        self.uri = token_metadata.uri.clone();
+        let update_authority: Option<Pubkey> = token_metadata.update_authority.into();
+        self.update_authority = update_authority.unwrap_or_default();
+        self.additional_metadata = token_metadata.additional_metadata.clone();
+        self.is_mutable = update_authority.is_some();
+    }
+}
 
 #[account]
 #[derive(Default)]
@@ -44,6 +91,16 @@ pub struct GroupAccount {
 
 impl GroupAccount {
     pub const SIZE: usize = 8 + 32 + (1 + 32) + 4 + 4;
+
+    pub fn mirror(&mut self, token_group: &TokenGroup) -> Result<()> {
+        self.mint = token_group.mint;
+        self.update_authority = token_group.update_authority.into();
+        self.size = u32::try_from(u64::from(token_group.size))
+            .map_err(|_| crate::errors::TokenExtensionError::GroupSizeLimitExceeded)?;
+        self.max_size = u32::try_from(u64::from(token_group.max_size))
+            .map_err(|_| crate::errors::TokenExtensionError::GroupSizeLimitExceeded)?;
+        Ok(())
+    }
 }
 
 #[account]
@@ -56,4 +113,328 @@ pub struct MemberAccount {
 
 impl MemberAccount {
     pub const SIZE: usize = 8 + 32 + 32 + 4;
-} 
\ No newline at end of file
+
+    pub fn mirror(&mut self, token_group_member: &TokenGroupMember) -> Result<()> {
+        self.mint = token_group_member.mint;
+        self.group = token_group_member.group;
+        self.member_number = u32::try_from(u64::from(token_group_member.member_number))
+            .map_err(|_| crate::errors::TokenExtensionError::GroupSizeLimitExceeded)?;
+        Ok(())
+    }
+} 
+
+#[account]
+#[derive(Default)]
+pub struct FeeStats {
+    pub mint: Pubkey,
+    pub total_fees_collected: u64,
+    pub last_withdraw_ts: i64,
+}
+
+impl FeeStats {
+    pub const SIZE: usize = 8 + 32 + 8 + 8;
+
+    pub fn record_withdrawal(&mut self, amount: u64, timestamp: i64) -> Result<()> {
+        self.total_fees_collected = self
+            .total_fees_collected
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.last_withdraw_ts = timestamp;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct FeeEscrow {
+    pub mint: Pubkey,
+    pub escrow: Pubkey,
+}
+
+impl FeeEscrow {
+    pub const SIZE: usize = 8 + 32 + 32;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct FeeSchedule {
+    pub older_transfer_fee_basis_points: u16,
+    pub older_maximum_fee: u64,
+    pub older_epoch: u64,
+    pub newer_transfer_fee_basis_points: u16,
+    pub newer_maximum_fee: u64,
+    pub newer_epoch: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct InterestConfigView {
+    pub current_rate: i16,
+    pub pre_update_average_rate: i16,
+    pub initialization_timestamp: i64,
+    pub last_update_timestamp: i64,
+    pub rate_authority: Option<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ResolvedAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_writable: bool,
+    pub is_signer: bool,
+}
+
+#[account]
+#[derive(Default)]
+pub struct AllowlistConfig {
+    pub mint: Pubkey,
+    pub list_authority: Pubkey,
+}
+
+impl AllowlistConfig {
+    pub const SIZE: usize = 8 + 32 + 32;
+}
+
+#[account]
+#[derive(Default)]
+pub struct AllowlistEntry {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub added_at: i64,
+}
+
+impl AllowlistEntry {
+    pub const SIZE: usize = 8 + 32 + 32 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct StateChange {
+    pub timestamp: i64,
+    pub old_state: u8,
+    pub new_state: u8,
+}
+
+#[account]
+#[derive(Default)]
+pub struct StateHistory {
+    pub mint: Pubkey,
+    pub entries: Vec<StateChange>,
+}
+
+impl StateHistory {
+    pub const MAX_ENTRIES: usize = 16;
+
+    // structure: discriminator(8) + mint(32) + entries_vec(4 + 16 * (timestamp(8) + old_state(1) + new_state(1)))
+    pub const SIZE: usize = 8 + 32 + 4 + Self::MAX_ENTRIES * (8 + 1 + 1);
+
+    pub fn record(&mut self, timestamp: i64, old_state: u8, new_state: u8) {
+        // drop the oldest entry once full
+        if self.entries.len() >= Self::MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(StateChange {
+            timestamp,
+            old_state,
+            new_state,
+        });
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct AccountRegistry {
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub extension_types: Vec<u8>,
+    pub verified_at: i64,
+    pub memo_required_incoming: bool,
+}
+
+impl AccountRegistry {
+    pub const MAX_EXTENSIONS: usize = 16;
+
+    // structure: discriminator(8) + token_account(32) + mint(32) + extension_types_vec(4+16) + verified_at(8) + memo_required_incoming(1)
+    pub const SIZE: usize = 8 + 32 + 32 + 4 + Self::MAX_EXTENSIONS + 8 + 1;
+}
+
+#[account]
+#[derive(Default)]
+pub struct Sponsor {
+    pub owner: Pubkey,
+    pub funder: Pubkey,
+}
+
+impl Sponsor {
+    // structure: discriminator(8) + owner(32) + funder(32)
+    pub const SIZE: usize = 8 + 32 + 32;
+}
+
+#[account]
+#[derive(Default)]
+pub struct SbtMeta {
+    pub mint: Pubkey,
+    pub payer: Pubkey,
+    pub issuer: Pubkey,
+    pub label: String,
+    pub created_at: i64,
+}
+
+impl SbtMeta {
+    pub const MAX_LABEL_LEN: usize = 64;
+
+    // structure: discriminator(8) + mint(32) + payer(32) + issuer(32) + label(4+64) + created_at(8)
+    pub const SIZE: usize = 8 + 32 + 32 + 32 + 4 + Self::MAX_LABEL_LEN + 8;
+}
+
+#[account]
+#[derive(Default)]
+pub struct IssuerRegistry {
+    pub authority: Pubkey,
+    pub capacity: u16,
+    pub mints: Vec<Pubkey>,
+}
+
+impl IssuerRegistry {
+    // structure: discriminator(8) + authority(32) + capacity(2) + mints_vec(4 + 32 * capacity)
+    pub fn space(capacity: u16) -> usize {
+        8 + 32 + 2 + 4 + 32 * capacity as usize
+    }
+
+    pub fn append(&mut self, mint: Pubkey) -> Result<()> {
+        if self.mints.len() >= self.capacity as usize {
+            return Err(crate::errors::TokenExtensionError::IssuerRegistryFull.into());
+        }
+        self.mints.push(mint);
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Default)]
+pub enum MemoPolicyRule {
+    #[default]
+    AnyUtf8,
+    Numeric { min_len: u16, max_len: u16 },
+    Prefix { prefix: String },
+}
+
+#[account]
+#[derive(Default)]
+pub struct MemoPolicy {
+    pub mint: Pubkey,
+    pub rule: MemoPolicyRule,
+}
+
+impl MemoPolicy {
+    pub const MAX_PREFIX_LEN: usize = 32;
+
+    // structure: discriminator(8) + mint(32) + rule_variant(1) + largest variant, prefix(4+32)
+    pub const SIZE: usize = 8 + 32 + 1 + 4 + Self::MAX_PREFIX_LEN;
+
+    pub fn allows(&self, memo: &str) -> bool {
+        match &self.rule {
+            MemoPolicyRule::AnyUtf8 => true,
+            MemoPolicyRule::Numeric { min_len, max_len } => {
+                memo.len() >= *min_len as usize
+                    && memo.len() <= *max_len as usize
+                    && memo.bytes().all(|byte| byte.is_ascii_digit())
+            }
+            MemoPolicyRule::Prefix { prefix } => memo.starts_with(prefix.as_str()),
+        }
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct RateLimits {
+    pub mint: Pubkey,
+    pub min_rate: i16,
+    pub max_rate: i16,
+    pub max_change_per_update: u16,
+}
+
+impl RateLimits {
+    // structure: discriminator(8) + mint(32) + min_rate(2) + max_rate(2) + max_change_per_update(2)
+    pub const SIZE: usize = 8 + 32 + 2 + 2 + 2;
+}
+
+#[account]
+#[derive(Default)]
+pub struct PendingRate {
+    pub mint: Pubkey,
+    pub proposer: Pubkey,
+    pub rate: i16,
+    pub effective_after: i64,
+}
+
+impl PendingRate {
+    // structure: discriminator(8) + mint(32) + proposer(32) + rate(2) + effective_after(8)
+    pub const SIZE: usize = 8 + 32 + 32 + 2 + 8;
+}
+
+#[account]
+#[derive(Default)]
+pub struct DelegateConfig {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl DelegateConfig {
+    // structure: discriminator(8) + mint(32) + authority(32)
+    pub const SIZE: usize = 8 + 32 + 32;
+}
+
+#[account]
+#[derive(Default)]
+pub struct MintCloseConfig {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl MintCloseConfig {
+    // structure: discriminator(8) + mint(32) + authority(32)
+    pub const SIZE: usize = 8 + 32 + 32;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ClawbackEntry {
+    pub timestamp: i64,
+    pub source: Pubkey,
+    pub amount: u64,
+    pub reason_hash: [u8; 32],
+}
+
+#[account]
+#[derive(Default)]
+pub struct ClawbackLog {
+    pub mint: Pubkey,
+    pub entries: Vec<ClawbackEntry>,
+}
+
+impl ClawbackLog {
+    pub const MAX_ENTRIES: usize = 64;
+    // structure per entry: timestamp(8) + source(32) + amount(8) + reason_hash(32)
+    pub const ENTRY_SIZE: usize = 8 + 32 + 8 + 32;
+
+    // structure: discriminator(8) + mint(32) + entries_vec(4 + entry * entries), grown one entry at a time
+    pub fn space(entries: usize) -> usize {
+        8 + 32 + 4 + Self::ENTRY_SIZE * entries.min(Self::MAX_ENTRIES)
+    }
+
+    pub fn record(&mut self, entry: ClawbackEntry) {
+        // drop the oldest entry once full
+        if self.entries.len() >= Self::MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(entry);
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct TransferStats {
+    pub mint: Pubkey,
+    pub count: u64,
+    pub volume: u64,
+}
+
+impl TransferStats {
+    // structure: discriminator(8) + mint(32) + count(8) + volume(8)
+    pub const SIZE: usize = 8 + 32 + 8 + 8;
+}
@@ -0,0 +1,305 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use spl_token_2022::extension::{
+    memo_transfer::RequiredMemoTransfers,
+    BaseStateWithExtensions,
+    ExtensionType,
+    StateWithExtensions,
+};
+
+// extension pairs that make no sense on the same mint
+const CONFLICTING_EXTENSIONS: [(ExtensionType, ExtensionType); 3] = [
+    (ExtensionType::NonTransferable, ExtensionType::TransferFeeConfig),
+    (ExtensionType::NonTransferable, ExtensionType::TransferHook),
+    (ExtensionType::NonTransferable, ExtensionType::ConfidentialTransferMint),
+];
+
+pub fn extensions_compatible(extensions: &[ExtensionType]) -> Result<()> {
+    for (first, second) in CONFLICTING_EXTENSIONS.iter() {
+        if extensions.contains(first) && extensions.contains(second) {
+            msg!("incompatible extensions: {:?} with {:?}", first, second);
+            return Err(crate::errors::TokenExtensionError::InvalidExtensionType.into());
+        }
+    }
+
+    Ok(())
+}
+
+// the token program refuses to toggle the cpi guard under cpi, so the owner enables it in the
+// same transaction: the next top-level instruction must be the token program's enable_cpi_guard
+// for the account, signed by the owner, e.g. [create_locked_account, enable_cpi_guard].
+// fails with CpiGuardRequiresTopLevel under cpi and CpiGuardEnableMissing when the enable is missing
+pub fn require_cpi_guard_enable_follows(
+    instructions_sysvar: &AccountInfo,
+    token_program: &Pubkey,
+    token_account: &Pubkey,
+    owner: &Pubkey,
+) -> Result<()> {
+    if get_stack_height() != TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Err(crate::errors::TokenExtensionError::CpiGuardRequiresTopLevel.into());
+    }
+
+    let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+    if !cpi_guard_toggle_at(instructions_sysvar, current_index + 1, true, token_program, token_account, owner)? {
+        return Err(crate::errors::TokenExtensionError::CpiGuardEnableMissing.into());
+    }
+
+    Ok(())
+}
+
+// whether the transaction instruction at index toggles the cpi guard of token_account, signed by owner
+pub fn cpi_guard_toggle_at(
+    instructions_sysvar: &AccountInfo,
+    index: usize,
+    enable: bool,
+    token_program: &Pubkey,
+    token_account: &Pubkey,
+    owner: &Pubkey,
+) -> Result<bool> {
+    let toggle_ix = if enable {
+        spl_token_2022::instruction::enable_cpi_guard(token_program, token_account, owner, &[])?
+    } else {
+        spl_token_2022::instruction::disable_cpi_guard(token_program, token_account, owner, &[])?
+    };
+
+    let ix = match load_instruction_at_checked(index, instructions_sysvar) {
+        Ok(ix) => ix,
+        Err(_) => return Ok(false),
+    };
+
+    Ok(ix.program_id == toggle_ix.program_id
+        && ix.data == toggle_ix.data
+        && ix.accounts.first().map(|meta| meta.pubkey) == Some(*token_account)
+        && ix.accounts.get(1).map(|meta| meta.pubkey) == Some(*owner))
+}
+
+// i16::MIN has no positive counterpart and breaks the accrual math, so it is never accepted
+pub fn validate_interest_rate(
+    current_rate: i16,
+    new_rate: i16,
+    rate_limits: Option<&crate::state::RateLimits>,
+) -> Result<()> {
+    if new_rate == i16::MIN {
+        return Err(crate::errors::TokenExtensionError::InvalidInterestRate.into());
+    }
+
+    if let Some(limits) = rate_limits {
+        let change = (new_rate as i32 - current_rate as i32).unsigned_abs();
+        if new_rate < limits.min_rate
+            || new_rate > limits.max_rate
+            || change > limits.max_change_per_update as u32
+        {
+            msg!(
+                "rate {} outside [{}, {}] or change {} above {}",
+                new_rate,
+                limits.min_rate,
+                limits.max_rate,
+                change,
+                limits.max_change_per_update,
+            );
+            return Err(crate::errors::TokenExtensionError::InvalidInterestRate.into());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_token_metadata(name: &str, symbol: &str, uri: &str) -> Result<()> {
+    use crate::state::MetadataAccount;
+
+    for (field, value, max_len) in [
+        ("name", name, MetadataAccount::MAX_NAME_LEN),
+        ("symbol", symbol, MetadataAccount::MAX_SYMBOL_LEN),
+        ("uri", uri, MetadataAccount::MAX_URI_LEN),
+    ] {
+        let len = value.chars().count();
+        if len == 0 || len > max_len {
+            msg!("metadata {} must be 1 to {} characters, got {}", field, max_len, len);
+            return Err(crate::errors::TokenExtensionError::InvalidMetadata.into());
+        }
+    }
+
+    if !MetadataAccount::URI_SCHEMES.iter().any(|scheme| uri.starts_with(scheme)) {
+        msg!("metadata uri must start with one of {:?}", MetadataAccount::URI_SCHEMES);
+        return Err(crate::errors::TokenExtensionError::InvalidMetadata.into());
+    }
+
+    Ok(())
+}
+
+// the token program only accepts a memo that is the sibling right before a cpi transfer,
+// so the memo of the top-level instruction right before ours is re-issued through the memo program
+pub fn forward_required_memo<'info>(
+    destination: &AccountInfo<'info>,
+    instructions_sysvar: &AccountInfo<'info>,
+    memo_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let memo_required = {
+        let destination_data = destination.try_borrow_data()?;
+        let destination_state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&destination_data)?;
+        match destination_state.get_extension::<RequiredMemoTransfers>() {
+            Ok(memo_transfer) => bool::from(memo_transfer.require_incoming_transfer_memos),
+            Err(_) => false,
+        }
+    };
+
+    if !memo_required {
+        return Ok(());
+    }
+
+    let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+    let prior_memo = match current_index.checked_sub(1) {
+        Some(index) => {
+            let instruction = load_instruction_at_checked(index, instructions_sysvar)?;
+            (instruction.program_id == spl_memo::id()).then_some(instruction.data)
+        }
+        None => None,
+    };
+
+    let memo = match prior_memo {
+        Some(memo) => memo,
+        None => return Err(crate::errors::TokenExtensionError::MemoRequiredForTransfer.into()),
+    };
+
+    let memo_ix = spl_memo::build_memo(&memo, &[]);
+
+    anchor_lang::solana_program::program::invoke(
+        &memo_ix,
+        &[
+            memo_program.clone(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_every_conflicting_pair() {
+        for (first, second) in CONFLICTING_EXTENSIONS.iter() {
+            assert_eq!(
+                extensions_compatible(&[*first, *second]),
+                Err(crate::errors::TokenExtensionError::InvalidExtensionType.into()),
+            );
+            assert_eq!(
+                extensions_compatible(&[*second, ExtensionType::MetadataPointer, *first]),
+                Err(crate::errors::TokenExtensionError::InvalidExtensionType.into()),
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_compatible_extensions() {
+        assert!(extensions_compatible(&[]).is_ok());
+        assert!(extensions_compatible(&[ExtensionType::NonTransferable]).is_ok());
+        assert!(extensions_compatible(&[
+            ExtensionType::NonTransferable,
+            ExtensionType::PermanentDelegate,
+            ExtensionType::MetadataPointer,
+        ])
+        .is_ok());
+        assert!(extensions_compatible(&[
+            ExtensionType::TransferFeeConfig,
+            ExtensionType::TransferHook,
+        ])
+        .is_ok());
+    }
+
+    fn rate_limits() -> crate::state::RateLimits {
+        crate::state::RateLimits {
+            mint: Pubkey::default(),
+            min_rate: -100,
+            max_rate: 500,
+            max_change_per_update: 50,
+        }
+    }
+
+    #[test]
+    fn accepts_rate_within_limits() {
+        let limits = rate_limits();
+        assert!(validate_interest_rate(100, 150, Some(&limits)).is_ok());
+        assert!(validate_interest_rate(-60, -100, Some(&limits)).is_ok());
+        assert!(validate_interest_rate(i16::MAX, i16::MIN + 1, None).is_ok());
+    }
+
+    #[test]
+    fn rejects_rate_outside_limits() {
+        let limits = rate_limits();
+        assert_eq!(
+            validate_interest_rate(480, 510, Some(&limits)),
+            Err(crate::errors::TokenExtensionError::InvalidInterestRate.into()),
+        );
+        assert_eq!(
+            validate_interest_rate(-90, -101, Some(&limits)),
+            Err(crate::errors::TokenExtensionError::InvalidInterestRate.into()),
+        );
+        assert_eq!(
+            validate_interest_rate(0, i16::MIN, None),
+            Err(crate::errors::TokenExtensionError::InvalidInterestRate.into()),
+        );
+    }
+
+    #[test]
+    fn rejects_oversized_rate_change() {
+        let limits = rate_limits();
+        assert_eq!(
+            validate_interest_rate(100, 151, Some(&limits)),
+            Err(crate::errors::TokenExtensionError::InvalidInterestRate.into()),
+        );
+        assert_eq!(
+            validate_interest_rate(0, -51, Some(&limits)),
+            Err(crate::errors::TokenExtensionError::InvalidInterestRate.into()),
+        );
+    }
+
+    #[test]
+    fn accepts_metadata_within_bounds() {
+        assert!(validate_token_metadata("Token", "TKN", "https://example.com/token.json").is_ok());
+        assert!(validate_token_metadata(&"n".repeat(32), &"s".repeat(10), "ipfs://cid").is_ok());
+        assert!(validate_token_metadata("Token", "TKN", &format!("ar://{}", "u".repeat(195))).is_ok());
+    }
+
+    #[test]
+    fn rejects_empty_metadata_fields() {
+        for (name, symbol, uri) in [
+            ("", "TKN", "https://example.com"),
+            ("Token", "", "https://example.com"),
+            ("Token", "TKN", ""),
+        ] {
+            assert_eq!(
+                validate_token_metadata(name, symbol, uri),
+                Err(crate::errors::TokenExtensionError::InvalidMetadata.into()),
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_metadata_fields() {
+        assert_eq!(
+            validate_token_metadata(&"n".repeat(33), "TKN", "https://example.com"),
+            Err(crate::errors::TokenExtensionError::InvalidMetadata.into()),
+        );
+        assert_eq!(
+            validate_token_metadata("Token", &"s".repeat(11), "https://example.com"),
+            Err(crate::errors::TokenExtensionError::InvalidMetadata.into()),
+        );
+        assert_eq!(
+            validate_token_metadata("Token", "TKN", &format!("https://{}", "u".repeat(193))),
+            Err(crate::errors::TokenExtensionError::InvalidMetadata.into()),
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_uri_scheme() {
+        for uri in ["http://example.com", "ftp://example.com", "example.com"] {
+            assert_eq!(
+                validate_token_metadata("Token", "TKN", uri),
+                Err(crate::errors::TokenExtensionError::InvalidMetadata.into()),
+            );
+        }
+    }
+}
@@ -0,0 +1,367 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  SYSVAR_RENT_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createDisableCpiGuardInstruction,
+  createEnableCpiGuardInstruction,
+  createMint,
+  disableCpiGuard,
+  getAccount,
+  getCpiGuard,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { TestCpiCaller } from "../target/types/test_cpi_caller";
+import { connection, eventsFrom, expectError, payer, program, provider } from "./helpers";
+
+describe("CPI Guard", () => {
+  let mint: PublicKey;
+
+  before(async () => {
+    mint = await createMint(connection, payer, payer.publicKey, null, 6, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+  });
+
+  async function cpiGuardOf(tokenAccount: PublicKey): Promise<boolean | undefined> {
+    const account = await getAccount(connection, tokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    return getCpiGuard(account)?.lockCpi;
+  }
+
+  async function guardedAccount(owner: Keypair): Promise<PublicKey> {
+    const tokenAccount = Keypair.generate();
+
+    const transaction = new Transaction().add(
+      await program.methods
+        .createAccountWithCpiGuard()
+        .accountsPartial({
+          payer: payer.publicKey,
+          tokenAccount: tokenAccount.publicKey,
+          mint,
+          owner: owner.publicKey,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .instruction(),
+      createEnableCpiGuardInstruction(tokenAccount.publicKey, owner.publicKey, [], TOKEN_2022_PROGRAM_ID),
+    );
+    await provider.sendAndConfirm(transaction, [tokenAccount, owner]);
+
+    return tokenAccount.publicKey;
+  }
+
+  describe("create_account_with_cpi_guard", () => {
+    const caller = anchor.workspace.TestCpiCaller as Program<TestCpiCaller>;
+
+    function accountsFor(tokenAccount: Keypair, owner: Keypair) {
+      return {
+        payer: payer.publicKey,
+        tokenAccount: tokenAccount.publicKey,
+        mint,
+        owner: owner.publicKey,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      };
+    }
+
+    it("creates a guarded account when the owner's enable follows", async () => {
+      const tokenAccount = Keypair.generate();
+      const owner = Keypair.generate();
+
+      const transaction = new Transaction().add(
+        await program.methods.createAccountWithCpiGuard().accountsPartial(accountsFor(tokenAccount, owner)).instruction(),
+        createEnableCpiGuardInstruction(tokenAccount.publicKey, owner.publicKey, [], TOKEN_2022_PROGRAM_ID),
+      );
+      await provider.sendAndConfirm(transaction, [tokenAccount, owner]);
+
+      const account = await getAccount(connection, tokenAccount.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(account.owner.toBase58()).to.equal(owner.publicKey.toBase58());
+      expect(account.mint.toBase58()).to.equal(mint.toBase58());
+      expect(await cpiGuardOf(tokenAccount.publicKey)).to.equal(true);
+    });
+
+    it("fails when the enable does not follow", async () => {
+      const tokenAccount = Keypair.generate();
+      const owner = Keypair.generate();
+
+      await expectError(
+        program.methods.createAccountWithCpiGuard().accountsPartial(accountsFor(tokenAccount, owner)).signers([tokenAccount, owner]).rpc(),
+        "CpiGuardEnableMissing",
+      );
+      expect(await connection.getAccountInfo(tokenAccount.publicKey)).to.equal(null);
+    });
+
+    it("fails with CpiGuardRequiresTopLevel when invoked through CPI", async () => {
+      const tokenAccount = Keypair.generate();
+      const owner = Keypair.generate();
+
+      const nested = await caller.methods
+        .nestCreateAccountWithCpiGuard()
+        .accountsPartial({ ...accountsFor(tokenAccount, owner), tokenExtensionsProgram: program.programId })
+        .instruction();
+
+      const transaction = new Transaction().add(
+        nested,
+        createEnableCpiGuardInstruction(tokenAccount.publicKey, owner.publicKey, [], TOKEN_2022_PROGRAM_ID),
+      );
+      await expectError(provider.sendAndConfirm(transaction, [tokenAccount, owner]), "CpiGuardRequiresTopLevel");
+      expect(await connection.getAccountInfo(tokenAccount.publicKey)).to.equal(null);
+    });
+  });
+
+  describe("get_cpi_guard_status and assert_cpi_guard", () => {
+    function statusOf(tokenAccount: PublicKey): Promise<boolean> {
+      return program.methods.getCpiGuardStatus().accountsPartial({ tokenAccount }).view();
+    }
+
+    function assertCpiGuard(tokenAccount: PublicKey, expected: boolean) {
+      return program.methods.assertCpiGuard(expected).accountsPartial({ tokenAccount }).rpc();
+    }
+
+    it("reports a guarded account as enabled", async () => {
+      const tokenAccount = await guardedAccount(Keypair.generate());
+
+      expect(await statusOf(tokenAccount)).to.equal(true);
+      await assertCpiGuard(tokenAccount, true);
+      await expectError(assertCpiGuard(tokenAccount, false), "CpiGuardEnabled");
+    });
+
+    it("reports an account with the guard disabled as disabled", async () => {
+      const owner = Keypair.generate();
+      const tokenAccount = await guardedAccount(owner);
+      await disableCpiGuard(connection, payer, tokenAccount, owner, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+      expect(await statusOf(tokenAccount)).to.equal(false);
+      await assertCpiGuard(tokenAccount, false);
+      await expectError(assertCpiGuard(tokenAccount, true), "CpiGuardDisabled");
+    });
+
+    it("treats an account without the extension as disabled", async () => {
+      const tokenAccount = await createAssociatedTokenAccount(connection, payer, mint, Keypair.generate().publicKey, undefined, TOKEN_2022_PROGRAM_ID);
+
+      expect(await statusOf(tokenAccount)).to.equal(false);
+      await assertCpiGuard(tokenAccount, false);
+      await expectError(assertCpiGuard(tokenAccount, true), "CpiGuardDisabled");
+    });
+  });
+
+  describe("reallocate_for_cpi_guard", () => {
+    async function reallocate(tokenAccount: PublicKey, owner: Keypair, enable: boolean): Promise<string> {
+      const transaction = new Transaction().add(
+        await program.methods
+          .reallocateForCpiGuard()
+          .accountsPartial({
+            payer: payer.publicKey,
+            tokenAccount,
+            owner: owner.publicKey,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .instruction(),
+      );
+      if (enable) {
+        transaction.add(createEnableCpiGuardInstruction(tokenAccount, owner.publicKey, [], TOKEN_2022_PROGRAM_ID));
+      }
+      return provider.sendAndConfirm(transaction, [owner], { commitment: "confirmed" });
+    }
+
+    async function reallocatedEvent(sig: string) {
+      const event = (await eventsFrom(sig)).find((e) => e.name === "cpiGuardReallocated");
+      expect(event).to.not.equal(undefined);
+      return event!.data;
+    }
+
+    it("adds the extension to a fresh ATA and enables the guard", async () => {
+      const owner = Keypair.generate();
+      const tokenAccount = await createAssociatedTokenAccount(connection, payer, mint, owner.publicKey, undefined, TOKEN_2022_PROGRAM_ID);
+      const sizeBefore = (await connection.getAccountInfo(tokenAccount))!.data.length;
+
+      const event = await reallocatedEvent(await reallocate(tokenAccount, owner, true));
+
+      expect((await connection.getAccountInfo(tokenAccount))!.data.length).to.be.greaterThan(sizeBefore);
+      expect(await cpiGuardOf(tokenAccount)).to.equal(true);
+      expect(event.tokenAccount.toBase58()).to.equal(tokenAccount.toBase58());
+      expect(event.reallocated).to.equal(true);
+      expect(event.alreadyEnabled).to.equal(false);
+    });
+
+    it("only enables the guard on an account that already has the space", async () => {
+      const owner = Keypair.generate();
+      const tokenAccount = await guardedAccount(owner);
+      await disableCpiGuard(connection, payer, tokenAccount, owner, [], undefined, TOKEN_2022_PROGRAM_ID);
+      const sizeBefore = (await connection.getAccountInfo(tokenAccount))!.data.length;
+
+      const event = await reallocatedEvent(await reallocate(tokenAccount, owner, true));
+
+      expect((await connection.getAccountInfo(tokenAccount))!.data.length).to.equal(sizeBefore);
+      expect(await cpiGuardOf(tokenAccount)).to.equal(true);
+      expect(event.reallocated).to.equal(false);
+      expect(event.alreadyEnabled).to.equal(false);
+    });
+
+    it("leaves an already guarded account alone without a following enable", async () => {
+      const owner = Keypair.generate();
+      const tokenAccount = await guardedAccount(owner);
+
+      const event = await reallocatedEvent(await reallocate(tokenAccount, owner, false));
+
+      expect(await cpiGuardOf(tokenAccount)).to.equal(true);
+      expect(event.reallocated).to.equal(false);
+      expect(event.alreadyEnabled).to.equal(true);
+    });
+
+    it("fails when the enable does not follow an unguarded account", async () => {
+      const owner = Keypair.generate();
+      const tokenAccount = await createAssociatedTokenAccount(connection, payer, mint, owner.publicKey, undefined, TOKEN_2022_PROGRAM_ID);
+
+      await expectError(reallocate(tokenAccount, owner, false), "CpiGuardEnableMissing");
+      expect(await cpiGuardOf(tokenAccount)).to.equal(undefined);
+    });
+  });
+
+  describe("batch_set_cpi_guard", () => {
+    const caller = anchor.workspace.TestCpiCaller as Program<TestCpiCaller>;
+
+    function batchIx(owner: Keypair, enable: boolean, tokenAccounts: PublicKey[]) {
+      return program.methods
+        .batchSetCpiGuard(enable)
+        .accountsPartial({
+          owner: owner.publicKey,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts(tokenAccounts.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+        .instruction();
+    }
+
+    function toggleIx(tokenAccount: PublicKey, owner: Keypair, enable: boolean) {
+      return enable
+        ? createEnableCpiGuardInstruction(tokenAccount, owner.publicKey, [], TOKEN_2022_PROGRAM_ID)
+        : createDisableCpiGuardInstruction(tokenAccount, owner.publicKey, [], TOKEN_2022_PROGRAM_ID);
+    }
+
+    async function unguardedAccount(owner: Keypair): Promise<PublicKey> {
+      const tokenAccount = await guardedAccount(owner);
+      await disableCpiGuard(connection, payer, tokenAccount, owner, [], undefined, TOKEN_2022_PROGRAM_ID);
+      return tokenAccount;
+    }
+
+    it("toggles the changed accounts and skips the ones already in place", async () => {
+      const owner = Keypair.generate();
+      const a = await unguardedAccount(owner);
+      const b = await guardedAccount(owner);
+      const c = await unguardedAccount(owner);
+
+      // b is already guarded, so only a and c get a following toggle
+      const transaction = new Transaction().add(
+        await batchIx(owner, true, [a, b, c]),
+        toggleIx(a, owner, true),
+        toggleIx(c, owner, true),
+      );
+      const sig = await provider.sendAndConfirm(transaction, [owner], { commitment: "confirmed" });
+
+      for (const tokenAccount of [a, b, c]) {
+        expect(await cpiGuardOf(tokenAccount)).to.equal(true);
+      }
+
+      const event = (await eventsFrom(sig)).find((e) => e.name === "cpiGuardBatchUpdated");
+      expect(event!.data.owner.toBase58()).to.equal(owner.publicKey.toBase58());
+      expect(event!.data.enabled).to.equal(true);
+      expect(event!.data.changed).to.equal(2);
+      expect(event!.data.skipped).to.equal(1);
+    });
+
+    it("disables every account of the owner", async () => {
+      const owner = Keypair.generate();
+      const a = await guardedAccount(owner);
+      const b = await guardedAccount(owner);
+
+      const transaction = new Transaction().add(
+        await batchIx(owner, false, [a, b]),
+        toggleIx(a, owner, false),
+        toggleIx(b, owner, false),
+      );
+      await provider.sendAndConfirm(transaction, [owner]);
+
+      expect(await cpiGuardOf(a)).to.equal(false);
+      expect(await cpiGuardOf(b)).to.equal(false);
+    });
+
+    it("fails the whole batch on an account of another owner", async () => {
+      const owner = Keypair.generate();
+      const stranger = Keypair.generate();
+      const mine = await unguardedAccount(owner);
+      const theirs = await unguardedAccount(stranger);
+
+      const transaction = new Transaction().add(
+        await batchIx(owner, true, [mine, theirs]),
+        toggleIx(mine, owner, true),
+        toggleIx(theirs, stranger, true),
+      );
+      await expectError(provider.sendAndConfirm(transaction, [owner, stranger]), "InvalidAuthority");
+      expect(await cpiGuardOf(mine)).to.equal(false);
+    });
+
+    it("fails when a changed account has no following toggle", async () => {
+      const owner = Keypair.generate();
+      const a = await unguardedAccount(owner);
+      const b = await unguardedAccount(owner);
+
+      const transaction = new Transaction().add(await batchIx(owner, true, [a, b]), toggleIx(a, owner, true));
+      await expectError(provider.sendAndConfirm(transaction, [owner]), "CpiGuardToggleMissing");
+      expect(await cpiGuardOf(a)).to.equal(false);
+    });
+
+    it("rejects an account listed twice", async () => {
+      const owner = Keypair.generate();
+      const a = await unguardedAccount(owner);
+
+      const transaction = new Transaction().add(
+        await batchIx(owner, true, [a, a]),
+        toggleIx(a, owner, true),
+        toggleIx(a, owner, true),
+      );
+      await expectError(provider.sendAndConfirm(transaction, [owner]), "DuplicateBatchAccount");
+      expect(await cpiGuardOf(a)).to.equal(false);
+    });
+
+    it("rejects more than MAX_BATCH_CPI_GUARD_ACCOUNTS accounts", async () => {
+      const owner = Keypair.generate();
+      const tokenAccount = await guardedAccount(owner);
+
+      const transaction = new Transaction().add(await batchIx(owner, true, Array(17).fill(tokenAccount)));
+      await expectError(provider.sendAndConfirm(transaction, [owner]), "InvalidBatchSize");
+    });
+
+    it("fails with CpiGuardRequiresTopLevel when invoked through CPI", async () => {
+      const owner = Keypair.generate();
+      const tokenAccount = await unguardedAccount(owner);
+
+      const nested = await caller.methods
+        .nestBatchSetCpiGuard(true)
+        .accountsPartial({
+          owner: owner.publicKey,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenExtensionsProgram: program.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts([{ pubkey: tokenAccount, isSigner: false, isWritable: true }])
+        .instruction();
+
+      const transaction = new Transaction().add(nested, toggleIx(tokenAccount, owner, true));
+      await expectError(provider.sendAndConfirm(transaction, [owner]), "CpiGuardRequiresTopLevel");
+      expect(await cpiGuardOf(tokenAccount)).to.equal(false);
+    });
+  });
+});
@@ -0,0 +1,183 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  AccountState,
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createMint,
+  getAccount,
+  getDefaultAccountState,
+  getMint,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { connection, expectError, payer, pda, program } from "./helpers";
+
+describe("Default Account State", () => {
+  async function createDefaultStateMint(defaultState: object, freezeAuthority: PublicKey = payer.publicKey): Promise<PublicKey> {
+    const mint = Keypair.generate();
+
+    await program.methods
+      .createMintWithDefaultState(defaultState as any, freezeAuthority, 6)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+
+    return mint.publicKey;
+  }
+
+  async function defaultStateOf(mint: PublicKey): Promise<AccountState> {
+    const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+    return getDefaultAccountState(mintInfo)!.state;
+  }
+
+  describe("DefaultState argument", () => {
+    it("exposes only Initialized and Frozen in the IDL", () => {
+      const defaultState = program.idl.types!.find((t) => t.name.toLowerCase() === "defaultstate");
+      expect(defaultState).to.not.equal(undefined);
+
+      const variants = (defaultState!.type as { kind: "enum"; variants: { name: string }[] }).variants;
+      expect(variants.map((v) => v.name.toLowerCase())).to.deep.equal(["initialized", "frozen"]);
+    });
+
+    it("round-trips Frozen and Initialized through create and update", async () => {
+      const mint = await createDefaultStateMint({ frozen: {} });
+      expect(await defaultStateOf(mint)).to.equal(AccountState.Frozen);
+
+      await program.methods
+        .updateDefaultAccountState({ initialized: {} })
+        .accountsPartial({
+          mint,
+          freezeAuthority: payer.publicKey,
+          stateHistory: null,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect(await defaultStateOf(mint)).to.equal(AccountState.Initialized);
+    });
+
+    it("cannot encode an Uninitialized default state", async () => {
+      let rejected = false;
+      try {
+        await createDefaultStateMint({ uninitialized: {} });
+      } catch {
+        rejected = true;
+      }
+      expect(rejected).to.equal(true);
+    });
+  });
+
+  describe("update_default_account_state checks", () => {
+    function updateDefaultState(mint: PublicKey, freezeAuthority: Keypair = payer) {
+      return program.methods
+        .updateDefaultAccountState({ frozen: {} })
+        .accountsPartial({
+          mint,
+          freezeAuthority: freezeAuthority.publicKey,
+          stateHistory: null,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers(freezeAuthority === payer ? [] : [freezeAuthority])
+        .rpc();
+    }
+
+    it("rejects a signer that is not the mint's freeze authority", async () => {
+      const mint = await createDefaultStateMint({ initialized: {} });
+
+      await expectError(updateDefaultState(mint, Keypair.generate()), "InvalidAuthority");
+      expect(await defaultStateOf(mint)).to.equal(AccountState.Initialized);
+    });
+
+    it("rejects a plain mint without the DefaultAccountState extension", async () => {
+      const mint = await createMint(connection, payer, payer.publicKey, payer.publicKey, 6, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+
+      await expectError(updateDefaultState(mint), "InvalidExtensionType");
+    });
+  });
+
+  describe("allowlisted thaw", () => {
+    function allowlistEntryFor(mint: PublicKey, owner: PublicKey): PublicKey {
+      return pda([Buffer.from("allowlist"), mint.toBuffer(), owner.toBuffer()]);
+    }
+
+    async function createAllowlistedMint(): Promise<PublicKey> {
+      const mint = Keypair.generate();
+      await program.methods
+        .createAllowlistedMint(payer.publicKey, 6)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          allowlistConfig: pda([Buffer.from("allowlist_config"), mint.publicKey.toBuffer()]),
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+      return mint.publicKey;
+    }
+
+    function addToAllowlist(mint: PublicKey, owner: PublicKey) {
+      return program.methods
+        .addToAllowlist(owner)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint,
+          listAuthority: payer.publicKey,
+          allowlistConfig: pda([Buffer.from("allowlist_config"), mint.toBuffer()]),
+          allowlistEntry: allowlistEntryFor(mint, owner),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    function thawIfAllowlisted(mint: PublicKey, tokenAccount: PublicKey, owner: PublicKey) {
+      return program.methods
+        .thawIfAllowlisted()
+        .accountsPartial({
+          tokenAccount,
+          mint,
+          allowlistEntry: allowlistEntryFor(mint, owner),
+          freezeAuthority: pda([Buffer.from("freeze_authority"), mint.toBuffer()]),
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+    }
+
+    async function frozenAccount(mint: PublicKey, owner: PublicKey): Promise<PublicKey> {
+      const tokenAccount = await createAssociatedTokenAccount(
+        connection, payer, mint, owner, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+      expect((await getAccount(connection, tokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).isFrozen).to.equal(true);
+      return tokenAccount;
+    }
+
+    it("thaws the account of an allowlisted owner", async () => {
+      const mint = await createAllowlistedMint();
+      const owner = Keypair.generate().publicKey;
+      const tokenAccount = await frozenAccount(mint, owner);
+
+      await addToAllowlist(mint, owner);
+      await thawIfAllowlisted(mint, tokenAccount, owner);
+
+      expect((await getAccount(connection, tokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).isFrozen).to.equal(false);
+    });
+
+    it("leaves the account of an owner missing from the allowlist frozen", async () => {
+      const mint = await createAllowlistedMint();
+      const owner = Keypair.generate().publicKey;
+      const tokenAccount = await frozenAccount(mint, owner);
+
+      await expectError(thawIfAllowlisted(mint, tokenAccount, owner), "AccountNotInitialized");
+      expect((await getAccount(connection, tokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).isFrozen).to.equal(true);
+    });
+  });
+});
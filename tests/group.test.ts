@@ -0,0 +1,373 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  TOKEN_2022_PROGRAM_ID,
+  createMint,
+  getGroupMemberPointerState,
+  getGroupPointerState,
+  getMint,
+  getTokenGroupState,
+  getTokenMetadata,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { connection, eventsFrom, expectError, payer, pda, program } from "./helpers";
+
+describe("Group", () => {
+  function groupAccountFor(groupMint: PublicKey): PublicKey {
+    return pda([Buffer.from("group"), groupMint.toBuffer()]);
+  }
+
+  async function createCollection(maxSize: number): Promise<PublicKey> {
+    const mint = Keypair.generate();
+    await program.methods
+      .createCollection("Collection", "COL", "https://example.com/collection.json", maxSize)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        groupAccount: groupAccountFor(mint.publicKey),
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+    return mint.publicKey;
+  }
+
+  function memberAccountFor(memberMint: PublicKey): PublicKey {
+    return pda([Buffer.from("member"), memberMint.toBuffer()]);
+  }
+
+  async function createMember(groupMint: PublicKey): Promise<PublicKey> {
+    const mint = Keypair.generate();
+    await program.methods
+      .createMintWithMember(0)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        groupMint,
+        groupUpdateAuthority: payer.publicKey,
+        groupAccount: groupAccountFor(groupMint),
+        memberAccount: memberAccountFor(mint.publicKey),
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+    return mint.publicKey;
+  }
+
+  async function tokenGroupOf(groupMint: PublicKey) {
+    const mintInfo = await getMint(connection, groupMint, "confirmed", TOKEN_2022_PROGRAM_ID);
+    return getTokenGroupState(mintInfo)!;
+  }
+
+  describe("update_group_pointer", () => {
+    async function placeholderGroupPointerMint(): Promise<PublicKey> {
+      const mint = Keypair.generate();
+      await program.methods
+        .createMintWithGroupPointer(payer.publicKey, Keypair.generate().publicKey, 0)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+      return mint.publicKey;
+    }
+
+    function updateGroupPointer(mint: PublicKey, newAddress: PublicKey | null, authority: Keypair = payer) {
+      return program.methods
+        .updateGroupPointer(newAddress)
+        .accountsPartial({ mint, authority: authority.publicKey, tokenProgram: TOKEN_2022_PROGRAM_ID })
+        .signers(authority === payer ? [] : [authority])
+        .rpc();
+    }
+
+    async function groupPointerTarget(mint: PublicKey): Promise<PublicKey | null> {
+      const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      return getGroupPointerState(mintInfo)!.groupAddress;
+    }
+
+    it("re-points the group pointer at a migrated collection account", async () => {
+      const mint = await placeholderGroupPointerMint();
+      const migrated = Keypair.generate().publicKey;
+
+      await updateGroupPointer(mint, migrated);
+
+      expect((await groupPointerTarget(mint))!.toBase58()).to.equal(migrated.toBase58());
+    });
+
+    it("clears the pointer when passed None", async () => {
+      const mint = await placeholderGroupPointerMint();
+
+      await updateGroupPointer(mint, null);
+
+      expect(await groupPointerTarget(mint)).to.equal(null);
+    });
+
+    it("rejects a signer that is not the pointer authority", async () => {
+      const mint = await placeholderGroupPointerMint();
+      const before = await groupPointerTarget(mint);
+
+      await expectError(updateGroupPointer(mint, mint, Keypair.generate()), "InvalidAuthority");
+      expect((await groupPointerTarget(mint))!.toBase58()).to.equal(before!.toBase58());
+    });
+  });
+
+  describe("update_member_pointer", () => {
+    async function placeholderMemberPointerMint(): Promise<PublicKey> {
+      const mint = Keypair.generate();
+      await program.methods
+        .createMintWithMemberPointer(payer.publicKey, Keypair.generate().publicKey, 0)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+      return mint.publicKey;
+    }
+
+    function updateMemberPointer(mint: PublicKey, newAddress: PublicKey | null, authority: Keypair = payer) {
+      return program.methods
+        .updateMemberPointer(newAddress)
+        .accountsPartial({ mint, authority: authority.publicKey, tokenProgram: TOKEN_2022_PROGRAM_ID })
+        .signers(authority === payer ? [] : [authority])
+        .rpc();
+    }
+
+    async function memberPointerTarget(mint: PublicKey): Promise<PublicKey | null> {
+      const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      return getGroupMemberPointerState(mintInfo)!.memberAddress;
+    }
+
+    it("re-links the member pointer to the mint itself", async () => {
+      const mint = await placeholderMemberPointerMint();
+
+      await updateMemberPointer(mint, mint);
+
+      expect((await memberPointerTarget(mint))!.toBase58()).to.equal(mint.toBase58());
+    });
+
+    it("clears the pointer when passed None", async () => {
+      const mint = await placeholderMemberPointerMint();
+
+      await updateMemberPointer(mint, null);
+
+      expect(await memberPointerTarget(mint)).to.equal(null);
+    });
+
+    it("rejects a signer that is not the pointer authority", async () => {
+      const mint = await placeholderMemberPointerMint();
+      const before = await memberPointerTarget(mint);
+
+      await expectError(updateMemberPointer(mint, mint, Keypair.generate()), "InvalidAuthority");
+      expect((await memberPointerTarget(mint))!.toBase58()).to.equal(before!.toBase58());
+    });
+  });
+
+  describe("set_group_update_authority", () => {
+    function setGroupAuthority(groupMint: PublicKey, newAuthority: PublicKey | null, confirmRenounce: boolean, authority: Keypair = payer) {
+      return program.methods
+        .setGroupUpdateAuthority(newAuthority, confirmRenounce)
+        .accountsPartial({ mint: groupMint, updateAuthority: authority.publicKey, tokenProgram: TOKEN_2022_PROGRAM_ID })
+        .signers(authority === payer ? [] : [authority])
+        .rpc();
+    }
+
+    function updateMaxSize(groupMint: PublicKey, maxSize: number, authority: Keypair = payer) {
+      return program.methods
+        .updateGroupMaxSize(maxSize)
+        .accountsPartial({ mint: groupMint, updateAuthority: authority.publicKey, tokenProgram: TOKEN_2022_PROGRAM_ID })
+        .signers(authority === payer ? [] : [authority])
+        .rpc();
+    }
+
+    it("hands max size updates to the new authority", async () => {
+      const groupMint = await createCollection(10);
+      const dao = Keypair.generate();
+
+      await setGroupAuthority(groupMint, dao.publicKey, false);
+      expect((await tokenGroupOf(groupMint)).updateAuthority!.toBase58()).to.equal(dao.publicKey.toBase58());
+
+      await updateMaxSize(groupMint, 20, dao);
+      expect(Number((await tokenGroupOf(groupMint)).maxSize)).to.equal(20);
+
+      await expectError(updateMaxSize(groupMint, 30), "Incorrect update authority");
+    });
+
+    it("rejects a signer that is not the group update authority", async () => {
+      const groupMint = await createCollection(10);
+      const stranger = Keypair.generate();
+
+      await expectError(setGroupAuthority(groupMint, stranger.publicKey, false, stranger), "InvalidAuthority");
+      expect((await tokenGroupOf(groupMint)).updateAuthority!.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("requires confirmation before locking the group", async () => {
+      const groupMint = await createCollection(10);
+
+      await expectError(setGroupAuthority(groupMint, null, false), "AuthorityRevocationNotConfirmed");
+
+      await setGroupAuthority(groupMint, null, true);
+      expect((await tokenGroupOf(groupMint)).updateAuthority).to.equal(undefined);
+
+      let rejected = false;
+      try {
+        await updateMaxSize(groupMint, 20);
+      } catch {
+        rejected = true;
+      }
+      expect(rejected).to.equal(true);
+      expect(Number((await tokenGroupOf(groupMint)).maxSize)).to.equal(10);
+    });
+  });
+
+  describe("group size on member creation", () => {
+    it("mirrors the group size and member numbers as members join", async () => {
+      const groupMint = await createCollection(2);
+
+      const first = await createMember(groupMint);
+      const second = await createMember(groupMint);
+
+      const groupAccount = await program.account.groupAccount.fetch(groupAccountFor(groupMint));
+      expect(groupAccount.mint.toBase58()).to.equal(groupMint.toBase58());
+      expect(groupAccount.size).to.equal(2);
+      expect(groupAccount.maxSize).to.equal(2);
+      expect(Number((await tokenGroupOf(groupMint)).size)).to.equal(2);
+
+      const firstMember = await program.account.memberAccount.fetch(memberAccountFor(first));
+      expect(firstMember.mint.toBase58()).to.equal(first.toBase58());
+      expect(firstMember.group.toBase58()).to.equal(groupMint.toBase58());
+      expect(firstMember.memberNumber).to.equal(1);
+      expect((await program.account.memberAccount.fetch(memberAccountFor(second))).memberNumber).to.equal(2);
+    });
+
+    it("rejects a member beyond max_size with GroupSizeLimitExceeded", async () => {
+      const groupMint = await createCollection(2);
+      await createMember(groupMint);
+      await createMember(groupMint);
+
+      await expectError(createMember(groupMint), "GroupSizeLimitExceeded");
+      expect((await program.account.groupAccount.fetch(groupAccountFor(groupMint))).size).to.equal(2);
+    });
+  });
+
+  describe("create_collection", () => {
+    it("creates a group mint carrying the collection metadata", async () => {
+      const mint = Keypair.generate();
+      const sig = await program.methods
+        .createCollection("Collection", "COL", "https://example.com/collection.json", 5)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          groupAccount: groupAccountFor(mint.publicKey),
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc({ commitment: "confirmed" });
+
+      const event = (await eventsFrom(sig)).find((e) => e.name === "collectionCreated");
+      expect(event!.data.mint.toBase58()).to.equal(mint.publicKey.toBase58());
+      expect(event!.data.updateAuthority.toBase58()).to.equal(payer.publicKey.toBase58());
+      expect(event!.data.maxSize).to.equal(5);
+
+      const mintInfo = await getMint(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(mintInfo.decimals).to.equal(0);
+      expect(getGroupPointerState(mintInfo)!.groupAddress!.toBase58()).to.equal(mint.publicKey.toBase58());
+
+      const metadata = (await getTokenMetadata(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID))!;
+      expect(metadata.name).to.equal("Collection");
+      expect(metadata.symbol).to.equal("COL");
+      expect(metadata.uri).to.equal("https://example.com/collection.json");
+
+      const groupAccount = await program.account.groupAccount.fetch(groupAccountFor(mint.publicKey));
+      expect(groupAccount.size).to.equal(0);
+      expect(groupAccount.maxSize).to.equal(5);
+    });
+
+    it("counts a member created against the collection", async () => {
+      const groupMint = await createCollection(5);
+
+      const member = await createMember(groupMint);
+
+      expect(Number((await tokenGroupOf(groupMint)).size)).to.equal(1);
+      expect((await program.account.groupAccount.fetch(groupAccountFor(groupMint))).size).to.equal(1);
+      expect((await program.account.memberAccount.fetch(memberAccountFor(member))).group.toBase58()).to.equal(groupMint.toBase58());
+      expect((await getTokenMetadata(connection, groupMint, "confirmed", TOKEN_2022_PROGRAM_ID))!.name).to.equal("Collection");
+    });
+
+    it("rejects invalid collection metadata", async () => {
+      const mint = Keypair.generate();
+
+      await expectError(
+        program.methods
+          .createCollection("Collection", "COL", "http://example.com/collection.json", 5)
+          .accountsPartial({
+            payer: payer.publicKey,
+            mint: mint.publicKey,
+            mintAuthority: payer.publicKey,
+            groupAccount: groupAccountFor(mint.publicKey),
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([mint])
+          .rpc(),
+        "InvalidMetadata",
+      );
+    });
+  });
+
+  describe("create_mint_with_member group checks", () => {
+    async function groupPointerMint(groupAddress: (mint: PublicKey) => PublicKey): Promise<PublicKey> {
+      const mint = Keypair.generate();
+      await program.methods
+        .createMintWithGroupPointer(payer.publicKey, groupAddress(mint.publicKey), 0)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+      return mint.publicKey;
+    }
+
+    it("rejects a plain mint as the group with InvalidGroupConfig", async () => {
+      const plainMint = await createMint(connection, payer, payer.publicKey, null, 0, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+
+      await expectError(createMember(plainMint), "InvalidGroupConfig");
+    });
+
+    it("rejects a group mint whose pointer targets another account", async () => {
+      const groupMint = await groupPointerMint(() => Keypair.generate().publicKey);
+
+      await expectError(createMember(groupMint), "InvalidGroupConfig");
+    });
+
+    it("rejects a group mint that points at itself but has no TokenGroup", async () => {
+      const groupMint = await groupPointerMint((mint) => mint);
+
+      await expectError(createMember(groupMint), "InvalidGroupConfig");
+    });
+  });
+});
@@ -0,0 +1,70 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, TransactionInstruction } from "@solana/web3.js";
+import { expect } from "chai";
+import { TokenExtensions } from "../target/types/token_extensions";
+
+export const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+export const program = anchor.workspace.TokenExtensions as Program<TokenExtensions>;
+export const connection = provider.connection;
+export const payer = (provider.wallet as anchor.Wallet).payer;
+
+export function pda(seeds: (Buffer | Uint8Array)[], programId: PublicKey = program.programId): PublicKey {
+  return PublicKey.findProgramAddressSync(seeds, programId)[0];
+}
+
+export async function airdrop(to: PublicKey, sol = 2): Promise<void> {
+  const signature = await connection.requestAirdrop(to, sol * LAMPORTS_PER_SOL);
+  const latest = await connection.getLatestBlockhash();
+  await connection.confirmTransaction({ signature, ...latest }, "confirmed");
+}
+
+export async function fundedKeypair(sol = 2): Promise<Keypair> {
+  const keypair = Keypair.generate();
+  await airdrop(keypair.publicKey, sol);
+  return keypair;
+}
+
+// anchor errors carry the variant name, token program errors only their logs
+export async function expectError(promise: Promise<unknown>, code: string): Promise<void> {
+  try {
+    await promise;
+  } catch (error: any) {
+    const anchorCode = error?.error?.errorCode?.code;
+    const logs: string[] = error?.logs ?? error?.transactionLogs ?? [];
+    const message = [anchorCode, error?.message, ...logs].filter(Boolean).join("\n");
+    expect(message).to.include(code);
+    return;
+  }
+  expect.fail(`expected the transaction to fail with ${code}`);
+}
+
+export const MEMO_PROGRAM_ID = new PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+export function memoInstruction(memo: string, signers: PublicKey[] = []): TransactionInstruction {
+  return new TransactionInstruction({
+    programId: MEMO_PROGRAM_ID,
+    keys: signers.map((pubkey) => ({ pubkey, isSigner: true, isWritable: false })),
+    data: Buffer.from(memo, "utf8"),
+  });
+}
+
+// decodes the anchor events the program emitted in a confirmed transaction
+export async function eventsFrom(signature: string): Promise<anchor.Event[]> {
+  const transaction = await connection.getTransaction(signature, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+  const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+  return Array.from(parser.parseLogs(transaction?.meta?.logMessages ?? []));
+}
+
+// return data is logged as "Program return: <program id> <base64>"
+export function returnDataFrom(logs: string[], programId: PublicKey = program.programId): Buffer {
+  const prefix = `Program return: ${programId.toBase58()} `;
+  const line = logs.find((log) => log.startsWith(prefix));
+  expect(line, `no return data from ${programId.toBase58()}`).to.not.equal(undefined);
+  return Buffer.from(line!.slice(prefix.length), "base64");
+}
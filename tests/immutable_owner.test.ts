@@ -0,0 +1,346 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  SYSVAR_RENT_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  createAssociatedTokenAccount,
+  createEnableCpiGuardInstruction,
+  createMint,
+  createTransferCheckedInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getCpiGuard,
+  getExtensionTypes,
+  getMemoTransfer,
+  mintTo,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { TestCpiCaller } from "../target/types/test_cpi_caller";
+import { connection, expectError, fundedKeypair, memoInstruction, payer, pda, program, provider } from "./helpers";
+
+describe("Immutable Owner", () => {
+  let mint: PublicKey;
+
+  async function extensionsOf(tokenAccount: PublicKey): Promise<ExtensionType[]> {
+    const account = await getAccount(connection, tokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    return getExtensionTypes(account.tlvData);
+  }
+
+  before(async () => {
+    mint = await createMint(connection, payer, payer.publicKey, null, 6, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+  });
+
+  describe("create_ata_with_extensions", () => {
+    function createAta(owner: PublicKey) {
+      return program.methods
+        .createAtaWithExtensions()
+        .accountsPartial({
+          payer: payer.publicKey,
+          tokenAccount: getAssociatedTokenAddressSync(mint, owner, false, TOKEN_2022_PROGRAM_ID),
+          owner,
+          mint,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        });
+    }
+
+    it("returns the derived associated token address", async () => {
+      const owner = Keypair.generate().publicKey;
+      const expected = getAssociatedTokenAddressSync(mint, owner, false, TOKEN_2022_PROGRAM_ID);
+
+      // return data is logged as "Program return: <program id> <base64>"
+      const { raw } = await createAta(owner).simulate();
+      const prefix = `Program return: ${program.programId.toBase58()} `;
+      const returned = raw.find((line) => line.startsWith(prefix))!.slice(prefix.length);
+      expect(new PublicKey(Buffer.from(returned, "base64")).toBase58()).to.equal(expected.toBase58());
+    });
+
+    it("creates an ATA carrying the ImmutableOwner extension, idempotently", async () => {
+      const owner = Keypair.generate().publicKey;
+      const tokenAccount = getAssociatedTokenAddressSync(mint, owner, false, TOKEN_2022_PROGRAM_ID);
+
+      await createAta(owner).rpc();
+      await createAta(owner).rpc();
+
+      const account = await getAccount(connection, tokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(account.owner.toBase58()).to.equal(owner.toBase58());
+      expect(await extensionsOf(tokenAccount)).to.include(ExtensionType.ImmutableOwner);
+    });
+  });
+
+  describe("create_locked_account", () => {
+    const caller = anchor.workspace.TestCpiCaller as Program<TestCpiCaller>;
+
+    function createLockedAccountIx(tokenAccount: Keypair, owner: Keypair) {
+      return program.methods
+        .createLockedAccount()
+        .accountsPartial({
+          payer: payer.publicKey,
+          tokenAccount: tokenAccount.publicKey,
+          mint,
+          owner: owner.publicKey,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .instruction();
+    }
+
+    it("creates an immutable-owner account with the CPI guard enabled in the same transaction", async () => {
+      const tokenAccount = Keypair.generate();
+      const owner = Keypair.generate();
+
+      const transaction = new Transaction().add(
+        await createLockedAccountIx(tokenAccount, owner),
+        createEnableCpiGuardInstruction(tokenAccount.publicKey, owner.publicKey, [], TOKEN_2022_PROGRAM_ID),
+      );
+      await provider.sendAndConfirm(transaction, [tokenAccount, owner]);
+
+      const account = await getAccount(connection, tokenAccount.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(account.owner.toBase58()).to.equal(owner.publicKey.toBase58());
+      expect(getExtensionTypes(account.tlvData)).to.include(ExtensionType.ImmutableOwner);
+      expect(getCpiGuard(account)!.lockCpi).to.equal(true);
+    });
+
+    it("fails when the enable_cpi_guard instruction does not follow", async () => {
+      const tokenAccount = Keypair.generate();
+      const owner = Keypair.generate();
+
+      const transaction = new Transaction().add(await createLockedAccountIx(tokenAccount, owner));
+      await expectError(provider.sendAndConfirm(transaction, [tokenAccount, owner]), "CpiGuardEnableMissing");
+    });
+
+    it("fails when the following enable is signed by someone other than the owner", async () => {
+      const tokenAccount = Keypair.generate();
+      const owner = Keypair.generate();
+      const stranger = Keypair.generate();
+
+      const transaction = new Transaction().add(
+        await createLockedAccountIx(tokenAccount, owner),
+        createEnableCpiGuardInstruction(tokenAccount.publicKey, stranger.publicKey, [], TOKEN_2022_PROGRAM_ID),
+      );
+      await expectError(
+        provider.sendAndConfirm(transaction, [tokenAccount, owner, stranger]),
+        "CpiGuardEnableMissing",
+      );
+    });
+
+    it("fails cleanly when invoked through CPI", async () => {
+      const tokenAccount = Keypair.generate();
+      const owner = Keypair.generate();
+
+      const nested = await caller.methods
+        .nestCreateLockedAccount()
+        .accountsPartial({
+          payer: payer.publicKey,
+          tokenAccount: tokenAccount.publicKey,
+          mint,
+          owner: owner.publicKey,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenExtensionsProgram: program.programId,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .instruction();
+
+      const transaction = new Transaction().add(
+        nested,
+        createEnableCpiGuardInstruction(tokenAccount.publicKey, owner.publicKey, [], TOKEN_2022_PROGRAM_ID),
+      );
+      await expectError(provider.sendAndConfirm(transaction, [tokenAccount, owner]), "CpiGuardRequiresTopLevel");
+      expect(await connection.getAccountInfo(tokenAccount.publicKey)).to.equal(null);
+    });
+  });
+
+  describe("create_exchange_deposit_account", () => {
+    const depositAccount = Keypair.generate();
+    const exchange = Keypair.generate();
+    let source: PublicKey;
+
+    function deposit(amount: number) {
+      return createTransferCheckedInstruction(
+        source, mint, depositAccount.publicKey, payer.publicKey, amount, 6, [], TOKEN_2022_PROGRAM_ID,
+      );
+    }
+
+    before(async () => {
+      await program.methods
+        .createExchangeDepositAccount()
+        .accountsPartial({
+          payer: payer.publicKey,
+          tokenAccount: depositAccount.publicKey,
+          mint,
+          owner: exchange.publicKey,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([depositAccount, exchange])
+        .rpc();
+
+      source = await createAssociatedTokenAccount(
+        connection, payer, mint, payer.publicKey, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+      await mintTo(connection, payer, mint, source, payer, 10_000, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+    });
+
+    it("carries ImmutableOwner and requires incoming memos", async () => {
+      const account = await getAccount(connection, depositAccount.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(getExtensionTypes(account.tlvData)).to.include(ExtensionType.ImmutableOwner);
+      expect(getMemoTransfer(account)!.requireIncomingTransferMemos).to.equal(true);
+    });
+
+    it("rejects a deposit without a memo", async () => {
+      await expectError(provider.sendAndConfirm(new Transaction().add(deposit(1_000))), "No memo");
+
+      const account = await getAccount(connection, depositAccount.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(account.amount).to.equal(BigInt(0));
+    });
+
+    it("accepts a deposit preceded by a memo", async () => {
+      await provider.sendAndConfirm(new Transaction().add(memoInstruction("user-4242"), deposit(1_000)));
+
+      const account = await getAccount(connection, depositAccount.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(account.amount).to.equal(BigInt(1_000));
+    });
+  });
+
+  describe("sponsored account creation", () => {
+    const user = Keypair.generate();
+
+    function sponsorFor(funder: Keypair): PublicKey {
+      return pda([Buffer.from("sponsor"), funder.publicKey.toBuffer(), user.publicKey.toBuffer()]);
+    }
+
+    function fundSponsor(funder: Keypair, lamports: number) {
+      return program.methods
+        .fundSponsor(new anchor.BN(lamports))
+        .accountsPartial({
+          funder: funder.publicKey,
+          sponsor: sponsorFor(funder),
+          owner: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers(funder === payer ? [] : [funder])
+        .rpc();
+    }
+
+    function defundSponsor(funder: Keypair, lamports: number, destination: PublicKey) {
+      return program.methods
+        .defundSponsor(new anchor.BN(lamports))
+        .accountsPartial({ sponsor: sponsorFor(funder), funder: funder.publicKey, owner: user.publicKey, destination })
+        .signers(funder === payer ? [] : [funder])
+        .rpc();
+    }
+
+    function createSponsored(relayer: Keypair, tokenAccount: Keypair) {
+      return program.methods
+        .createAccountWithImmutableOwnerSponsored()
+        .accountsPartial({
+          sponsor: sponsorFor(relayer),
+          relayer: relayer.publicKey,
+          tokenAccount: tokenAccount.publicKey,
+          mint,
+          owner: user.publicKey,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers(relayer === payer ? [tokenAccount] : [relayer, tokenAccount])
+        .rpc();
+    }
+
+    before(async () => {
+      await fundSponsor(payer, LAMPORTS_PER_SOL / 20);
+    });
+
+    it("records the funder as the relayer", async () => {
+      const record = await program.account.sponsor.fetch(sponsorFor(payer));
+      expect(record.owner.toBase58()).to.equal(user.publicKey.toBase58());
+      expect(record.funder.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("keeps a second funder in its own sponsor instead of claiming the first", async () => {
+      const griefer = await fundedKeypair(1);
+
+      await fundSponsor(griefer, 0);
+
+      expect((await program.account.sponsor.fetch(sponsorFor(griefer))).funder.toBase58()).to.equal(griefer.publicKey.toBase58());
+      expect((await program.account.sponsor.fetch(sponsorFor(payer))).funder.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("gives a user with zero SOL a fully initialized immutable-owner account", async () => {
+      const tokenAccount = Keypair.generate();
+      expect(await connection.getBalance(user.publicKey)).to.equal(0);
+
+      await createSponsored(payer, tokenAccount);
+
+      const account = await getAccount(connection, tokenAccount.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(account.isInitialized).to.equal(true);
+      expect(account.owner.toBase58()).to.equal(user.publicKey.toBase58());
+      expect(account.mint.toBase58()).to.equal(mint.toBase58());
+      expect(getExtensionTypes(account.tlvData)).to.include(ExtensionType.ImmutableOwner);
+      expect(await connection.getBalance(user.publicKey)).to.equal(0);
+    });
+
+    it("rejects a relayer that did not fund the sponsor", async () => {
+      const stranger = await fundedKeypair(1);
+      await expectError(createSponsored(stranger, Keypair.generate()), "AccountNotInitialized");
+    });
+
+    it("lets only the funder withdraw, and never below the sponsor's rent", async () => {
+      const destination = Keypair.generate().publicKey;
+      const stranger = await fundedKeypair(1);
+
+      await expectError(defundSponsor(stranger, 1_000, destination), "AccountNotInitialized");
+      await expectError(defundSponsor(payer, LAMPORTS_PER_SOL, destination), "InsufficientFunds");
+
+      await defundSponsor(payer, 1_000_000, destination);
+      expect(await connection.getBalance(destination)).to.equal(1_000_000);
+    });
+  });
+
+  describe("is_owner_immutable", () => {
+    function isOwnerImmutable(tokenAccount: PublicKey): Promise<boolean> {
+      return program.methods.isOwnerImmutable().accountsPartial({ tokenAccount }).view();
+    }
+
+    it("reports true for a Token-2022 ATA", async () => {
+      const tokenAccount = await createAssociatedTokenAccount(
+        connection, payer, mint, Keypair.generate().publicKey, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+      expect(await isOwnerImmutable(tokenAccount)).to.equal(true);
+    });
+
+    it("reports false for a Token-2022 account without the extension", async () => {
+      const tokenAccount = await createAccount(
+        connection, payer, mint, payer.publicKey, Keypair.generate(), { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+      expect(await extensionsOf(tokenAccount)).to.not.include(ExtensionType.ImmutableOwner);
+      expect(await isOwnerImmutable(tokenAccount)).to.equal(false);
+    });
+
+    it("reports false rather than erroring for a legacy SPL token account", async () => {
+      const legacyMint = await createMint(connection, payer, payer.publicKey, null, 6, undefined, undefined, TOKEN_PROGRAM_ID);
+      const tokenAccount = await createAccount(
+        connection, payer, legacyMint, payer.publicKey, Keypair.generate(), { commitment: "confirmed" }, TOKEN_PROGRAM_ID,
+      );
+      expect(await isOwnerImmutable(tokenAccount)).to.equal(false);
+    });
+  });
+});
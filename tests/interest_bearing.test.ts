@@ -0,0 +1,513 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  amountToUiAmount,
+  createAssociatedTokenAccount,
+  createMint,
+  getAccount,
+  getInterestBearingMintConfigState,
+  getMetadataPointerState,
+  getMint,
+  getTokenMetadata,
+  getTransferFeeAmount,
+  getTransferFeeConfig,
+  mintTo,
+  transferCheckedWithFee,
+  uiAmountToAmount,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { connection, eventsFrom, expectError, fundedKeypair, payer, pda, program, returnDataFrom } from "./helpers";
+
+describe("Interest Bearing", () => {
+  async function createInterestMint(rate: number, rateAuthority: PublicKey | null = payer.publicKey): Promise<PublicKey> {
+    const mint = Keypair.generate();
+
+    await program.methods
+      .createInterestBearingMint(rateAuthority, rate, 2)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+
+    return mint.publicKey;
+  }
+
+  function updateInterestRate(mint: PublicKey, rate: number, rateAuthority: Keypair = payer) {
+    return program.methods
+      .updateInterestRate(rate)
+      .accountsPartial({
+        mint,
+        rateAuthority: rateAuthority.publicKey,
+        rateLimits: PublicKey.findProgramAddressSync([Buffer.from("rate_limits"), mint.toBuffer()], program.programId)[0],
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers(rateAuthority === payer ? [] : [rateAuthority])
+      .rpc();
+  }
+
+  describe("amount conversion views", () => {
+    async function viewUiAmount(mint: PublicKey, amount: number): Promise<string> {
+      const { raw } = await program.methods
+        .amountToUiAmount(new anchor.BN(amount))
+        .accountsPartial({ mint, tokenProgram: TOKEN_2022_PROGRAM_ID })
+        .simulate();
+      return returnDataFrom(raw).toString("utf8");
+    }
+
+    async function viewAmount(mint: PublicKey, uiAmount: string): Promise<bigint> {
+      const { raw } = await program.methods
+        .uiAmountToAmount(uiAmount)
+        .accountsPartial({ mint, tokenProgram: TOKEN_2022_PROGRAM_ID })
+        .simulate();
+      return returnDataFrom(raw).readBigUInt64LE(0);
+    }
+
+    // interest keeps accruing between the two simulations, so compare within a small tolerance
+    async function expectMatchesReference(mint: PublicKey, amount: number) {
+      const ours = await viewUiAmount(mint, amount);
+      const reference = (await amountToUiAmount(connection, payer, mint, amount, TOKEN_2022_PROGRAM_ID)) as string;
+      expect(Number(ours)).to.be.closeTo(Number(reference), Number(reference) * 1e-6);
+
+      const ourAmount = await viewAmount(mint, reference);
+      const referenceAmount = (await uiAmountToAmount(connection, payer, mint, reference, TOKEN_2022_PROGRAM_ID)) as bigint;
+      expect(Number(ourAmount)).to.be.closeTo(Number(referenceAmount), 1);
+    }
+
+    for (const rate of [500, 0, -500, 32_767]) {
+      it(`matches the SPL reference at ${rate} bps`, async () => {
+        const mint = await createInterestMint(rate);
+
+        for (const amount of [1, 12_345, 1_000_000_000]) {
+          await expectMatchesReference(mint, amount);
+        }
+      });
+    }
+
+    it("matches the SPL reference after a mid-life rate change", async () => {
+      const mint = await createInterestMint(1_000);
+      await updateInterestRate(mint, -250);
+
+      await expectMatchesReference(mint, 1_000_000_000);
+    });
+  });
+
+  describe("set_rate_authority", () => {
+    function setRateAuthority(mint: PublicKey, newAuthority: PublicKey | null, confirmRenounce: boolean, rateAuthority: Keypair = payer) {
+      return program.methods
+        .setRateAuthority(newAuthority, confirmRenounce)
+        .accountsPartial({
+          mint,
+          rateAuthority: rateAuthority.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers(rateAuthority === payer ? [] : [rateAuthority]);
+    }
+
+    async function rateAuthorityOf(mint: PublicKey): Promise<PublicKey | null> {
+      const config = await program.methods.getInterestConfig().accountsPartial({ mint }).view();
+      return config.rateAuthority;
+    }
+
+    it("rotates the authority and emits the old and new authority", async () => {
+      const mint = await createInterestMint(500);
+      const dao = Keypair.generate();
+
+      const sig = await setRateAuthority(mint, dao.publicKey, false).rpc({ commitment: "confirmed" });
+
+      expect((await rateAuthorityOf(mint))!.toBase58()).to.equal(dao.publicKey.toBase58());
+
+      const event = (await eventsFrom(sig)).find((e) => e.name === "rateAuthorityChanged");
+      expect(event).to.not.equal(undefined);
+      expect(event!.data.mint.toBase58()).to.equal(mint.toBase58());
+      expect(event!.data.oldAuthority.toBase58()).to.equal(payer.publicKey.toBase58());
+      expect(event!.data.newAuthority.toBase58()).to.equal(dao.publicKey.toBase58());
+
+      await updateInterestRate(mint, 600, dao);
+      const config = await program.methods.getInterestConfig().accountsPartial({ mint }).view();
+      expect(config.currentRate).to.equal(600);
+    });
+
+    it("stops the old authority from updating the rate after rotation", async () => {
+      const mint = await createInterestMint(500);
+
+      await setRateAuthority(mint, Keypair.generate().publicKey, false).rpc();
+
+      await expectError(updateInterestRate(mint, 600), "owner does not match");
+    });
+
+    it("rejects a signer that is not the current rate authority", async () => {
+      const mint = await createInterestMint(500);
+      const stranger = Keypair.generate();
+
+      await expectError(setRateAuthority(mint, stranger.publicKey, false, stranger).rpc(), "InvalidAuthority");
+      expect((await rateAuthorityOf(mint))!.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("requires confirmation before renouncing the authority", async () => {
+      const mint = await createInterestMint(500);
+
+      await expectError(setRateAuthority(mint, null, false).rpc(), "AuthorityRevocationNotConfirmed");
+      expect((await rateAuthorityOf(mint))!.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("fixes the rate permanently once renounced", async () => {
+      const mint = await createInterestMint(500);
+
+      const sig = await setRateAuthority(mint, null, true).rpc({ commitment: "confirmed" });
+
+      expect(await rateAuthorityOf(mint)).to.equal(null);
+      const event = (await eventsFrom(sig)).find((e) => e.name === "rateAuthorityChanged");
+      expect(event!.data.newAuthority).to.equal(null);
+
+      await expectError(updateInterestRate(mint, 600), "No authority exists");
+    });
+  });
+
+  describe("timelocked rate changes", () => {
+    async function createTimelockMint(rate: number): Promise<PublicKey> {
+      const mint = Keypair.generate();
+
+      await program.methods
+        .createInterestBearingMintWithTimelock(rate, 2)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+
+      return mint.publicKey;
+    }
+
+    function pendingRateFor(mint: PublicKey): PublicKey {
+      return pda([Buffer.from("pending_rate"), mint.toBuffer()]);
+    }
+
+    async function chainTime(): Promise<number> {
+      return (await connection.getBlockTime(await connection.getSlot("confirmed")))!;
+    }
+
+    async function waitUntil(timestamp: number) {
+      while ((await chainTime()) <= timestamp) {
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+    }
+
+    function proposeRate(mint: PublicKey, rate: number, effectiveAfter: number, proposer: Keypair = payer) {
+      return program.methods
+        .proposeInterestRate(rate, new anchor.BN(effectiveAfter))
+        .accountsPartial({
+          proposer: proposer.publicKey,
+          mint,
+          pendingRate: pendingRateFor(mint),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers(proposer === payer ? [] : [proposer])
+        .rpc();
+    }
+
+    function executeRate(mint: PublicKey, proposer: PublicKey = payer.publicKey) {
+      return program.methods
+        .executeInterestRate()
+        .accountsPartial({
+          mint,
+          pendingRate: pendingRateFor(mint),
+          proposer,
+          rateAuthority: pda([Buffer.from("rate_authority"), mint.toBuffer()]),
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+    }
+
+    function cancelRate(mint: PublicKey, proposer: Keypair = payer) {
+      return program.methods
+        .cancelInterestRate()
+        .accountsPartial({
+          proposer: proposer.publicKey,
+          mint,
+          pendingRate: pendingRateFor(mint),
+        })
+        .signers(proposer === payer ? [] : [proposer])
+        .rpc();
+    }
+
+    async function currentRateOf(mint: PublicKey): Promise<number> {
+      const config = await program.methods.getInterestConfig().accountsPartial({ mint }).view();
+      return config.currentRate;
+    }
+
+    it("rejects execution before the effective time and applies the rate after it", async () => {
+      const mint = await createTimelockMint(500);
+      const effectiveAfter = (await chainTime()) + 3;
+
+      await proposeRate(mint, 200, effectiveAfter);
+      const pending = await program.account.pendingRate.fetch(pendingRateFor(mint));
+      expect(pending.rate).to.equal(200);
+      expect(pending.effectiveAfter.toNumber()).to.equal(effectiveAfter);
+
+      await expectError(executeRate(mint), "RateChangeNotReady");
+      expect(await currentRateOf(mint)).to.equal(500);
+
+      await waitUntil(effectiveAfter);
+      await executeRate(mint);
+
+      expect(await currentRateOf(mint)).to.equal(200);
+      expect(await connection.getAccountInfo(pendingRateFor(mint))).to.equal(null);
+    });
+
+    it("lets the proposer cancel a pending change", async () => {
+      const mint = await createTimelockMint(500);
+
+      await proposeRate(mint, 100, (await chainTime()) + 60);
+      await cancelRate(mint);
+
+      expect(await connection.getAccountInfo(pendingRateFor(mint))).to.equal(null);
+      expect(await currentRateOf(mint)).to.equal(500);
+    });
+
+    it("rejects a cancel from anyone other than the proposer", async () => {
+      const mint = await createTimelockMint(500);
+
+      await proposeRate(mint, 100, (await chainTime()) + 60);
+
+      await expectError(cancelRate(mint, Keypair.generate()), "InvalidAuthority");
+    });
+
+    it("rejects a proposal from a signer that is not the mint authority", async () => {
+      const mint = await createTimelockMint(500);
+      const stranger = await fundedKeypair();
+
+      await expectError(proposeRate(mint, 100, (await chainTime()) + 60, stranger), "InvalidAuthority");
+    });
+
+    it("rejects an effective time that is not in the future", async () => {
+      const mint = await createTimelockMint(500);
+
+      await expectError(proposeRate(mint, 100, (await chainTime()) - 1), "InvalidEffectiveTime");
+    });
+
+    it("rejects proposals for a mint whose rate authority is not the timelock PDA", async () => {
+      const mint = await createInterestMint(500);
+
+      await expectError(proposeRate(mint, 100, (await chainTime()) + 60), "InterestRateUpdateNotAllowed");
+    });
+  });
+
+  describe("create_interest_bearing_mint_with_metadata", () => {
+    function createRwaMint(mint: Keypair, rate: number, name = "Treasury Note") {
+      return program.methods
+        .createInterestBearingMintWithMetadata(payer.publicKey, rate, name, "TNOTE", "https://example.com/tnote.json", 2)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          registry: pda([Buffer.from("registry"), mint.publicKey.toBuffer()]),
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+    }
+
+    it("makes both the rate and the metadata readable", async () => {
+      const mint = Keypair.generate();
+
+      await createRwaMint(mint, 425);
+
+      const mintInfo = await getMint(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      const interestConfig = getInterestBearingMintConfigState(mintInfo)!;
+      expect(interestConfig.currentRate).to.equal(425);
+      expect(interestConfig.rateAuthority.toBase58()).to.equal(payer.publicKey.toBase58());
+
+      const pointer = getMetadataPointerState(mintInfo)!;
+      expect(pointer.metadataAddress!.toBase58()).to.equal(mint.publicKey.toBase58());
+
+      const metadata = await getTokenMetadata(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(metadata!.name).to.equal("Treasury Note");
+      expect(metadata!.symbol).to.equal("TNOTE");
+      expect(metadata!.uri).to.equal("https://example.com/tnote.json");
+    });
+
+    it("records the interest and metadata extensions in the registry", async () => {
+      const mint = Keypair.generate();
+
+      await createRwaMint(mint, 425);
+
+      const entry = await program.account.tokenExtensionMint.fetch(pda([Buffer.from("registry"), mint.publicKey.toBuffer()]));
+      expect(Array.from(entry.extensionTypes)).to.deep.equal([
+        ExtensionType.InterestBearingConfig,
+        ExtensionType.MetadataPointer,
+        ExtensionType.TokenMetadata,
+      ]);
+    });
+
+    it("rejects invalid metadata before creating the mint", async () => {
+      const mint = Keypair.generate();
+
+      await expectError(createRwaMint(mint, 425, ""), "InvalidMetadata");
+      expect(await connection.getAccountInfo(mint.publicKey)).to.equal(null);
+    });
+  });
+
+  describe("get_interest_config", () => {
+    it("matches the extension parsed directly from the mint account", async () => {
+      const mint = await createInterestMint(300);
+      await updateInterestRate(mint, -120);
+
+      const view = await program.methods.getInterestConfig().accountsPartial({ mint }).view();
+      const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      const parsed = getInterestBearingMintConfigState(mintInfo)!;
+
+      expect(view.currentRate).to.equal(parsed.currentRate);
+      expect(view.currentRate).to.equal(-120);
+      expect(view.preUpdateAverageRate).to.equal(parsed.preUpdateAverageRate);
+      expect(view.initializationTimestamp.toNumber()).to.equal(Number(parsed.initializationTimestamp));
+      expect(view.lastUpdateTimestamp.toNumber()).to.equal(Number(parsed.lastUpdateTimestamp));
+      expect(view.rateAuthority!.toBase58()).to.equal(parsed.rateAuthority.toBase58());
+    });
+
+    it("returns no rate authority for a mint created without one", async () => {
+      const mint = await createInterestMint(300, null);
+
+      const view = await program.methods.getInterestConfig().accountsPartial({ mint }).view();
+      expect(view.rateAuthority).to.equal(null);
+    });
+
+    it("rejects a mint without the InterestBearingConfig extension", async () => {
+      const mint = await createMint(connection, payer, payer.publicKey, null, 2, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+
+      await expectError(program.methods.getInterestConfig().accountsPartial({ mint }).view(), "InvalidExtensionType");
+    });
+  });
+
+  describe("RateUpdated event", () => {
+    const SECONDS_PER_YEAR = 60 * 60 * 24 * 365.24;
+
+    // same continuous compounding the token program applies in amount_to_ui_amount, for one whole token
+    function uiAmountPerToken(
+      config: { initializationTimestamp: bigint; lastUpdateTimestamp: bigint; preUpdateAverageRate: number; currentRate: number },
+      timestamp: number,
+    ): number {
+      const preUpdateSeconds = Number(config.lastUpdateTimestamp - config.initializationTimestamp);
+      const postUpdateSeconds = timestamp - Number(config.lastUpdateTimestamp);
+      const preUpdateExp = Math.exp((config.preUpdateAverageRate * preUpdateSeconds) / SECONDS_PER_YEAR / 10_000);
+      const postUpdateExp = Math.exp((config.currentRate * postUpdateSeconds) / SECONDS_PER_YEAR / 10_000);
+      return preUpdateExp * postUpdateExp;
+    }
+
+    async function updateAndCheck(mint: PublicKey, rate: number) {
+      const before = getInterestBearingMintConfigState(await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID))!;
+
+      const sig = await program.methods
+        .updateInterestRate(rate)
+        .accountsPartial({
+          mint,
+          rateAuthority: payer.publicKey,
+          rateLimits: pda([Buffer.from("rate_limits"), mint.toBuffer()]),
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const event = (await eventsFrom(sig)).find((e) => e.name === "rateUpdated");
+      expect(event).to.not.equal(undefined);
+      expect(event!.data.mint.toBase58()).to.equal(mint.toBase58());
+      expect(event!.data.oldRate).to.equal(before.currentRate);
+      expect(event!.data.newRate).to.equal(rate);
+
+      const expected = uiAmountPerToken(
+        {
+          initializationTimestamp: BigInt(before.initializationTimestamp),
+          lastUpdateTimestamp: BigInt(before.lastUpdateTimestamp),
+          preUpdateAverageRate: before.preUpdateAverageRate,
+          currentRate: before.currentRate,
+        },
+        event!.data.timestamp.toNumber(),
+      );
+      expect(Number(event!.data.uiAmountPerTokenBefore)).to.be.closeTo(expected, 0.01);
+    }
+
+    it("snapshots the value of one token at each rate change", async () => {
+      const mint = await createInterestMint(2_000);
+
+      await updateAndCheck(mint, 1_000);
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      await updateAndCheck(mint, -500);
+    });
+  });
+
+  describe("create_yield_mint_with_fee", () => {
+    function createYieldMint(mint: Keypair, feeBps: number) {
+      return program.methods
+        .createYieldMintWithFee(32_767, payer.publicKey, feeBps, new anchor.BN(1_000_000), {
+          transferFeeConfigAuthority: payer.publicKey,
+          withdrawWithheldAuthority: payer.publicKey,
+        }, 2)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          registry: pda([Buffer.from("registry"), mint.publicKey.toBuffer()]),
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+    }
+
+    it("withholds fees and accrues interest on the same mint", async () => {
+      const mint = Keypair.generate();
+      await createYieldMint(mint, 500);
+
+      const recipient = Keypair.generate();
+      const source = await createAssociatedTokenAccount(connection, payer, mint.publicKey, payer.publicKey, undefined, TOKEN_2022_PROGRAM_ID);
+      const destination = await createAssociatedTokenAccount(connection, payer, mint.publicKey, recipient.publicKey, undefined, TOKEN_2022_PROGRAM_ID);
+      await mintTo(connection, payer, mint.publicKey, source, payer, 1_000_000_000_000, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+      await transferCheckedWithFee(connection, payer, source, mint.publicKey, destination, payer, 10_000, 2, 500, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+      const destinationAccount = await getAccount(connection, destination, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(Number(destinationAccount.amount)).to.equal(9_500);
+      expect(Number(getTransferFeeAmount(destinationAccount)!.withheldAmount)).to.equal(500);
+
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      const uiAmount = (await amountToUiAmount(connection, payer, mint.publicKey, 1_000_000_000_000, TOKEN_2022_PROGRAM_ID)) as string;
+      expect(Number(uiAmount)).to.be.greaterThan(10_000_000_000);
+    });
+
+    it("records both extensions in the registry", async () => {
+      const mint = Keypair.generate();
+      await createYieldMint(mint, 500);
+
+      const mintInfo = await getMint(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(getInterestBearingMintConfigState(mintInfo)!.currentRate).to.equal(32_767);
+      expect(getTransferFeeConfig(mintInfo)!.newerTransferFee.transferFeeBasisPoints).to.equal(500);
+
+      const entry = await program.account.tokenExtensionMint.fetch(pda([Buffer.from("registry"), mint.publicKey.toBuffer()]));
+      expect(Array.from(entry.extensionTypes)).to.deep.equal([
+        ExtensionType.InterestBearingConfig,
+        ExtensionType.TransferFeeConfig,
+      ]);
+    });
+
+    it("rejects a fee above 100%", async () => {
+      const mint = Keypair.generate();
+
+      await expectError(createYieldMint(mint, 10_001), "InvalidTransferFeeConfig");
+      expect(await connection.getAccountInfo(mint.publicKey)).to.equal(null);
+    });
+  });
+});
@@ -0,0 +1,750 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMetadataPointerState,
+  getMint,
+  getTokenMetadata,
+  tokenMetadataUpdateField,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { connection, expectError, payer, pda, program, returnDataFrom } from "./helpers";
+
+describe("Metadata", () => {
+  function registryFor(mint: Keypair): PublicKey {
+    return pda([Buffer.from("registry"), mint.publicKey.toBuffer()]);
+  }
+
+  function createMetadataMint(mint: Keypair, register: boolean, registry: PublicKey | null) {
+    return program.methods
+      .createMintWithMetadata("Registry Token", "REG", "https://example.com/registry.json", 6, register, false)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        registry,
+        metadataMirror: null,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+  }
+
+  function mirrorFor(mint: PublicKey): PublicKey {
+    return pda([Buffer.from("metadata_mirror"), mint.toBuffer()]);
+  }
+
+  function createMirroredMint(mint: Keypair, immutable: boolean) {
+    return program.methods
+      .createMintWithMetadata("Mirror Token", "MIR", "https://example.com/mirror.json", 6, false, immutable)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        registry: null,
+        metadataMirror: mirrorFor(mint.publicKey),
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+  }
+
+  function updateField(mint: PublicKey, field: string, value: string, updateAuthority: Keypair = payer) {
+    return program.methods
+      .updateMetadataField(field, value)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint,
+        updateAuthority: updateAuthority.publicKey,
+        metadataMirror: mirrorFor(mint),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers(updateAuthority === payer ? [] : [updateAuthority])
+      .rpc();
+  }
+
+  function removeField(mint: PublicKey, key: string, idempotent: boolean) {
+    return program.methods
+      .removeMetadataField(key, idempotent)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint,
+        updateAuthority: payer.publicKey,
+        metadataMirror: mirrorFor(mint),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+  }
+
+  function setUpdateAuthority(mint: PublicKey, newAuthority: PublicKey | null, confirmRenounce: boolean, updateAuthority: Keypair = payer) {
+    return program.methods
+      .setMetadataUpdateAuthority(newAuthority, confirmRenounce)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint,
+        updateAuthority: updateAuthority.publicKey,
+        metadataMirror: mirrorFor(mint),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers(updateAuthority === payer ? [] : [updateAuthority])
+      .rpc();
+  }
+
+  async function placeholderPointerMint(): Promise<PublicKey> {
+    const mint = Keypair.generate();
+    await program.methods
+      .createMintWithMetadataPointer(payer.publicKey, Keypair.generate().publicKey, 6)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+    return mint.publicKey;
+  }
+
+  function updatePointer(mint: PublicKey, newAddress: PublicKey | null, authority: Keypair = payer) {
+    return program.methods
+      .updateMetadataPointer(newAddress)
+      .accountsPartial({ mint, authority: authority.publicKey, tokenProgram: TOKEN_2022_PROGRAM_ID })
+      .signers(authority === payer ? [] : [authority])
+      .rpc();
+  }
+
+  async function pointerTarget(mint: PublicKey): Promise<PublicKey | null> {
+    const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+    return getMetadataPointerState(mintInfo)!.metadataAddress;
+  }
+
+  describe("create_mint_with_metadata registry", () => {
+    it("records the metadata extensions when register is set", async () => {
+      const mint = Keypair.generate();
+
+      await createMetadataMint(mint, true, registryFor(mint));
+
+      const entry = await program.account.tokenExtensionMint.fetch(registryFor(mint));
+      expect(entry.mint.toBase58()).to.equal(mint.publicKey.toBase58());
+      expect(Array.from(entry.extensionTypes)).to.deep.equal([
+        ExtensionType.MetadataPointer,
+        ExtensionType.TokenMetadata,
+      ]);
+    });
+
+    it("requires the registry account when register is set", async () => {
+      await expectError(createMetadataMint(Keypair.generate(), true, null), "RegistryAccountMissing");
+    });
+
+    it("rejects a registry account passed without register", async () => {
+      const mint = Keypair.generate();
+
+      await expectError(createMetadataMint(mint, false, registryFor(mint)), "UnexpectedRegistryAccount");
+    });
+  });
+
+  describe("remove_metadata_field", () => {
+    async function additionalKeys(mint: PublicKey): Promise<string[]> {
+      const metadata = await getTokenMetadata(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      return metadata!.additionalMetadata.map(([key]) => key);
+    }
+
+    async function mintWithKey(key: string): Promise<PublicKey> {
+      const mint = Keypair.generate();
+      await createMetadataMint(mint, false, null);
+      await updateField(mint.publicKey, key, "value");
+      return mint.publicKey;
+    }
+
+    it("removes an existing key", async () => {
+      const mint = await mintWithKey("colour");
+      expect(await additionalKeys(mint)).to.deep.equal(["colour"]);
+
+      await removeField(mint, "colour", false);
+
+      expect(await additionalKeys(mint)).to.deep.equal([]);
+    });
+
+    it("accepts a missing key when idempotent", async () => {
+      const mint = await mintWithKey("colour");
+
+      await removeField(mint, "color", true);
+
+      expect(await additionalKeys(mint)).to.deep.equal(["colour"]);
+    });
+
+    it("rejects a missing key with MetadataFieldNotFound when strict", async () => {
+      const mint = await mintWithKey("colour");
+
+      await expectError(removeField(mint, "color", false), "MetadataFieldNotFound");
+    });
+  });
+
+  describe("metadata rent on grow and shrink", () => {
+    it("funds a 500 byte growth, then reclaims the excess after shrinking", async () => {
+      const mint = Keypair.generate();
+      await createMetadataMint(mint, false, null);
+      await updateField(mint.publicKey, "description", "short");
+      const before = (await connection.getAccountInfo(mint.publicKey))!;
+
+      const longValue = "d".repeat(505);
+      await updateField(mint.publicKey, "description", longValue);
+
+      const grown = (await connection.getAccountInfo(mint.publicKey))!;
+      expect(grown.data.length).to.equal(before.data.length + 500);
+      expect(grown.lamports).to.be.at.least(await connection.getMinimumBalanceForRentExemption(grown.data.length));
+      const metadata = await getTokenMetadata(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(metadata!.additionalMetadata).to.deep.equal([["description", longValue]]);
+
+      await updateField(mint.publicKey, "description", "short");
+      const shrunk = (await connection.getAccountInfo(mint.publicKey))!;
+      expect(shrunk.data.length).to.equal(before.data.length);
+
+      const destination = Keypair.generate().publicKey;
+      await program.methods
+        .reclaimMetadataRent()
+        .accountsPartial({
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          destination,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+
+      const rentExempt = await connection.getMinimumBalanceForRentExemption(shrunk.data.length);
+      expect((await connection.getAccountInfo(mint.publicKey))!.lamports).to.equal(rentExempt);
+      expect(await connection.getBalance(destination)).to.equal(shrunk.lamports - rentExempt);
+    });
+  });
+
+  describe("set_metadata_update_authority", () => {
+    async function metadataMint(): Promise<PublicKey> {
+      const mint = Keypair.generate();
+      await createMetadataMint(mint, false, null);
+      return mint.publicKey;
+    }
+
+    it("hands updates to the new authority and stops the old one", async () => {
+      const mint = await metadataMint();
+      const newAuthority = Keypair.generate();
+
+      await setUpdateAuthority(mint, newAuthority.publicKey, false);
+
+      const metadata = await getTokenMetadata(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(metadata!.updateAuthority!.toBase58()).to.equal(newAuthority.publicKey.toBase58());
+
+      await updateField(mint, "name", "Renamed Token", newAuthority);
+      expect((await getTokenMetadata(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID))!.name).to.equal("Renamed Token");
+
+      await expectError(updateField(mint, "name", "Old Authority"), "Incorrect update authority");
+    });
+
+    it("rejects a signer that is not the stored update authority", async () => {
+      const mint = await metadataMint();
+      const stranger = Keypair.generate();
+
+      await expectError(setUpdateAuthority(mint, stranger.publicKey, false, stranger), "InvalidAuthority");
+    });
+
+    it("requires confirmation before making the metadata immutable", async () => {
+      const mint = await metadataMint();
+
+      await expectError(setUpdateAuthority(mint, null, false), "AuthorityRevocationNotConfirmed");
+
+      await setUpdateAuthority(mint, null, true);
+      expect((await getTokenMetadata(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID))!.updateAuthority).to.equal(undefined);
+      await expectError(updateField(mint, "name", "Too Late"), "MetadataImmutable");
+    });
+  });
+
+  describe("emit_metadata", () => {
+    function emitMetadata(mint: PublicKey, start: number | null, end: number | null) {
+      return program.methods
+        .emitMetadata(start === null ? null : new anchor.BN(start), end === null ? null : new anchor.BN(end))
+        .accountsPartial({ mint, metadata: null, metadataProgram: null, tokenProgram: TOKEN_2022_PROGRAM_ID });
+    }
+
+    // TokenMetadata as packed by the interface: two pubkeys, three strings, then the key/value pairs
+    function decodeTokenMetadata(data: Buffer) {
+      let offset = 64;
+      const readString = () => {
+        const len = data.readUInt32LE(offset);
+        const value = data.subarray(offset + 4, offset + 4 + len).toString("utf8");
+        offset += 4 + len;
+        return value;
+      };
+
+      const updateAuthority = new PublicKey(data.subarray(0, 32));
+      const mint = new PublicKey(data.subarray(32, 64));
+      const name = readString();
+      const symbol = readString();
+      const uri = readString();
+      const additionalMetadata: [string, string][] = [];
+      const pairs = data.readUInt32LE(offset);
+      offset += 4;
+      for (let i = 0; i < pairs; i++) {
+        additionalMetadata.push([readString(), readString()]);
+      }
+
+      return { updateAuthority, mint, name, symbol, uri, additionalMetadata };
+    }
+
+    it("returns the metadata stored on the mint", async () => {
+      const mint = Keypair.generate();
+      await createMetadataMint(mint, false, null);
+      await updateField(mint.publicKey, "colour", "teal");
+
+      const { raw } = await emitMetadata(mint.publicKey, null, null).simulate();
+      const emitted = decodeTokenMetadata(returnDataFrom(raw));
+
+      expect(emitted.updateAuthority.toBase58()).to.equal(payer.publicKey.toBase58());
+      expect(emitted.mint.toBase58()).to.equal(mint.publicKey.toBase58());
+      expect(emitted.name).to.equal("Registry Token");
+      expect(emitted.symbol).to.equal("REG");
+      expect(emitted.uri).to.equal("https://example.com/registry.json");
+      expect(emitted.additionalMetadata).to.deep.equal([["colour", "teal"]]);
+    });
+
+    it("returns only the requested byte range", async () => {
+      const mint = Keypair.generate();
+      await createMetadataMint(mint, false, null);
+
+      const { raw } = await emitMetadata(mint.publicKey, 32, 64).simulate();
+
+      expect(new PublicKey(returnDataFrom(raw)).toBase58()).to.equal(mint.publicKey.toBase58());
+    });
+
+    it("rejects an external metadata account that was not supplied", async () => {
+      const mint = await placeholderPointerMint();
+
+      await expectError(emitMetadata(mint, null, null).rpc(), "MetadataAccountMissing");
+    });
+  });
+
+  describe("update_metadata_pointer", () => {
+    it("re-points a placeholder pointer at the mint itself", async () => {
+      const mint = await placeholderPointerMint();
+
+      await updatePointer(mint, mint);
+
+      expect((await pointerTarget(mint))!.toBase58()).to.equal(mint.toBase58());
+    });
+
+    it("clears the pointer when passed None", async () => {
+      const mint = await placeholderPointerMint();
+
+      await updatePointer(mint, null);
+
+      expect(await pointerTarget(mint)).to.equal(null);
+    });
+
+    it("rejects a signer that is not the pointer authority", async () => {
+      const mint = await placeholderPointerMint();
+      const before = await pointerTarget(mint);
+
+      await expectError(updatePointer(mint, mint, Keypair.generate()), "InvalidAuthority");
+      expect((await pointerTarget(mint))!.toBase58()).to.equal(before!.toBase58());
+    });
+  });
+
+  describe("create_mint_with_external_metadata", () => {
+    const METAPLEX_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+    function metaplexMetadataFor(mint: Keypair): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), METAPLEX_METADATA_PROGRAM_ID.toBuffer(), mint.publicKey.toBuffer()],
+        METAPLEX_METADATA_PROGRAM_ID,
+      )[0];
+    }
+
+    function createExternalMetadataMint(mint: Keypair, metadataAddress: PublicKey, enforceMetaplex: boolean) {
+      return program.methods
+        .createMintWithExternalMetadata(payer.publicKey, metadataAddress, enforceMetaplex, 6)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          registry: registryFor(mint),
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+    }
+
+    it("points at the Metaplex PDA when enforced", async () => {
+      const mint = Keypair.generate();
+
+      await createExternalMetadataMint(mint, metaplexMetadataFor(mint), true);
+
+      expect((await pointerTarget(mint.publicKey))!.toBase58()).to.equal(metaplexMetadataFor(mint).toBase58());
+      expect((await program.account.tokenExtensionMint.fetch(registryFor(mint))).metaplexMetadata).to.equal(true);
+    });
+
+    it("rejects any other address with MetadataPointerMismatch when enforced", async () => {
+      const mint = Keypair.generate();
+
+      await expectError(createExternalMetadataMint(mint, Keypair.generate().publicKey, true), "MetadataPointerMismatch");
+      expect(await connection.getAccountInfo(mint.publicKey)).to.equal(null);
+    });
+
+    it("accepts a free-form address and records it as non-Metaplex", async () => {
+      const mint = Keypair.generate();
+      const metadataAddress = Keypair.generate().publicKey;
+
+      await createExternalMetadataMint(mint, metadataAddress, false);
+
+      expect((await pointerTarget(mint.publicKey))!.toBase58()).to.equal(metadataAddress.toBase58());
+      expect((await program.account.tokenExtensionMint.fetch(registryFor(mint))).metaplexMetadata).to.equal(false);
+    });
+
+    it("still records the Metaplex PDA when it is passed without enforcement", async () => {
+      const mint = Keypair.generate();
+
+      await createExternalMetadataMint(mint, metaplexMetadataFor(mint), false);
+
+      expect((await program.account.tokenExtensionMint.fetch(registryFor(mint))).metaplexMetadata).to.equal(true);
+    });
+  });
+
+  describe("metadata mirror", () => {
+    async function expectMirrorInSync(mint: PublicKey) {
+      const metadata = (await getTokenMetadata(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID))!;
+      const mirror = await program.account.metadataAccount.fetch(mirrorFor(mint));
+
+      expect(mirror.mint.toBase58()).to.equal(mint.toBase58());
+      expect(mirror.name).to.equal(metadata.name);
+      expect(mirror.symbol).to.equal(metadata.symbol);
+      expect(mirror.uri).to.equal(metadata.uri);
+      expect(mirror.updateAuthority.toBase58()).to.equal(metadata.updateAuthority!.toBase58());
+      expect(mirror.additionalMetadata.map((pair: any) => [pair[0], pair[1]])).to.deep.equal(metadata.additionalMetadata);
+      expect(mirror.isMutable).to.equal(true);
+    }
+
+    function closeMirror(mint: PublicKey, authority: Keypair, destination: PublicKey) {
+      return program.methods
+        .closeMetadataMirror()
+        .accountsPartial({ mint, updateAuthority: authority.publicKey, metadataMirror: mirrorFor(mint), destination })
+        .signers(authority === payer ? [] : [authority])
+        .rpc();
+    }
+
+    it("stays in sync across two updates", async () => {
+      const mint = Keypair.generate();
+      await createMirroredMint(mint, false);
+      await expectMirrorInSync(mint.publicKey);
+
+      await updateField(mint.publicKey, "name", "Mirror Token v2");
+      await expectMirrorInSync(mint.publicKey);
+
+      await updateField(mint.publicKey, "colour", "teal");
+      await expectMirrorInSync(mint.publicKey);
+    });
+
+    it("grows the mirror as additional metadata is added", async () => {
+      const mint = Keypair.generate();
+      await createMirroredMint(mint, false);
+      const before = (await connection.getAccountInfo(mirrorFor(mint.publicKey)))!;
+
+      await updateField(mint.publicKey, "description", "d".repeat(300));
+
+      const after = (await connection.getAccountInfo(mirrorFor(mint.publicKey)))!;
+      expect(after.data.length).to.equal(before.data.length + 4 + "description".length + 4 + 300);
+      expect(after.lamports).to.be.at.least(await connection.getMinimumBalanceForRentExemption(after.data.length));
+      await expectMirrorInSync(mint.publicKey);
+    });
+
+    it("is left alone by updates until init_metadata_mirror runs", async () => {
+      const mint = Keypair.generate();
+      await createMetadataMint(mint, false, null);
+      await updateField(mint.publicKey, "colour", "teal");
+      expect(await connection.getAccountInfo(mirrorFor(mint.publicKey))).to.equal(null);
+
+      await program.methods
+        .initMetadataMirror()
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          metadataMirror: mirrorFor(mint.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await expectMirrorInSync(mint.publicKey);
+
+      await updateField(mint.publicKey, "colour", "navy");
+      await expectMirrorInSync(mint.publicKey);
+    });
+
+    it("closes to the update authority only", async () => {
+      const mint = Keypair.generate();
+      await createMirroredMint(mint, false);
+      const destination = Keypair.generate().publicKey;
+
+      await expectError(closeMirror(mint.publicKey, Keypair.generate(), destination), "InvalidAuthority");
+
+      const mirrorLamports = (await connection.getAccountInfo(mirrorFor(mint.publicKey)))!.lamports;
+      await closeMirror(mint.publicKey, payer, destination);
+
+      expect(await connection.getAccountInfo(mirrorFor(mint.publicKey))).to.equal(null);
+      expect(await connection.getBalance(destination)).to.equal(mirrorLamports);
+    });
+
+    it("drops removed keys from the mirror", async () => {
+      const mint = Keypair.generate();
+      await createMirroredMint(mint, false);
+      await updateField(mint.publicKey, "colour", "teal");
+
+      await removeField(mint.publicKey, "colour", false);
+
+      await expectMirrorInSync(mint.publicKey);
+      expect((await program.account.metadataAccount.fetch(mirrorFor(mint.publicKey))).additionalMetadata).to.deep.equal([]);
+    });
+
+    it("follows an update authority rotation when closing", async () => {
+      const mint = Keypair.generate();
+      await createMirroredMint(mint, false);
+      const newAuthority = Keypair.generate();
+
+      await setUpdateAuthority(mint.publicKey, newAuthority.publicKey, false);
+      await expectMirrorInSync(mint.publicKey);
+
+      const destination = Keypair.generate().publicKey;
+      await expectError(closeMirror(mint.publicKey, payer, destination), "InvalidAuthority");
+      await closeMirror(mint.publicKey, newAuthority, destination);
+      expect(await connection.getAccountInfo(mirrorFor(mint.publicKey))).to.equal(null);
+    });
+
+    it("lets the mint authority close the mirror once the update authority is renounced", async () => {
+      const mint = Keypair.generate();
+      await createMirroredMint(mint, false);
+
+      await setUpdateAuthority(mint.publicKey, null, true);
+      expect((await program.account.metadataAccount.fetch(mirrorFor(mint.publicKey))).isMutable).to.equal(false);
+
+      await closeMirror(mint.publicKey, payer, Keypair.generate().publicKey);
+      expect(await connection.getAccountInfo(mirrorFor(mint.publicKey))).to.equal(null);
+    });
+  });
+
+  describe("reserved metadata field names", () => {
+    function migrateField(mint: PublicKey, key: string) {
+      return program.methods
+        .migrateMetadataField(key)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint,
+          updateAuthority: payer.publicKey,
+          metadataMirror: mirrorFor(mint),
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+    }
+
+    // a plain string field is encoded as an additional-metadata key, as the handler used to do
+    async function mintWithStrayKey(key: string, value: string): Promise<PublicKey> {
+      const mint = Keypair.generate();
+      await createMetadataMint(mint, false, null);
+      await tokenMetadataUpdateField(connection, payer, mint.publicKey, payer, key, value, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+      return mint.publicKey;
+    }
+
+    it("updates the base fields case-insensitively without adding keys", async () => {
+      const mint = Keypair.generate();
+      await createMetadataMint(mint, false, null);
+
+      await updateField(mint.publicKey, "NAME", "Renamed Token");
+      await updateField(mint.publicKey, "Symbol", "RNM");
+      await updateField(mint.publicKey, "uri", "ipfs://renamed");
+
+      const metadata = (await getTokenMetadata(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID))!;
+      expect(metadata.name).to.equal("Renamed Token");
+      expect(metadata.symbol).to.equal("RNM");
+      expect(metadata.uri).to.equal("ipfs://renamed");
+      expect(metadata.additionalMetadata).to.deep.equal([]);
+    });
+
+    it("migrates a stray name key into the base name", async () => {
+      const mint = await mintWithStrayKey("Name", "Intended Token");
+
+      await migrateField(mint, "Name");
+
+      const metadata = (await getTokenMetadata(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID))!;
+      expect(metadata.name).to.equal("Intended Token");
+      expect(metadata.additionalMetadata).to.deep.equal([]);
+    });
+
+    it("refuses to migrate a key that is not a reserved name", async () => {
+      const mint = await mintWithStrayKey("colour", "teal");
+
+      await expectError(migrateField(mint, "colour"), "InvalidMetadata");
+    });
+
+    it("rejects a migration when the stray key is missing", async () => {
+      const mint = Keypair.generate();
+      await createMetadataMint(mint, false, null);
+
+      await expectError(migrateField(mint.publicKey, "name"), "MetadataFieldNotFound");
+    });
+  });
+
+  describe("immutable metadata at creation", () => {
+    it("drops the update authority and rejects updates with MetadataImmutable", async () => {
+      const mint = Keypair.generate();
+      await createMirroredMint(mint, true);
+
+      const metadata = (await getTokenMetadata(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID))!;
+      expect(metadata.updateAuthority).to.equal(undefined);
+      expect((await program.account.metadataAccount.fetch(mirrorFor(mint.publicKey))).isMutable).to.equal(false);
+
+      await expectError(updateField(mint.publicKey, "name", "Changed"), "MetadataImmutable");
+      await expectError(updateField(mint.publicKey, "colour", "teal"), "MetadataImmutable");
+      expect((await getTokenMetadata(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID))!.name).to.equal("Mirror Token");
+    });
+
+    it("keeps a mutable mint updatable", async () => {
+      const mint = Keypair.generate();
+      await createMirroredMint(mint, false);
+
+      await updateField(mint.publicKey, "name", "Changed");
+
+      expect((await getTokenMetadata(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID))!.name).to.equal("Changed");
+      expect((await program.account.metadataAccount.fetch(mirrorFor(mint.publicKey))).isMutable).to.equal(true);
+    });
+
+    it("lets the mint authority close the mirror of immutable metadata", async () => {
+      const mint = Keypair.generate();
+      await createMirroredMint(mint, true);
+
+      await program.methods
+        .closeMetadataMirror()
+        .accountsPartial({
+          mint: mint.publicKey,
+          updateAuthority: payer.publicKey,
+          metadataMirror: mirrorFor(mint.publicKey),
+          destination: payer.publicKey,
+        })
+        .rpc();
+
+      expect(await connection.getAccountInfo(mirrorFor(mint.publicKey))).to.equal(null);
+    });
+  });
+
+  describe("set_metadata_pointer_authority", () => {
+    function setPointerAuthority(mint: PublicKey, newAuthority: PublicKey | null, confirmRenounce: boolean, authority: Keypair = payer) {
+      return program.methods
+        .setMetadataPointerAuthority(newAuthority, confirmRenounce)
+        .accountsPartial({ mint, authority: authority.publicKey, tokenProgram: TOKEN_2022_PROGRAM_ID })
+        .signers(authority === payer ? [] : [authority])
+        .rpc();
+    }
+
+    async function pointerAuthority(mint: PublicKey): Promise<PublicKey | null> {
+      const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      return getMetadataPointerState(mintInfo)!.authority;
+    }
+
+    it("hands the pointer to the new authority and stops the old one", async () => {
+      const mint = await placeholderPointerMint();
+      const dao = Keypair.generate();
+
+      await setPointerAuthority(mint, dao.publicKey, false);
+
+      expect((await pointerAuthority(mint))!.toBase58()).to.equal(dao.publicKey.toBase58());
+      await updatePointer(mint, mint, dao);
+      expect((await pointerTarget(mint))!.toBase58()).to.equal(mint.toBase58());
+
+      await expectError(updatePointer(mint, null), "InvalidAuthority");
+    });
+
+    it("rejects a signer that is not the pointer authority", async () => {
+      const mint = await placeholderPointerMint();
+      const stranger = Keypair.generate();
+
+      await expectError(setPointerAuthority(mint, stranger.publicKey, false, stranger), "InvalidAuthority");
+      expect((await pointerAuthority(mint))!.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("requires confirmation before freezing the pointer", async () => {
+      const mint = await placeholderPointerMint();
+
+      await expectError(setPointerAuthority(mint, null, false), "AuthorityRevocationNotConfirmed");
+
+      await setPointerAuthority(mint, null, true);
+      expect(await pointerAuthority(mint)).to.equal(null);
+      await expectError(updatePointer(mint, mint), "InvalidAuthority");
+    });
+  });
+
+  describe("create_nft", () => {
+    function createNft(mint: Keypair, recipient: PublicKey, keepMintAuthority: boolean, recipientAccount: PublicKey = recipient) {
+      return program.methods
+        .createNft("Badge #1", "BDG", "https://example.com/badge/1.json", recipient, keepMintAuthority)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          recipient: recipientAccount,
+          recipientTokenAccount: getAssociatedTokenAddressSync(mint.publicKey, recipientAccount, false, TOKEN_2022_PROGRAM_ID),
+          registry: registryFor(mint),
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+    }
+
+    it("mints a single token with readable metadata and no mint authority", async () => {
+      const mint = Keypair.generate();
+      const recipient = Keypair.generate().publicKey;
+
+      await createNft(mint, recipient, false);
+
+      const mintInfo = await getMint(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(mintInfo.decimals).to.equal(0);
+      expect(mintInfo.supply).to.equal(BigInt(1));
+      expect(mintInfo.mintAuthority).to.equal(null);
+
+      const tokenAccount = getAssociatedTokenAddressSync(mint.publicKey, recipient, false, TOKEN_2022_PROGRAM_ID);
+      expect((await getAccount(connection, tokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount).to.equal(BigInt(1));
+
+      const metadata = (await getTokenMetadata(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID))!;
+      expect(metadata.name).to.equal("Badge #1");
+      expect(metadata.symbol).to.equal("BDG");
+      expect(metadata.uri).to.equal("https://example.com/badge/1.json");
+    });
+
+    it("keeps the mint authority for editions when asked", async () => {
+      const mint = Keypair.generate();
+
+      await createNft(mint, Keypair.generate().publicKey, true);
+
+      const mintInfo = await getMint(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(mintInfo.supply).to.equal(BigInt(1));
+      expect(mintInfo.mintAuthority!.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("rejects a recipient account that does not match the argument", async () => {
+      const mint = Keypair.generate();
+
+      await expectError(createNft(mint, Keypair.generate().publicKey, false, Keypair.generate().publicKey), "ConstraintAddress");
+      expect(await connection.getAccountInfo(mint.publicKey)).to.equal(null);
+    });
+  });
+});
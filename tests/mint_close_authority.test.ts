@@ -0,0 +1,373 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  burn,
+  closeAccount,
+  createAssociatedTokenAccount,
+  getMint,
+  getMintCloseAuthority,
+  getTokenMetadata,
+  mintTo,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { connection, eventsFrom, expectError, payer, pda, program } from "./helpers";
+
+describe("Mint Close Authority", () => {
+  async function createCloseMint(
+    closeAuthority: PublicKey,
+    usePdaCloseAuthority = false,
+  ): Promise<PublicKey> {
+    const mint = Keypair.generate();
+
+    await program.methods
+      .createMintWithCloseAuthority(closeAuthority, 6, usePdaCloseAuthority, false)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        registry: null,
+        closeConfig: usePdaCloseAuthority ? pda([Buffer.from("mint_close_config"), mint.publicKey.toBuffer()]) : null,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+
+    return mint.publicKey;
+  }
+
+  function closeMint(mint: PublicKey, destination: PublicKey, closeAuthority: Keypair = payer) {
+    return program.methods
+      .closeMint()
+      .accountsPartial({
+        mint,
+        destination,
+        closeAuthority: closeAuthority.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers(closeAuthority === payer ? [] : [closeAuthority])
+      .rpc();
+  }
+
+  describe("close_mint", () => {
+    it("refuses to close a mint with outstanding supply", async () => {
+      const mint = await createCloseMint(payer.publicKey);
+      const holder = await createAssociatedTokenAccount(
+        connection, payer, mint, payer.publicKey, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+      await mintTo(connection, payer, mint, holder, payer, 1, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+
+      await expectError(closeMint(mint, payer.publicKey), "MintSupplyNotZero");
+      expect(await connection.getAccountInfo(mint)).to.not.equal(null);
+    });
+
+    it("rejects a signer that is not the configured close authority", async () => {
+      const mint = await createCloseMint(payer.publicKey);
+      const stranger = Keypair.generate();
+
+      await expectError(closeMint(mint, payer.publicKey, stranger), "InvalidCloseAuthority");
+      expect(await connection.getAccountInfo(mint)).to.not.equal(null);
+    });
+
+    it("closes an empty mint and pays its rent to the destination", async () => {
+      const mint = await createCloseMint(payer.publicKey);
+      const destination = Keypair.generate().publicKey;
+      const rent = (await connection.getAccountInfo(mint))!.lamports;
+
+      await closeMint(mint, destination);
+
+      expect(await connection.getAccountInfo(mint)).to.equal(null);
+      expect(await connection.getBalance(destination)).to.equal(rent);
+    });
+  });
+
+  describe("close_mint_with_pda", () => {
+    const governance = Keypair.generate();
+    const successor = Keypair.generate();
+    let mint: PublicKey;
+    let closeAuthority: PublicKey;
+    let closeConfig: PublicKey;
+
+    function closeMintWithPda(authority: Keypair, destination: PublicKey = payer.publicKey) {
+      return program.methods
+        .closeMintWithPda()
+        .accountsPartial({
+          mint,
+          destination,
+          closeAuthority,
+          authority: authority.publicKey,
+          closeConfig,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    function setMintCloseConfig(authority: Keypair, newAuthority: PublicKey) {
+      return program.methods
+        .setMintCloseConfig(newAuthority)
+        .accountsPartial({ mint, authority: authority.publicKey, closeConfig })
+        .signers([authority])
+        .rpc();
+    }
+
+    before(async () => {
+      mint = await createCloseMint(governance.publicKey, true);
+      closeAuthority = pda([Buffer.from("mint_close"), mint.toBuffer()]);
+      closeConfig = pda([Buffer.from("mint_close_config"), mint.toBuffer()]);
+      console.log(`   PDA Close Authority: ${closeAuthority.toBase58()}`);
+    });
+
+    it("sets the derived PDA as close authority and records governance", async () => {
+      const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(getMintCloseAuthority(mintInfo)!.closeAuthority.toBase58()).to.equal(closeAuthority.toBase58());
+
+      const config = await program.account.mintCloseConfig.fetch(closeConfig);
+      expect(config.authority.toBase58()).to.equal(governance.publicKey.toBase58());
+    });
+
+    it("requires the close config account when the PDA is requested", async () => {
+      const mintKeypair = Keypair.generate();
+
+      await expectError(
+        program.methods
+          .createMintWithCloseAuthority(governance.publicKey, 6, true, false)
+          .accountsPartial({
+            payer: payer.publicKey,
+            mint: mintKeypair.publicKey,
+            mintAuthority: payer.publicKey,
+            registry: null,
+            closeConfig: null,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([mintKeypair])
+          .rpc(),
+        "CloseConfigMissing",
+      );
+    });
+
+    it("rejects a close config account passed without the PDA", async () => {
+      const mintKeypair = Keypair.generate();
+      const unusedConfig = pda([Buffer.from("mint_close_config"), mintKeypair.publicKey.toBuffer()]);
+
+      await expectError(
+        program.methods
+          .createMintWithCloseAuthority(governance.publicKey, 6, false, false)
+          .accountsPartial({
+            payer: payer.publicKey,
+            mint: mintKeypair.publicKey,
+            mintAuthority: payer.publicKey,
+            registry: null,
+            closeConfig: unusedConfig,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([mintKeypair])
+          .rpc(),
+        "UnexpectedCloseConfig",
+      );
+      expect(await connection.getAccountInfo(unusedConfig)).to.equal(null);
+    });
+
+    it("rejects a close signed by anyone but governance", async () => {
+      await expectError(closeMintWithPda(Keypair.generate()), "InvalidAuthority");
+      expect(await connection.getAccountInfo(mint)).to.not.equal(null);
+    });
+
+    it("lets only governance hand over the close config", async () => {
+      await expectError(setMintCloseConfig(successor, successor.publicKey), "InvalidAuthority");
+
+      await setMintCloseConfig(governance, successor.publicKey);
+
+      const config = await program.account.mintCloseConfig.fetch(closeConfig);
+      expect(config.authority.toBase58()).to.equal(successor.publicKey.toBase58());
+    });
+
+    it("closes the mint and its close config when governance signs", async () => {
+      await expectError(closeMintWithPda(governance), "InvalidAuthority");
+
+      await closeMintWithPda(successor);
+
+      expect(await connection.getAccountInfo(mint)).to.equal(null);
+      expect(await connection.getAccountInfo(closeConfig)).to.equal(null);
+    });
+  });
+
+  describe("set_close_authority", () => {
+    const multisig = Keypair.generate();
+    let mint: PublicKey;
+
+    function setCloseAuthority(closeAuthority: Keypair, newAuthority: PublicKey | null, confirmRevoke: boolean) {
+      return program.methods
+        .setCloseAuthority(newAuthority, confirmRevoke)
+        .accountsPartial({
+          mint,
+          closeAuthority: closeAuthority.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers(closeAuthority === payer ? [] : [closeAuthority])
+        .rpc();
+    }
+
+    async function currentCloseAuthority(): Promise<PublicKey> {
+      const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      return getMintCloseAuthority(mintInfo)!.closeAuthority;
+    }
+
+    before(async () => {
+      mint = await createCloseMint(payer.publicKey);
+    });
+
+    it("rejects a signer that is not the current close authority", async () => {
+      await expectError(setCloseAuthority(multisig, multisig.publicKey, false), "InvalidCloseAuthority");
+      expect((await currentCloseAuthority()).toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("rotates the close authority", async () => {
+      await setCloseAuthority(payer, multisig.publicKey, false);
+      expect((await currentCloseAuthority()).toBase58()).to.equal(multisig.publicKey.toBase58());
+    });
+
+    it("refuses to revoke without confirmation", async () => {
+      await expectError(setCloseAuthority(multisig, null, false), "AuthorityRevocationNotConfirmed");
+      expect((await currentCloseAuthority()).toBase58()).to.equal(multisig.publicKey.toBase58());
+    });
+
+    it("revokes the close authority once confirmed", async () => {
+      await setCloseAuthority(multisig, null, true);
+      expect((await currentCloseAuthority()).equals(PublicKey.default)).to.equal(true);
+    });
+  });
+
+  describe("registry entry", () => {
+    function createRegisteredCloseMint(mint: Keypair, register: boolean, registry: PublicKey | null) {
+      return program.methods
+        .createMintWithCloseAuthority(payer.publicKey, 6, false, register)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          registry,
+          closeConfig: null,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+    }
+
+    it("records the close authority extension when register is set", async () => {
+      const mint = Keypair.generate();
+      const registry = pda([Buffer.from("registry"), mint.publicKey.toBuffer()]);
+
+      await createRegisteredCloseMint(mint, true, registry);
+
+      const entry = await program.account.tokenExtensionMint.fetch(registry);
+      expect(entry.mint.toBase58()).to.equal(mint.publicKey.toBase58());
+      expect(entry.authority.toBase58()).to.equal(payer.publicKey.toBase58());
+      expect(Array.from(entry.extensionTypes)).to.deep.equal([ExtensionType.MintCloseAuthority]);
+      expect(entry.createdAt.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("requires the registry account when register is set", async () => {
+      await expectError(createRegisteredCloseMint(Keypair.generate(), true, null), "RegistryAccountMissing");
+    });
+
+    it("rejects a registry account passed without register", async () => {
+      const mint = Keypair.generate();
+      const registry = pda([Buffer.from("registry"), mint.publicKey.toBuffer()]);
+
+      await expectError(createRegisteredCloseMint(mint, false, registry), "UnexpectedRegistryAccount");
+      expect(await connection.getAccountInfo(registry)).to.equal(null);
+    });
+  });
+
+  describe("create_closeable_mint_with_metadata", () => {
+    it("recovers the full metadata-bearing rent after mint, burn and close", async () => {
+      const mint = Keypair.generate();
+      const destination = Keypair.generate().publicKey;
+
+      await program.methods
+        .createCloseableMintWithMetadata(payer.publicKey, "Wrapped Asset", "wAST", "https://example.com/wast.json", 6)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+
+      const mintInfo = await getMint(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(getMintCloseAuthority(mintInfo)!.closeAuthority.toBase58()).to.equal(payer.publicKey.toBase58());
+      const metadata = await getTokenMetadata(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(metadata!.name).to.equal("Wrapped Asset");
+      expect(metadata!.mint.toBase58()).to.equal(mint.publicKey.toBase58());
+
+      const holder = await createAssociatedTokenAccount(
+        connection, payer, mint.publicKey, payer.publicKey, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+      await mintTo(connection, payer, mint.publicKey, holder, payer, 1_000, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+      await burn(connection, payer, holder, mint.publicKey, payer, 1_000, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+      await closeAccount(connection, payer, holder, payer.publicKey, payer, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+
+      const rent = (await connection.getAccountInfo(mint.publicKey))!.lamports;
+      await closeMint(mint.publicKey, destination);
+
+      expect(await connection.getAccountInfo(mint.publicKey)).to.equal(null);
+      expect(await connection.getBalance(destination)).to.equal(rent);
+    });
+  });
+
+  describe("mint lifecycle events", () => {
+    it("emits MintCreated with the extension list and MintClosed with the reclaimed lamports", async () => {
+      const mint = Keypair.generate();
+      const destination = Keypair.generate().publicKey;
+
+      const createSignature = await program.methods
+        .createMintWithCloseAuthority(payer.publicKey, 6, false, false)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          registry: null,
+          closeConfig: null,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc({ commitment: "confirmed" });
+
+      const created = (await eventsFrom(createSignature)).find((e) => e.name === "mintCreated");
+      expect(created!.data.mint.toBase58()).to.equal(mint.publicKey.toBase58());
+      expect(Array.from(created!.data.extensionTypes)).to.deep.equal([ExtensionType.MintCloseAuthority]);
+
+      const rent = (await connection.getAccountInfo(mint.publicKey))!.lamports;
+      const closeSignature = await program.methods
+        .closeMint()
+        .accountsPartial({
+          mint: mint.publicKey,
+          destination,
+          closeAuthority: payer.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const closed = (await eventsFrom(closeSignature)).find((e) => e.name === "mintClosed");
+      expect(closed!.data.mint.toBase58()).to.equal(mint.publicKey.toBase58());
+      expect(closed!.data.destination.toBase58()).to.equal(destination.toBase58());
+      expect(closed!.data.closeAuthority.toBase58()).to.equal(payer.publicKey.toBase58());
+      expect(closed!.data.lamportsReclaimed.toNumber()).to.equal(rent);
+    });
+  });
+});
@@ -0,0 +1,359 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  AuthorityType,
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  freezeAccount,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getExtensionTypes,
+  getMint,
+  setAuthority,
+  transferChecked,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { connection, expectError, payer, pda, program } from "./helpers";
+
+describe("Non-Transferable", () => {
+  async function createSoulboundMint(freezeAuthority: PublicKey | null = null): Promise<PublicKey> {
+    const mint = Keypair.generate();
+
+    await program.methods
+      .createNonTransferableMint(0, freezeAuthority, null)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        issuerRegistry: null,
+        sbtMeta: null,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+
+    return mint.publicKey;
+  }
+
+  async function createRevocableSoulboundMint(permanentDelegate: PublicKey): Promise<PublicKey> {
+    const mint = Keypair.generate();
+
+    await program.methods
+      .createRevocableSoulboundMint(permanentDelegate, 0)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        issuerRegistry: null,
+        sbtMeta: pda([Buffer.from("sbt_meta"), mint.publicKey.toBuffer()]),
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+
+    return mint.publicKey;
+  }
+
+  function mintSoulboundTo(mint: PublicKey, recipient: PublicKey, amount: number, mintAuthority: Keypair = payer) {
+    return program.methods
+      .mintSoulboundTo(new anchor.BN(amount))
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint,
+        mintAuthority: mintAuthority.publicKey,
+        recipient,
+        recipientTokenAccount: getAssociatedTokenAddressSync(mint, recipient, false, TOKEN_2022_PROGRAM_ID),
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers(mintAuthority === payer ? [] : [mintAuthority])
+      .rpc();
+  }
+
+  function burnSoulbound(mint: PublicKey, tokenAccount: PublicKey, owner: Keypair, amount: number) {
+    return program.methods
+      .burnSoulbound(new anchor.BN(amount))
+      .accountsPartial({ tokenAccount, mint, owner: owner.publicKey, tokenProgram: TOKEN_2022_PROGRAM_ID })
+      .signers([owner])
+      .rpc();
+  }
+
+  describe("mint, distribute and burn", () => {
+    const holder = Keypair.generate();
+    let mint: PublicKey;
+    let holderAccount: PublicKey;
+
+    before(async () => {
+      mint = await createSoulboundMint();
+      holderAccount = getAssociatedTokenAddressSync(mint, holder.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    });
+
+    it("creates the holder account and mints into it", async () => {
+      await mintSoulboundTo(mint, holder.publicKey, 5);
+
+      const account = await getAccount(connection, holderAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(account.amount).to.equal(BigInt(5));
+      expect(getExtensionTypes(account.tlvData)).to.include.members([
+        ExtensionType.ImmutableOwner,
+        ExtensionType.NonTransferableAccount,
+      ]);
+    });
+
+    it("rejects minting by someone other than the mint authority", async () => {
+      await expectError(mintSoulboundTo(mint, holder.publicKey, 1, Keypair.generate()), "owner does not match");
+    });
+
+    it("blocks transfer_checked with the token program's non-transferable error", async () => {
+      const other = await createAssociatedTokenAccount(
+        connection, payer, mint, payer.publicKey, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+
+      await expectError(
+        transferChecked(
+          connection, payer, holderAccount, mint, other, holder, 1, 0, [],
+          { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+        ),
+        "Transfer is disabled for this mint",
+      );
+    });
+
+    it("lets only the holder burn", async () => {
+      await expectError(burnSoulbound(mint, holderAccount, Keypair.generate(), 1), "owner does not match");
+
+      await burnSoulbound(mint, holderAccount, holder, 2);
+
+      const account = await getAccount(connection, holderAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(account.amount).to.equal(BigInt(3));
+    });
+  });
+
+  describe("revoke_soulbound", () => {
+    const issuer = Keypair.generate();
+    const holder = Keypair.generate();
+    let mint: PublicKey;
+    let holderAccount: PublicKey;
+
+    function revoke(permanentDelegate: Keypair, amount: number) {
+      return program.methods
+        .revokeSoulbound(new anchor.BN(amount))
+        .accountsPartial({
+          tokenAccount: holderAccount,
+          mint,
+          permanentDelegate: permanentDelegate.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([permanentDelegate])
+        .rpc();
+    }
+
+    async function balance(): Promise<bigint> {
+      return (await getAccount(connection, holderAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount;
+    }
+
+    before(async () => {
+      mint = await createRevocableSoulboundMint(issuer.publicKey);
+      holderAccount = getAssociatedTokenAddressSync(mint, holder.publicKey, false, TOKEN_2022_PROGRAM_ID);
+      await mintSoulboundTo(mint, holder.publicKey, 3);
+    });
+
+    it("lets the issuer revoke without the holder's signature", async () => {
+      await revoke(issuer, 1);
+      expect(await balance()).to.equal(BigInt(2));
+    });
+
+    it("rejects a random key as the permanent delegate", async () => {
+      await expectError(revoke(Keypair.generate(), 1), "InvalidDelegate");
+      expect(await balance()).to.equal(BigInt(2));
+    });
+
+    it("still lets the holder burn their own token", async () => {
+      await burnSoulbound(mint, holderAccount, holder, 1);
+      expect(await balance()).to.equal(BigInt(1));
+    });
+  });
+
+  describe("create_non_transferable_mint label and freeze authority", () => {
+    const freezeAuthority = Keypair.generate().publicKey;
+
+    function createLabeledMint(mint: Keypair, label: string | null, withSbtMeta: boolean) {
+      return program.methods
+        .createNonTransferableMint(0, freezeAuthority, label)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          issuerRegistry: null,
+          sbtMeta: withSbtMeta ? pda([Buffer.from("sbt_meta"), mint.publicKey.toBuffer()]) : null,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+    }
+
+    it("sets the freeze authority and stores the label in the sbt_meta PDA", async () => {
+      const mint = Keypair.generate();
+      const sbtMeta = pda([Buffer.from("sbt_meta"), mint.publicKey.toBuffer()]);
+
+      await createLabeledMint(mint, "employee vesting points", true);
+
+      const mintInfo = await getMint(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(mintInfo.freezeAuthority!.toBase58()).to.equal(freezeAuthority.toBase58());
+
+      const meta = await program.account.sbtMeta.fetch(sbtMeta);
+      expect(meta.payer.toBase58()).to.equal(payer.publicKey.toBase58());
+      expect(meta.createdAt.toNumber()).to.be.greaterThan(0);
+
+      const label = await program.methods.getSbtLabel().accountsPartial({ mint: mint.publicKey, sbtMeta }).view();
+      expect(label).to.equal("employee vesting points");
+    });
+
+    it("rejects a label longer than 64 characters", async () => {
+      await expectError(createLabeledMint(Keypair.generate(), "x".repeat(65), true), "LabelTooLong");
+    });
+
+    it("requires the sbt_meta account when a label is given", async () => {
+      await expectError(createLabeledMint(Keypair.generate(), "no meta", false), "SbtMetaAccountMissing");
+    });
+
+    it("skips the sbt_meta PDA when there is no label", async () => {
+      const mint = Keypair.generate();
+
+      await createLabeledMint(mint, null, false);
+
+      expect(await connection.getAccountInfo(pda([Buffer.from("sbt_meta"), mint.publicKey.toBuffer()]))).to.equal(null);
+    });
+  });
+
+  describe("closing soulbound accounts", () => {
+    it("burns the remaining balance and closes to the holder's destination", async () => {
+      const holder = Keypair.generate();
+      const destination = Keypair.generate().publicKey;
+      const mint = await createSoulboundMint();
+      const holderAccount = getAssociatedTokenAddressSync(mint, holder.publicKey, false, TOKEN_2022_PROGRAM_ID);
+      await mintSoulboundTo(mint, holder.publicKey, 2);
+      const rent = (await connection.getAccountInfo(holderAccount))!.lamports;
+
+      await program.methods
+        .closeSoulboundAccount()
+        .accountsPartial({
+          tokenAccount: holderAccount,
+          mint,
+          owner: holder.publicKey,
+          destination,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([holder])
+        .rpc();
+
+      expect(await connection.getAccountInfo(holderAccount)).to.equal(null);
+      expect(await connection.getBalance(destination)).to.equal(rent);
+      expect((await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID)).supply).to.equal(BigInt(0));
+    });
+
+    it("refuses to close a frozen account", async () => {
+      const holder = Keypair.generate();
+      const mint = await createSoulboundMint(payer.publicKey);
+      const holderAccount = getAssociatedTokenAddressSync(mint, holder.publicKey, false, TOKEN_2022_PROGRAM_ID);
+      await mintSoulboundTo(mint, holder.publicKey, 1);
+      await freezeAccount(connection, payer, holderAccount, mint, payer, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+
+      await expectError(
+        program.methods
+          .closeSoulboundAccount()
+          .accountsPartial({
+            tokenAccount: holderAccount,
+            mint,
+            owner: holder.publicKey,
+            destination: holder.publicKey,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([holder])
+          .rpc(),
+        "AccountFrozen",
+      );
+      expect(await connection.getAccountInfo(holderAccount)).to.not.equal(null);
+    });
+
+    describe("issuer-driven revoke and close", () => {
+      const permanentDelegate = Keypair.generate();
+      let mint: PublicKey;
+      let sbtMeta: PublicKey;
+
+      function revokeAndClose(tokenAccount: PublicKey, delegate: Keypair = permanentDelegate, issuer: Keypair = payer) {
+        return program.methods
+          .revokeAndCloseSoulboundAccount()
+          .accountsPartial({
+            tokenAccount,
+            mint,
+            permanentDelegate: delegate.publicKey,
+            sbtMeta,
+            issuer: issuer.publicKey,
+            originalPayer: payer.publicKey,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers(issuer === payer ? [delegate] : [delegate, issuer])
+          .rpc();
+      }
+
+      async function credentialFor(holder: Keypair, handOverCloseAuthority: boolean): Promise<PublicKey> {
+        const holderAccount = getAssociatedTokenAddressSync(mint, holder.publicKey, false, TOKEN_2022_PROGRAM_ID);
+        await mintSoulboundTo(mint, holder.publicKey, 1);
+        if (handOverCloseAuthority) {
+          await setAuthority(
+            connection, payer, holderAccount, holder, AuthorityType.CloseAccount, permanentDelegate.publicKey,
+            [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+          );
+        }
+        return holderAccount;
+      }
+
+      before(async () => {
+        mint = await createRevocableSoulboundMint(permanentDelegate.publicKey);
+        sbtMeta = pda([Buffer.from("sbt_meta"), mint.toBuffer()]);
+      });
+
+      it("records the issuer and payer in sbt_meta", async () => {
+        const meta = await program.account.sbtMeta.fetch(sbtMeta);
+        expect(meta.issuer.toBase58()).to.equal(payer.publicKey.toBase58());
+        expect(meta.payer.toBase58()).to.equal(payer.publicKey.toBase58());
+      });
+
+      it("burns and closes with the rent sent back to the original payer", async () => {
+        const holderAccount = await credentialFor(Keypair.generate(), true);
+
+        await revokeAndClose(holderAccount);
+
+        expect(await connection.getAccountInfo(holderAccount)).to.equal(null);
+      });
+
+      it("rejects a signer that is not the recorded issuer", async () => {
+        const holderAccount = await credentialFor(Keypair.generate(), true);
+
+        await expectError(revokeAndClose(holderAccount, permanentDelegate, Keypair.generate()), "InvalidAuthority");
+        expect(await connection.getAccountInfo(holderAccount)).to.not.equal(null);
+      });
+
+      it("rejects a signer that is not the permanent delegate", async () => {
+        const holderAccount = await credentialFor(Keypair.generate(), true);
+
+        await expectError(revokeAndClose(holderAccount, Keypair.generate()), "InvalidDelegate");
+      });
+
+      it("requires the holder to have handed the close authority to the delegate", async () => {
+        const holderAccount = await credentialFor(Keypair.generate(), false);
+
+        await expectError(revokeAndClose(holderAccount), "InvalidCloseAuthority");
+        expect(await connection.getAccountInfo(holderAccount)).to.not.equal(null);
+      });
+    });
+  });
+});
@@ -0,0 +1,467 @@
+import * as anchor from "@coral-xyz/anchor";
+import { createHash } from "crypto";
+import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY, Transaction } from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createMint,
+  createTransferCheckedInstruction,
+  freezeAccount,
+  getAccount,
+  getMint,
+  getPermanentDelegate,
+  mintTo,
+  thawAccount,
+  transferChecked,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { MEMO_PROGRAM_ID, connection, eventsFrom, expectError, fundedKeypair, payer, pda, program, provider } from "./helpers";
+
+describe("Permanent Delegate", () => {
+  async function createDelegateMint(delegate: PublicKey = payer.publicKey): Promise<PublicKey> {
+    const mint = Keypair.generate();
+
+    await program.methods
+      .createMintWithPermanentDelegate(delegate, 6)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+
+    await program.methods
+      .initClawbackLog()
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        clawbackLog: clawbackLogFor(mint.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return mint.publicKey;
+  }
+
+  function clawbackLogFor(mint: PublicKey): PublicKey {
+    return pda([Buffer.from("clawback_log"), mint.toBuffer()]);
+  }
+
+  async function fundedAccount(mint: PublicKey, owner: PublicKey, amount: number): Promise<PublicKey> {
+    const tokenAccount = await createAssociatedTokenAccount(connection, payer, mint, owner, undefined, TOKEN_2022_PROGRAM_ID);
+    if (amount > 0) {
+      await mintTo(connection, payer, mint, tokenAccount, payer, amount, [], undefined, TOKEN_2022_PROGRAM_ID);
+    }
+    return tokenAccount;
+  }
+
+  function delegatedBurn(mint: PublicKey, source: PublicKey, amount: number, reason: string, delegate: Keypair = payer) {
+    return program.methods
+      .delegatedBurn(new anchor.BN(amount), reason)
+      .accountsPartial({
+        source,
+        mint,
+        permanentDelegate: delegate.publicKey,
+        clawbackLog: clawbackLogFor(mint),
+        memoProgram: MEMO_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers(delegate === payer ? [] : [delegate]);
+  }
+
+  describe("delegated_burn", () => {
+    it("burns part of a holder's balance without the holder's signature", async () => {
+      const mint = await createDelegateMint();
+      const holder = Keypair.generate();
+      const source = await fundedAccount(mint, holder.publicKey, 1_000_000);
+
+      const sig = await delegatedBurn(mint, source, 400_000, "compromised wallet").rpc({ commitment: "confirmed" });
+
+      expect(Number((await getAccount(connection, source, "confirmed", TOKEN_2022_PROGRAM_ID)).amount)).to.equal(600_000);
+      expect(Number((await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID)).supply)).to.equal(600_000);
+
+      const event = (await eventsFrom(sig)).find((e) => e.name === "delegatedBurnEvent");
+      expect(event).to.not.equal(undefined);
+      expect(event!.data.source.toBase58()).to.equal(source.toBase58());
+      expect(event!.data.amount.toNumber()).to.equal(400_000);
+      expect(event!.data.delegate.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("rejects a signer that is not the permanent delegate", async () => {
+      const mint = await createDelegateMint();
+      const source = await fundedAccount(mint, Keypair.generate().publicKey, 1_000_000);
+
+      await expectError(delegatedBurn(mint, source, 1, "not the delegate", await fundedKeypair()).rpc(), "InvalidDelegate");
+      expect(Number((await getAccount(connection, source, "confirmed", TOKEN_2022_PROGRAM_ID)).amount)).to.equal(1_000_000);
+    });
+
+    it("rejects burning more than the account holds", async () => {
+      const mint = await createDelegateMint();
+      const source = await fundedAccount(mint, Keypair.generate().publicKey, 1_000);
+
+      await expectError(delegatedBurn(mint, source, 1_001, "too much").rpc(), "InsufficientFunds");
+    });
+  });
+
+  describe("governed clawbacks through the delegate PDA", () => {
+    async function createPdaDelegateMint(authority: PublicKey): Promise<PublicKey> {
+      const mint = Keypair.generate();
+
+      await program.methods
+        .createMintWithPdaDelegate(authority, 6)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          delegateConfig: delegateConfigFor(mint.publicKey),
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+
+      return mint.publicKey;
+    }
+
+    function delegateConfigFor(mint: PublicKey): PublicKey {
+      return pda([Buffer.from("delegate_config"), mint.toBuffer()]);
+    }
+
+    function delegateFor(mint: PublicKey): PublicKey {
+      return pda([Buffer.from("delegate"), mint.toBuffer()]);
+    }
+
+    function governedClawback(mint: PublicKey, source: PublicKey, destination: PublicKey, amount: number, authority: Keypair) {
+      return program.methods
+        .governedClawback(new anchor.BN(amount))
+        .accountsPartial({
+          source,
+          destination,
+          mint,
+          authority: authority.publicKey,
+          delegateConfig: delegateConfigFor(mint),
+          delegate: delegateFor(mint),
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    function setDelegateConfig(mint: PublicKey, newAuthority: PublicKey, authority: Keypair) {
+      return program.methods
+        .setDelegateConfig(newAuthority)
+        .accountsPartial({
+          mint,
+          authority: authority.publicKey,
+          delegateConfig: delegateConfigFor(mint),
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    async function balanceOf(tokenAccount: PublicKey): Promise<number> {
+      return Number((await getAccount(connection, tokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount);
+    }
+
+    it("sets the delegate PDA as the mint's permanent delegate", async () => {
+      const mint = await createPdaDelegateMint(payer.publicKey);
+
+      const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(getPermanentDelegate(mintInfo)!.delegate.toBase58()).to.equal(delegateFor(mint).toBase58());
+    });
+
+    it("claws back only with the configured governance signer", async () => {
+      const governance = Keypair.generate();
+      const mint = await createPdaDelegateMint(governance.publicKey);
+      const source = await fundedAccount(mint, Keypair.generate().publicKey, 1_000_000);
+      const treasury = await fundedAccount(mint, payer.publicKey, 0);
+
+      await expectError(governedClawback(mint, source, treasury, 250_000, Keypair.generate()), "InvalidAuthority");
+      expect(await balanceOf(source)).to.equal(1_000_000);
+
+      await governedClawback(mint, source, treasury, 250_000, governance);
+      expect(await balanceOf(source)).to.equal(750_000);
+      expect(await balanceOf(treasury)).to.equal(250_000);
+    });
+
+    it("moves clawback rights to the rotated governance signer", async () => {
+      const governance = Keypair.generate();
+      const newGovernance = Keypair.generate();
+      const mint = await createPdaDelegateMint(governance.publicKey);
+      const source = await fundedAccount(mint, Keypair.generate().publicKey, 1_000_000);
+      const treasury = await fundedAccount(mint, payer.publicKey, 0);
+
+      await expectError(setDelegateConfig(mint, newGovernance.publicKey, Keypair.generate()), "InvalidAuthority");
+      await setDelegateConfig(mint, newGovernance.publicKey, governance);
+
+      await expectError(governedClawback(mint, source, treasury, 1, governance), "InvalidAuthority");
+      await governedClawback(mint, source, treasury, 1, newGovernance);
+      expect(await balanceOf(treasury)).to.equal(1);
+    });
+
+    it("cannot be used as a delegate outside the program", async () => {
+      const mint = await createPdaDelegateMint(payer.publicKey);
+      const source = await fundedAccount(mint, Keypair.generate().publicKey, 1_000_000);
+      const destination = await fundedAccount(mint, payer.publicKey, 0);
+
+      // nobody holds a key for the PDA, so a direct transfer can only name it without its signature
+      const transferIx = createTransferCheckedInstruction(source, mint, destination, delegateFor(mint), 1, 6, [], TOKEN_2022_PROGRAM_ID);
+      transferIx.keys.find((key) => key.pubkey.equals(delegateFor(mint)))!.isSigner = false;
+
+      await expectError(provider.sendAndConfirm(new Transaction().add(transferIx), []), "missing required signature");
+      expect(await balanceOf(source)).to.equal(1_000_000);
+    });
+  });
+
+  describe("clawback reasons", () => {
+    function delegatedTransfer(mint: PublicKey, source: PublicKey, destination: PublicKey, amount: number, reason: string) {
+      return program.methods
+        .delegatedTransfer(new anchor.BN(amount), reason)
+        .accountsPartial({
+          source,
+          destination,
+          mint,
+          permanentDelegate: payer.publicKey,
+          clawbackLog: clawbackLogFor(mint),
+          memoProgram: MEMO_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+    }
+
+    function reasonHash(reason: string): number[] {
+      return Array.from(createHash("sha256").update(reason, "utf8").digest());
+    }
+
+    it("logs the hash of every reason and sends it as a memo", async () => {
+      const mint = await createDelegateMint();
+      const source = await fundedAccount(mint, Keypair.generate().publicKey, 1_000_000);
+      const treasury = await fundedAccount(mint, payer.publicKey, 0);
+
+      const sig = await delegatedTransfer(mint, source, treasury, 100_000, "court order 2026-114");
+      await delegatedBurn(mint, source, 50_000, "sanctions recall").rpc();
+
+      const tx = await connection.getTransaction(sig, { commitment: "confirmed", maxSupportedTransactionVersion: 0 });
+      expect(tx!.meta!.logMessages!.some((log) => log.includes("court order 2026-114"))).to.equal(true);
+
+      const log = await program.account.clawbackLog.fetch(clawbackLogFor(mint));
+      expect(log.mint.toBase58()).to.equal(mint.toBase58());
+      expect(log.entries.length).to.equal(2);
+      expect(log.entries[0].source.toBase58()).to.equal(source.toBase58());
+      expect(log.entries[0].amount.toNumber()).to.equal(100_000);
+      expect(Array.from(log.entries[0].reasonHash)).to.deep.equal(reasonHash("court order 2026-114"));
+      expect(log.entries[1].amount.toNumber()).to.equal(50_000);
+      expect(Array.from(log.entries[1].reasonHash)).to.deep.equal(reasonHash("sanctions recall"));
+    });
+
+    it("rejects an empty reason", async () => {
+      const mint = await createDelegateMint();
+      const source = await fundedAccount(mint, Keypair.generate().publicKey, 1_000_000);
+      const treasury = await fundedAccount(mint, payer.publicKey, 0);
+
+      await expectError(delegatedTransfer(mint, source, treasury, 1, ""), "InvalidMemo");
+      await expectError(delegatedBurn(mint, source, 1, "").rpc(), "InvalidMemo");
+    });
+
+    it("rejects a reason longer than 256 characters", async () => {
+      const mint = await createDelegateMint();
+      const source = await fundedAccount(mint, Keypair.generate().publicKey, 1_000_000);
+
+      await expectError(delegatedBurn(mint, source, 1, "x".repeat(257)).rpc(), "InvalidMemo");
+    });
+  });
+
+  describe("create_permissioned_mint", () => {
+    async function createPermissionedMint(): Promise<{ mint: PublicKey; sig: string }> {
+      const mint = Keypair.generate();
+
+      const sig = await program.methods
+        .createPermissionedMint(payer.publicKey, payer.publicKey, 6)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          registry: pda([Buffer.from("registry"), mint.publicKey.toBuffer()]),
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc({ commitment: "confirmed" });
+
+      await program.methods
+        .initClawbackLog()
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          clawbackLog: clawbackLogFor(mint.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      return { mint: mint.publicKey, sig };
+    }
+
+    async function thawedAccount(mint: PublicKey, owner: PublicKey, amount: number): Promise<PublicKey> {
+      const tokenAccount = await createAssociatedTokenAccount(connection, payer, mint, owner, undefined, TOKEN_2022_PROGRAM_ID);
+      await thawAccount(connection, payer, tokenAccount, mint, payer, [], undefined, TOKEN_2022_PROGRAM_ID);
+      if (amount > 0) {
+        await mintTo(connection, payer, mint, tokenAccount, payer, amount, [], undefined, TOKEN_2022_PROGRAM_ID);
+      }
+      return tokenAccount;
+    }
+
+    it("records both extensions in the registry and the creation event", async () => {
+      const { mint, sig } = await createPermissionedMint();
+      const extensions = [ExtensionType.DefaultAccountState, ExtensionType.PermanentDelegate];
+
+      const entry = await program.account.tokenExtensionMint.fetch(pda([Buffer.from("registry"), mint.toBuffer()]));
+      expect(Array.from(entry.extensionTypes)).to.deep.equal(extensions);
+
+      const event = (await eventsFrom(sig)).find((e) => e.name === "mintCreated");
+      expect(event!.data.mint.toBase58()).to.equal(mint.toBase58());
+      expect(Array.from(event!.data.extensionTypes)).to.deep.equal(extensions);
+    });
+
+    it("starts new accounts frozen", async () => {
+      const { mint } = await createPermissionedMint();
+
+      const tokenAccount = await createAssociatedTokenAccount(connection, payer, mint, Keypair.generate().publicKey, undefined, TOKEN_2022_PROGRAM_ID);
+
+      expect((await getAccount(connection, tokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).isFrozen).to.equal(true);
+      const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(mintInfo.freezeAuthority!.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("lets the delegate transfer out of a thawed account", async () => {
+      const { mint } = await createPermissionedMint();
+      const source = await thawedAccount(mint, Keypair.generate().publicKey, 1_000_000);
+      const treasury = await thawedAccount(mint, payer.publicKey, 0);
+
+      await program.methods
+        .delegatedTransfer(new anchor.BN(300_000), "permissioned recall")
+        .accountsPartial({
+          source,
+          destination: treasury,
+          mint,
+          permanentDelegate: payer.publicKey,
+          clawbackLog: clawbackLogFor(mint),
+          memoProgram: MEMO_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect(Number((await getAccount(connection, treasury, "confirmed", TOKEN_2022_PROGRAM_ID)).amount)).to.equal(300_000);
+    });
+
+    it("stops a holder from transferring out of a frozen account", async () => {
+      const { mint } = await createPermissionedMint();
+      const holder = await fundedKeypair();
+      const source = await thawedAccount(mint, holder.publicKey, 1_000_000);
+      const destination = await thawedAccount(mint, payer.publicKey, 0);
+      await freezeAccount(connection, payer, source, mint, payer, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+      await expectError(
+        transferChecked(connection, holder, source, mint, destination, holder, 1, 6, [], undefined, TOKEN_2022_PROGRAM_ID),
+        "Account is frozen",
+      );
+    });
+  });
+
+  describe("delegate and risk views", () => {
+    const RISK_PERMANENT_DELEGATE = 1 << 0;
+    const RISK_TRANSFER_HOOK = 1 << 1;
+    const RISK_PAUSABLE = 1 << 2;
+    const RISK_DEFAULT_FROZEN = 1 << 3;
+
+    function mintAccounts(mint: Keypair) {
+      return {
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      };
+    }
+
+    function permanentDelegateOf(mint: PublicKey) {
+      return program.methods.getPermanentDelegate().accountsPartial({ mint }).view();
+    }
+
+    function riskOf(mint: PublicKey): Promise<number> {
+      return program.methods.hasRiskyExtensions().accountsPartial({ mint }).view();
+    }
+
+    it("returns the delegate of a permanent delegate mint", async () => {
+      const delegate = Keypair.generate().publicKey;
+      const mint = await createDelegateMint(delegate);
+
+      expect((await permanentDelegateOf(mint))!.toBase58()).to.equal(delegate.toBase58());
+      expect(await riskOf(mint)).to.equal(RISK_PERMANENT_DELEGATE);
+    });
+
+    it("returns None and no risk for a plain mint", async () => {
+      const mint = await createMint(connection, payer, payer.publicKey, null, 6, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+
+      expect(await permanentDelegateOf(mint)).to.equal(null);
+      expect(await riskOf(mint)).to.equal(0);
+    });
+
+    it("flags a transfer hook mint", async () => {
+      const mint = Keypair.generate();
+      await program.methods
+        .createMintWithTransferHook(payer.publicKey, null, 6, false)
+        .accountsPartial({ ...mintAccounts(mint), hookProgram: null })
+        .signers([mint])
+        .rpc();
+
+      expect(await riskOf(mint.publicKey)).to.equal(RISK_TRANSFER_HOOK);
+    });
+
+    it("flags a pausable mint", async () => {
+      const mint = Keypair.generate();
+      await program.methods.createPausableMint(6).accountsPartial(mintAccounts(mint)).signers([mint]).rpc();
+
+      expect(await riskOf(mint.publicKey)).to.equal(RISK_PAUSABLE);
+    });
+
+    it("flags a Frozen default state but not an Initialized one", async () => {
+      const frozen = Keypair.generate();
+      await program.methods
+        .createMintWithDefaultState({ frozen: {} } as any, payer.publicKey, 6)
+        .accountsPartial(mintAccounts(frozen))
+        .signers([frozen])
+        .rpc();
+      expect(await riskOf(frozen.publicKey)).to.equal(RISK_DEFAULT_FROZEN);
+
+      const initialized = Keypair.generate();
+      await program.methods
+        .createMintWithDefaultState({ initialized: {} } as any, payer.publicKey, 6)
+        .accountsPartial(mintAccounts(initialized))
+        .signers([initialized])
+        .rpc();
+      expect(await riskOf(initialized.publicKey)).to.equal(0);
+    });
+
+    it("combines the bits of a permissioned mint", async () => {
+      const mint = Keypair.generate();
+      await program.methods
+        .createPermissionedMint(payer.publicKey, payer.publicKey, 6)
+        .accountsPartial({ ...mintAccounts(mint), registry: pda([Buffer.from("registry"), mint.publicKey.toBuffer()]) })
+        .signers([mint])
+        .rpc();
+
+      expect(await riskOf(mint.publicKey)).to.equal(RISK_PERMANENT_DELEGATE | RISK_DEFAULT_FROZEN);
+    });
+  });
+});
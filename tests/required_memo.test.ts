@@ -0,0 +1,240 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createMint,
+  getAccount,
+  getMemoTransfer,
+  mintTo,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { TestCpiCaller } from "../target/types/test_cpi_caller";
+import { MEMO_PROGRAM_ID, connection, expectError, payer, pda, program } from "./helpers";
+
+describe("Required Memo", () => {
+  const caller = anchor.workspace.TestCpiCaller as Program<TestCpiCaller>;
+
+  async function memoRequired(tokenAccount: PublicKey): Promise<boolean> {
+    const account = await getAccount(connection, tokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+    return getMemoTransfer(account)?.requireIncomingTransferMemos ?? false;
+  }
+
+  describe("PDA owner toggling through CPI", () => {
+    const escrowAuthority = PublicKey.findProgramAddressSync([Buffer.from("escrow")], caller.programId)[0];
+    const escrowTokenAccount = Keypair.generate();
+
+    before(async () => {
+      const mint = await createMint(connection, payer, payer.publicKey, null, 6, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+
+      await program.methods
+        .createAccountWithRequiredMemo(true)
+        .accountsPartial({
+          payer: payer.publicKey,
+          tokenAccount: escrowTokenAccount.publicKey,
+          mint,
+          owner: escrowAuthority,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([escrowTokenAccount])
+        .rpc();
+
+      expect(await memoRequired(escrowTokenAccount.publicKey)).to.equal(true);
+      console.log(`   Escrow Token Account: ${escrowTokenAccount.publicKey.toBase58()}`);
+      console.log(`   Escrow Authority (PDA): ${escrowAuthority.toBase58()}`);
+    });
+
+    it("caller program disables the memo requirement on its PDA-owned account", async () => {
+      await caller.methods
+        .toggleEscrowMemo(false)
+        .accountsPartial({
+          escrowTokenAccount: escrowTokenAccount.publicKey,
+          escrowAuthority,
+          tokenExtensionsProgram: program.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect(await memoRequired(escrowTokenAccount.publicKey)).to.equal(false);
+    });
+
+    it("caller program enables the memo requirement again", async () => {
+      await caller.methods
+        .toggleEscrowMemo(true)
+        .accountsPartial({
+          escrowTokenAccount: escrowTokenAccount.publicKey,
+          escrowAuthority,
+          tokenExtensionsProgram: program.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect(await memoRequired(escrowTokenAccount.publicKey)).to.equal(true);
+    });
+
+    it("rejects a PDA owner that was not signed for", async () => {
+      await expectError(
+        program.methods
+          .disableRequiredMemoTransfersCpi()
+          .accountsPartial({
+            tokenAccount: escrowTokenAccount.publicKey,
+            owner: escrowAuthority,
+            accountRegistry: null,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .rpc(),
+        "AccountNotSigner",
+      );
+
+      expect(await memoRequired(escrowTokenAccount.publicKey)).to.equal(true);
+    });
+
+    it("rejects an owner that does not own the token account", async () => {
+      const stranger = Keypair.generate();
+
+      await expectError(
+        program.methods
+          .disableRequiredMemoTransfersCpi()
+          .accountsPartial({
+            tokenAccount: escrowTokenAccount.publicKey,
+            owner: stranger.publicKey,
+            accountRegistry: null,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .rpc(),
+        "InvalidAuthority",
+      );
+    });
+  });
+
+  describe("memo policy", () => {
+    let mint: PublicKey;
+    let source: PublicKey;
+    let destination: PublicKey;
+    let memoPolicy: PublicKey;
+
+    function transferWithMemo(memo: string) {
+      return program.methods
+        .transferWithMemo(new anchor.BN(1), memo)
+        .accountsPartial({
+          source,
+          mint,
+          destination,
+          authority: payer.publicKey,
+          memoPolicy,
+          memoProgram: MEMO_PROGRAM_ID,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+    }
+
+    function setMemoPolicy(rule: object, mintAuthority: Keypair = payer) {
+      return program.methods
+        .setMemoPolicy(rule as any)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint,
+          mintAuthority: mintAuthority.publicKey,
+          memoPolicy,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers(mintAuthority === payer ? [] : [mintAuthority])
+        .rpc();
+    }
+
+    before(async () => {
+      mint = await createMint(connection, payer, payer.publicKey, null, 0, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+      memoPolicy = pda([Buffer.from("memo_policy"), mint.toBuffer()]);
+      source = await createAssociatedTokenAccount(
+        connection, payer, mint, payer.publicKey, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+      destination = await createAssociatedTokenAccount(
+        connection, payer, mint, Keypair.generate().publicKey, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+      await mintTo(connection, payer, mint, source, payer, 100, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+    });
+
+    it("accepts any memo while the mint has no policy", async () => {
+      expect(await connection.getAccountInfo(memoPolicy)).to.equal(null);
+      await transferWithMemo("anything goes");
+    });
+
+    it("lets only the mint authority set the policy", async () => {
+      await expectError(setMemoPolicy({ anyUtf8: {} }, Keypair.generate()), "InvalidAuthority");
+    });
+
+    it("accepts any utf-8 memo under AnyUtf8", async () => {
+      await setMemoPolicy({ anyUtf8: {} });
+      await transferWithMemo("déposit ✓");
+    });
+
+    it("enforces digits and length under Numeric", async () => {
+      await expectError(setMemoPolicy({ numeric: { minLen: 8, maxLen: 3 } }), "InvalidMemo");
+      await setMemoPolicy({ numeric: { minLen: 3, maxLen: 8 } });
+
+      await transferWithMemo("123456");
+      await expectError(transferWithMemo("12a45"), "InvalidMemo");
+      await expectError(transferWithMemo("12"), "InvalidMemo");
+      await expectError(transferWithMemo("123456789"), "InvalidMemo");
+    });
+
+    it("enforces the prefix under Prefix", async () => {
+      await setMemoPolicy({ prefix: { prefix: "DEP-" } });
+
+      await transferWithMemo("DEP-00042");
+      await expectError(transferWithMemo("WD-00042"), "InvalidMemo");
+    });
+  });
+
+  describe("reallocate_and_enable_memo", () => {
+    const owner = Keypair.generate();
+    let tokenAccount: PublicKey;
+
+    function reallocateAndEnable(signer: Keypair = owner) {
+      return program.methods
+        .reallocateAndEnableMemo()
+        .accountsPartial({
+          payer: payer.publicKey,
+          tokenAccount,
+          owner: signer.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([signer])
+        .rpc();
+    }
+
+    before(async () => {
+      const mint = await createMint(connection, payer, payer.publicKey, null, 6, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+      tokenAccount = await createAssociatedTokenAccount(
+        connection, payer, mint, owner.publicKey, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+    });
+
+    it("rejects a signer that does not own the account", async () => {
+      await expectError(reallocateAndEnable(Keypair.generate()), "owner does not match");
+      expect(await memoRequired(tokenAccount)).to.equal(false);
+    });
+
+    it("grows an ATA created without the extension and enables required memos", async () => {
+      const sizeBefore = (await connection.getAccountInfo(tokenAccount))!.data.length;
+
+      await reallocateAndEnable();
+
+      expect((await connection.getAccountInfo(tokenAccount))!.data.length).to.be.greaterThan(sizeBefore);
+      expect(await memoRequired(tokenAccount)).to.equal(true);
+    });
+
+    it("is idempotent once the extension space exists", async () => {
+      const sizeBefore = (await connection.getAccountInfo(tokenAccount))!.data.length;
+
+      await reallocateAndEnable();
+
+      expect((await connection.getAccountInfo(tokenAccount))!.data.length).to.equal(sizeBefore);
+      expect(await memoRequired(tokenAccount)).to.equal(true);
+    });
+  });
+});
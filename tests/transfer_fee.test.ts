@@ -0,0 +1,742 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  SYSVAR_RENT_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  enableRequiredMemoTransfers,
+  freezeAccount,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMint,
+  getPermanentDelegate,
+  getTransferFeeAmount,
+  getTransferFeeConfig,
+  getTransferHook,
+  mintTo,
+  reallocate,
+  transferChecked,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { TestCpiCaller } from "../target/types/test_cpi_caller";
+import {
+  MEMO_PROGRAM_ID,
+  connection,
+  eventsFrom,
+  expectError,
+  memoInstruction,
+  payer,
+  pda,
+  program,
+  provider,
+} from "./helpers";
+
+describe("Transfer Fee", () => {
+  async function createFeeMint(
+    basisPoints: number,
+    maximumFee: number,
+    usePdaWithdrawAuthority = false,
+  ): Promise<PublicKey> {
+    const mint = Keypair.generate();
+
+    await program.methods
+      .createMintWithTransferFee(
+        payer.publicKey,
+        payer.publicKey,
+        basisPoints,
+        new anchor.BN(maximumFee),
+        6,
+        usePdaWithdrawAuthority,
+        false,
+      )
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        registry: null,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+
+    return mint.publicKey;
+  }
+
+  async function fundedAccount(mint: PublicKey, owner: PublicKey, amount: number): Promise<PublicKey> {
+    const tokenAccount = await createAssociatedTokenAccount(
+      connection, payer, mint, owner, { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+    );
+    if (amount > 0) {
+      await mintTo(connection, payer, mint, tokenAccount, payer, amount, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+    }
+    return tokenAccount;
+  }
+
+  function transferWithFee(
+    mint: PublicKey,
+    source: PublicKey,
+    destination: PublicKey,
+    amount: number,
+    authority: Keypair = payer,
+    maxFeeTolerance: number | null = null,
+  ) {
+    return program.methods
+      .transferWithFee(new anchor.BN(amount), maxFeeTolerance === null ? null : new anchor.BN(maxFeeTolerance))
+      .accountsPartial({
+        source,
+        destination,
+        mint,
+        authority: authority.publicKey,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        memoProgram: MEMO_PROGRAM_ID,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers(authority === payer ? [] : [authority]);
+  }
+
+  describe("create_mint_with_fee_and_hook", () => {
+    it("initializes both the transfer fee and the transfer hook extensions", async () => {
+      const mint = Keypair.generate();
+      const feeAuthority = Keypair.generate().publicKey;
+      const withdrawAuthority = Keypair.generate().publicKey;
+      const hookAuthority = Keypair.generate().publicKey;
+      const hookProgramId = Keypair.generate().publicKey;
+
+      await program.methods
+        .createMintWithFeeAndHook(
+          feeAuthority,
+          withdrawAuthority,
+          150,
+          new anchor.BN(5_000_000),
+          hookAuthority,
+          hookProgramId,
+          6,
+        )
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+
+      const mintInfo = await getMint(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(mintInfo.decimals).to.equal(6);
+
+      const feeConfig = getTransferFeeConfig(mintInfo);
+      expect(feeConfig).to.not.equal(null);
+      expect(feeConfig!.transferFeeConfigAuthority.toBase58()).to.equal(feeAuthority.toBase58());
+      expect(feeConfig!.withdrawWithheldAuthority.toBase58()).to.equal(withdrawAuthority.toBase58());
+      expect(feeConfig!.newerTransferFee.transferFeeBasisPoints).to.equal(150);
+      expect(feeConfig!.newerTransferFee.maximumFee).to.equal(BigInt(5_000_000));
+
+      const transferHook = getTransferHook(mintInfo);
+      expect(transferHook).to.not.equal(null);
+      expect(transferHook!.authority.toBase58()).to.equal(hookAuthority.toBase58());
+      expect(transferHook!.programId.toBase58()).to.equal(hookProgramId.toBase58());
+      console.log(`   Fee + Hook Mint: ${mint.publicKey.toBase58()}`);
+    });
+  });
+
+  describe("transfer_with_fee event", () => {
+    it("emits amount, fee and net matching the withheld delta", async () => {
+      const mint = await createFeeMint(100, 1_000_000);
+      const recipient = Keypair.generate().publicKey;
+      const source = await fundedAccount(mint, payer.publicKey, 1_000_000);
+      const destination = await fundedAccount(mint, recipient, 0);
+
+      const signature = await transferWithFee(mint, source, destination, 100_000).rpc({ commitment: "confirmed" });
+
+      const event = (await eventsFrom(signature)).find((e) => e.name === "transferWithFeeEvent");
+      expect(event).to.not.equal(undefined);
+      expect(event!.data.mint.toBase58()).to.equal(mint.toBase58());
+      expect(event!.data.source.toBase58()).to.equal(source.toBase58());
+      expect(event!.data.destination.toBase58()).to.equal(destination.toBase58());
+      expect(event!.data.amount.toNumber()).to.equal(100_000);
+      expect(event!.data.fee.toNumber()).to.equal(1_000);
+      expect(event!.data.net.toNumber()).to.equal(99_000);
+      expect(event!.data.epoch.toNumber()).to.equal((await connection.getEpochInfo("confirmed")).epoch);
+
+      const destinationAccount = await getAccount(connection, destination, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(getTransferFeeAmount(destinationAccount)!.withheldAmount).to.equal(BigInt(event!.data.fee.toNumber()));
+      expect(destinationAccount.amount).to.equal(BigInt(event!.data.net.toNumber()));
+    });
+  });
+
+  describe("rotate_transfer_fee_authority", () => {
+    let mint: PublicKey;
+
+    function rotate(
+      authorityType: object,
+      newAuthority: PublicKey | null,
+      confirmRenounce: boolean,
+      currentAuthority: Keypair = payer,
+    ) {
+      return program.methods
+        .rotateTransferFeeAuthority(authorityType as any, newAuthority, confirmRenounce)
+        .accountsPartial({
+          mint,
+          currentAuthority: currentAuthority.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers(currentAuthority === payer ? [] : [currentAuthority])
+        .rpc();
+    }
+
+    async function feeConfig() {
+      const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      return getTransferFeeConfig(mintInfo)!;
+    }
+
+    before(async () => {
+      mint = await createFeeMint(50, 1_000_000);
+    });
+
+    it("rotates the transfer fee config authority", async () => {
+      const dao = Keypair.generate();
+
+      await rotate({ transferFeeConfig: {} }, dao.publicKey, false);
+
+      expect((await feeConfig()).transferFeeConfigAuthority.toBase58()).to.equal(dao.publicKey.toBase58());
+    });
+
+    it("rejects a signer that is not the current authority", async () => {
+      const stranger = Keypair.generate();
+
+      await expectError(rotate({ withheldWithdraw: {} }, stranger.publicKey, false, stranger), "InvalidAuthority");
+
+      expect((await feeConfig()).withdrawWithheldAuthority.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("refuses to renounce the withdraw authority without confirmation", async () => {
+      await expectError(rotate({ withheldWithdraw: {} }, null, false), "AuthorityRevocationNotConfirmed");
+
+      expect((await feeConfig()).withdrawWithheldAuthority.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("renounces the withdraw authority once confirmed", async () => {
+      await rotate({ withheldWithdraw: {} }, null, true);
+
+      expect((await feeConfig()).withdrawWithheldAuthority.equals(PublicKey.default)).to.equal(true);
+    });
+  });
+
+  describe("transfer_with_fee_to_owner", () => {
+    let mint: PublicKey;
+    let source: PublicKey;
+
+    function transferToOwner(destinationOwner: PublicKey, amount: number) {
+      return program.methods
+        .transferWithFeeToOwner(new anchor.BN(amount), null)
+        .accountsPartial({
+          payer: payer.publicKey,
+          source,
+          destination: getAssociatedTokenAddressSync(mint, destinationOwner, false, TOKEN_2022_PROGRAM_ID),
+          destinationOwner,
+          mint,
+          authority: payer.publicKey,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          memoProgram: MEMO_PROGRAM_ID,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      mint = await createFeeMint(100, 1_000_000);
+      source = await fundedAccount(mint, payer.publicKey, 1_000_000);
+    });
+
+    it("creates the destination ATA when it is missing", async () => {
+      const owner = Keypair.generate().publicKey;
+      const destination = getAssociatedTokenAddressSync(mint, owner, false, TOKEN_2022_PROGRAM_ID);
+      expect(await connection.getAccountInfo(destination)).to.equal(null);
+
+      await transferToOwner(owner, 10_000);
+
+      const destinationAccount = await getAccount(connection, destination, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(destinationAccount.owner.toBase58()).to.equal(owner.toBase58());
+      expect(destinationAccount.amount).to.equal(BigInt(9_900));
+      expect(getTransferFeeAmount(destinationAccount)!.withheldAmount).to.equal(BigInt(100));
+    });
+
+    it("reuses an existing destination ATA", async () => {
+      const owner = Keypair.generate().publicKey;
+      const destination = await fundedAccount(mint, owner, 0);
+
+      await transferToOwner(owner, 10_000);
+      await transferToOwner(owner, 10_000);
+
+      const destinationAccount = await getAccount(connection, destination, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(destinationAccount.amount).to.equal(BigInt(19_800));
+      expect(getTransferFeeAmount(destinationAccount)!.withheldAmount).to.equal(BigInt(200));
+    });
+  });
+
+  describe("transfer_with_fee from a PDA authority", () => {
+    const caller = anchor.workspace.TestCpiCaller as Program<TestCpiCaller>;
+    const vaultAuthority = PublicKey.findProgramAddressSync([Buffer.from("vault")], caller.programId)[0];
+    let mint: PublicKey;
+    let vault: PublicKey;
+    let destination: PublicKey;
+
+    function routeVaultTransfer(amount: number) {
+      return caller.methods
+        .routeVaultFeeTransfer(new anchor.BN(amount))
+        .accountsPartial({
+          vaultTokenAccount: vault,
+          vaultAuthority,
+          destination,
+          mint,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          memoProgram: MEMO_PROGRAM_ID,
+          tokenExtensionsProgram: program.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      mint = await createFeeMint(100, 1_000_000);
+      vault = await fundedAccount(mint, vaultAuthority, 100_000);
+      destination = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+      console.log(`   Vault Authority (PDA): ${vaultAuthority.toBase58()}`);
+    });
+
+    it("moves PDA-held tokens when the caller program signs for the PDA", async () => {
+      await routeVaultTransfer(50_000);
+
+      const vaultAccount = await getAccount(connection, vault, "confirmed", TOKEN_2022_PROGRAM_ID);
+      const destinationAccount = await getAccount(connection, destination, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(vaultAccount.amount).to.equal(BigInt(50_000));
+      expect(destinationAccount.amount).to.equal(BigInt(49_500));
+      expect(getTransferFeeAmount(destinationAccount)!.withheldAmount).to.equal(BigInt(500));
+    });
+
+    it("rejects the PDA authority when nobody signs for it", async () => {
+      await expectError(
+        program.methods
+          .transferWithFee(new anchor.BN(10_000), null)
+          .accountsPartial({
+            source: vault,
+            destination,
+            mint,
+            authority: vaultAuthority,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            memoProgram: MEMO_PROGRAM_ID,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .rpc(),
+        "InvalidAuthority",
+      );
+    });
+  });
+
+  describe("create_compliance_mint", () => {
+    const delegate = Keypair.generate();
+    const freezeAuthority = Keypair.generate().publicKey;
+    const mint = Keypair.generate();
+    const registry = pda([Buffer.from("registry"), mint.publicKey.toBuffer()]);
+
+    before(async () => {
+      await program.methods
+        .createComplianceMint(100, new anchor.BN(1_000_000), delegate.publicKey, freezeAuthority, 6)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          registry,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+      console.log(`   Compliance Mint: ${mint.publicKey.toBase58()}`);
+    });
+
+    it("initializes the fee, the permanent delegate and the freeze authority", async () => {
+      const mintInfo = await getMint(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(mintInfo.freezeAuthority!.toBase58()).to.equal(freezeAuthority.toBase58());
+      expect(getTransferFeeConfig(mintInfo)!.newerTransferFee.transferFeeBasisPoints).to.equal(100);
+      expect(getPermanentDelegate(mintInfo)!.delegate.toBase58()).to.equal(delegate.publicKey.toBase58());
+    });
+
+    it("records both extension types in the registry", async () => {
+      const entry = await program.account.tokenExtensionMint.fetch(registry);
+      expect(entry.mint.toBase58()).to.equal(mint.publicKey.toBase58());
+      expect(entry.authority.toBase58()).to.equal(payer.publicKey.toBase58());
+      expect(Array.from(entry.extensionTypes)).to.deep.equal([
+        ExtensionType.TransferFeeConfig,
+        ExtensionType.PermanentDelegate,
+      ]);
+    });
+
+    it("charges the fee on transfers and lets the delegate move any holder's tokens", async () => {
+      const holder = Keypair.generate().publicKey;
+      const source = await fundedAccount(mint.publicKey, payer.publicKey, 100_000);
+      const holderAccount = await fundedAccount(mint.publicKey, holder, 0);
+
+      await transferWithFee(mint.publicKey, source, holderAccount, 50_000).rpc();
+
+      let account = await getAccount(connection, holderAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(account.amount).to.equal(BigInt(49_500));
+      expect(getTransferFeeAmount(account)!.withheldAmount).to.equal(BigInt(500));
+
+      // the holder never signs, the permanent delegate claws the balance back
+      await transferChecked(
+        connection, payer, holderAccount, mint.publicKey, source, delegate, 49_500, 6, [],
+        { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+
+      account = await getAccount(connection, holderAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(account.amount).to.equal(BigInt(0));
+    });
+  });
+
+  describe("fee escrow", () => {
+    let mint: PublicKey;
+    let holderAccount: PublicKey;
+    let escrow: PublicKey;
+    let feeEscrow: PublicKey;
+    let withdrawAuthority: PublicKey;
+
+    function withdrawToEscrow(destination: PublicKey) {
+      return program.methods
+        .withdrawWithheldToEscrow()
+        .accountsPartial({
+          mint,
+          destination,
+          feeEscrow,
+          withdrawAuthority,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts([{ pubkey: holderAccount, isSigner: false, isWritable: true }])
+        .rpc();
+    }
+
+    before(async () => {
+      mint = await createFeeMint(100, 1_000_000, true);
+      feeEscrow = pda([Buffer.from("fee_escrow"), mint.toBuffer()]);
+      withdrawAuthority = pda([Buffer.from("fee_withdraw_authority"), mint.toBuffer()]);
+
+      const source = await fundedAccount(mint, payer.publicKey, 100_000);
+      holderAccount = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+      escrow = source;
+      await transferWithFee(mint, source, holderAccount, 50_000).rpc();
+    });
+
+    it("sets the program PDA as withdraw withheld authority", async () => {
+      const mintInfo = await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(getTransferFeeConfig(mintInfo)!.withdrawWithheldAuthority.toBase58()).to.equal(withdrawAuthority.toBase58());
+    });
+
+    it("rejects an escrow configured by someone other than the fee config authority", async () => {
+      const stranger = Keypair.generate();
+
+      await expectError(
+        program.methods
+          .configureFeeEscrow()
+          .accountsPartial({
+            payer: payer.publicKey,
+            mint,
+            authority: stranger.publicKey,
+            escrow,
+            feeEscrow,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([stranger])
+          .rpc(),
+        "InvalidAuthority",
+      );
+    });
+
+    it("configures the escrow token account", async () => {
+      await program.methods
+        .configureFeeEscrow()
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint,
+          authority: payer.publicKey,
+          escrow,
+          feeEscrow,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const config = await program.account.feeEscrow.fetch(feeEscrow);
+      expect(config.escrow.toBase58()).to.equal(escrow.toBase58());
+    });
+
+    it("refuses to withdraw to any other destination", async () => {
+      const other = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+
+      await expectError(withdrawToEscrow(other), "InvalidFeeEscrow");
+
+      const holder = await getAccount(connection, holderAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(getTransferFeeAmount(holder)!.withheldAmount).to.equal(BigInt(500));
+    });
+
+    it("withdraws withheld fees into the configured escrow", async () => {
+      const before = (await getAccount(connection, escrow, "confirmed", TOKEN_2022_PROGRAM_ID)).amount;
+
+      await withdrawToEscrow(escrow);
+
+      const after = (await getAccount(connection, escrow, "confirmed", TOKEN_2022_PROGRAM_ID)).amount;
+      expect(after - before).to.equal(BigInt(500));
+      const holder = await getAccount(connection, holderAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(getTransferFeeAmount(holder)!.withheldAmount).to.equal(BigInt(0));
+    });
+  });
+
+  describe("pending fee changes", () => {
+    let mint: PublicKey;
+    let currentEpoch: number;
+
+    before(async () => {
+      mint = await createFeeMint(100, 1_000_000);
+      currentEpoch = (await connection.getEpochInfo("confirmed")).epoch;
+
+      await program.methods
+        .setTransferFee(300, new anchor.BN(2_000_000))
+        .accountsPartial({
+          mint,
+          transferFeeConfigAuthority: payer.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+    });
+
+    it("returns both the effective and the scheduled fee", async () => {
+      const schedule = await program.methods.getFeeSchedule().accountsPartial({ mint }).view();
+
+      expect(schedule.olderTransferFeeBasisPoints).to.equal(100);
+      expect(schedule.olderMaximumFee.toNumber()).to.equal(1_000_000);
+      expect(schedule.newerTransferFeeBasisPoints).to.equal(300);
+      expect(schedule.newerMaximumFee.toNumber()).to.equal(2_000_000);
+      expect(schedule.newerEpoch.toNumber()).to.be.greaterThan(currentEpoch);
+    });
+
+    it("keeps quoting the older fee until the newer one takes effect", async () => {
+      const fee = await program.methods.getTransferFee(new anchor.BN(100_000)).accountsPartial({ mint }).view();
+      expect(fee.toNumber()).to.equal(1_000);
+    });
+
+    it("flags the pending change on the transfer event", async () => {
+      const source = await fundedAccount(mint, payer.publicKey, 100_000);
+      const destination = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+
+      const signature = await transferWithFee(mint, source, destination, 100_000).rpc({ commitment: "confirmed" });
+
+      const event = (await eventsFrom(signature)).find((e) => e.name === "transferWithFeeEvent");
+      expect(event!.data.fee.toNumber()).to.equal(1_000);
+      expect(event!.data.pendingFeeChange).to.equal(true);
+    });
+  });
+
+  describe("create_mint_with_transfer_fee registry", () => {
+    it("records the transfer fee extension when register is set", async () => {
+      const mint = Keypair.generate();
+      const registry = pda([Buffer.from("registry"), mint.publicKey.toBuffer()]);
+
+      await program.methods
+        .createMintWithTransferFee(payer.publicKey, payer.publicKey, 50, new anchor.BN(1_000), 6, false, true)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          registry,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+
+      const entry = await program.account.tokenExtensionMint.fetch(registry);
+      expect(entry.mint.toBase58()).to.equal(mint.publicKey.toBase58());
+      expect(Array.from(entry.extensionTypes)).to.deep.equal([ExtensionType.TransferFeeConfig]);
+    });
+
+    it("rejects a registry account passed without register", async () => {
+      const mint = Keypair.generate();
+      const registry = pda([Buffer.from("registry"), mint.publicKey.toBuffer()]);
+
+      await expectError(
+        program.methods
+          .createMintWithTransferFee(payer.publicKey, payer.publicKey, 50, new anchor.BN(1_000), 6, false, false)
+          .accountsPartial({
+            payer: payer.publicKey,
+            mint: mint.publicKey,
+            mintAuthority: payer.publicKey,
+            registry,
+            rent: SYSVAR_RENT_PUBKEY,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([mint])
+          .rpc(),
+        "UnexpectedRegistryAccount",
+      );
+    });
+  });
+
+  describe("required memo on fee transfers", () => {
+    const recipient = Keypair.generate();
+    let mint: PublicKey;
+    let source: PublicKey;
+    let memoDestination: PublicKey;
+
+    before(async () => {
+      mint = await createFeeMint(100, 1_000_000);
+      source = await fundedAccount(mint, payer.publicKey, 1_000_000);
+
+      // an existing ATA grown to fit the memo extension, as a wallet would do
+      memoDestination = await fundedAccount(mint, recipient.publicKey, 0);
+      await reallocate(
+        connection, payer, memoDestination, recipient, [ExtensionType.MemoTransfer],
+        [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+      await enableRequiredMemoTransfers(
+        connection, payer, memoDestination, recipient, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID,
+      );
+    });
+
+    async function balanceOf(tokenAccount: PublicKey): Promise<bigint> {
+      return (await getAccount(connection, tokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount;
+    }
+
+    it("forwards the memo that immediately precedes the transfer", async () => {
+      const transaction = new Transaction().add(
+        memoInstruction("invoice-7"),
+        await transferWithFee(mint, source, memoDestination, 10_000).instruction(),
+      );
+      await provider.sendAndConfirm(transaction);
+
+      expect(await balanceOf(memoDestination)).to.equal(BigInt(9_900));
+    });
+
+    it("fails fast with MemoRequiredForTransfer when no memo is present", async () => {
+      await expectError(transferWithFee(mint, source, memoDestination, 10_000).rpc(), "MemoRequiredForTransfer");
+    });
+
+    it("ignores a memo that is not the immediately preceding instruction", async () => {
+      const transaction = new Transaction().add(
+        memoInstruction("invoice-8"),
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+        await transferWithFee(mint, source, memoDestination, 10_000).instruction(),
+      );
+      await expectError(provider.sendAndConfirm(transaction), "MemoRequiredForTransfer");
+    });
+
+    it("needs no memo for a destination without the extension", async () => {
+      const plainDestination = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+
+      await transferWithFee(mint, source, plainDestination, 10_000).rpc();
+
+      expect(await balanceOf(plainDestination)).to.equal(BigInt(9_900));
+    });
+
+    it("applies the same memo rule in transfer_with_fee_to_owner", async () => {
+      const toOwner = () =>
+        program.methods
+          .transferWithFeeToOwner(new anchor.BN(10_000), null)
+          .accountsPartial({
+            payer: payer.publicKey,
+            source,
+            destination: memoDestination,
+            destinationOwner: recipient.publicKey,
+            mint,
+            authority: payer.publicKey,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            memoProgram: MEMO_PROGRAM_ID,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          });
+
+      await expectError(toOwner().rpc(), "MemoRequiredForTransfer");
+
+      const before = await balanceOf(memoDestination);
+      await provider.sendAndConfirm(new Transaction().add(memoInstruction("invoice-9"), await toOwner().instruction()));
+      expect((await balanceOf(memoDestination)) - before).to.equal(BigInt(9_900));
+    });
+  });
+
+  describe("transfer_with_fee pre-flight checks", () => {
+    async function createFreezableFeeMint(): Promise<PublicKey> {
+      const mint = Keypair.generate();
+      await program.methods
+        .createComplianceMint(100, new anchor.BN(1_000_000), payer.publicKey, payer.publicKey, 6)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          registry: pda([Buffer.from("registry"), mint.publicKey.toBuffer()]),
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+      return mint.publicKey;
+    }
+
+    function freeze(mint: PublicKey, tokenAccount: PublicKey) {
+      return freezeAccount(connection, payer, tokenAccount, mint, payer, [], { commitment: "confirmed" }, TOKEN_2022_PROGRAM_ID);
+    }
+
+    it("rejects a destination of another mint with MintMismatch", async () => {
+      const mint = await createFeeMint(100, 1_000_000);
+      const otherMint = await createFeeMint(100, 1_000_000);
+      const source = await fundedAccount(mint, payer.publicKey, 100_000);
+      const destination = await fundedAccount(otherMint, Keypair.generate().publicKey, 0);
+
+      await expectError(transferWithFee(mint, source, destination, 10_000).rpc(), "MintMismatch");
+    });
+
+    it("rejects a frozen destination with AccountFrozen", async () => {
+      const mint = await createFreezableFeeMint();
+      const source = await fundedAccount(mint, payer.publicKey, 100_000);
+      const destination = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+      await freeze(mint, destination);
+
+      await expectError(transferWithFee(mint, source, destination, 10_000).rpc(), "AccountFrozen");
+    });
+
+    it("rejects a frozen source with AccountFrozen", async () => {
+      const mint = await createFreezableFeeMint();
+      const source = await fundedAccount(mint, payer.publicKey, 100_000);
+      const destination = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+      await freeze(mint, source);
+
+      await expectError(transferWithFee(mint, source, destination, 10_000).rpc(), "AccountFrozen");
+    });
+
+    it("rejects an amount above the source balance with InsufficientFunds", async () => {
+      const mint = await createFeeMint(100, 1_000_000);
+      const source = await fundedAccount(mint, payer.publicKey, 10_000);
+      const destination = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+
+      await expectError(transferWithFee(mint, source, destination, 10_001).rpc(), "InsufficientFunds");
+      expect((await getAccount(connection, source, "confirmed", TOKEN_2022_PROGRAM_ID)).amount).to.equal(BigInt(10_000));
+    });
+  });
+});
@@ -0,0 +1,536 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY, Transaction } from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createMint,
+  getAccount,
+  getExtraAccountMetaAddress,
+  getExtraAccountMetas,
+  getMint,
+  getTokenMetadata,
+  getTransferHook,
+  mintTo,
+  transferCheckedWithTransferHook,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { TestTransferHook } from "../target/types/test_transfer_hook";
+import { connection, eventsFrom, expectError, fundedKeypair, payer, pda, program, provider } from "./helpers";
+
+describe("Transfer Hook", () => {
+  // a mint whose transfer hook is this program, so the meta list is our own PDA
+  async function createSelfHookMint(): Promise<PublicKey> {
+    const mint = Keypair.generate();
+
+    await program.methods
+      .createMintWithTransferHook(payer.publicKey, program.programId, 6, true)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint: mint.publicKey,
+        mintAuthority: payer.publicKey,
+        hookProgram: program.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+
+    return mint.publicKey;
+  }
+
+  function initializeMetaList(mint: PublicKey, metas: object[], hookProgram: PublicKey = program.programId, mintAuthority: Keypair = payer) {
+    return program.methods
+      .initializeExtraAccountMetaList(metas as any)
+      .accountsPartial({
+        payer: payer.publicKey,
+        mint,
+        mintAuthority: mintAuthority.publicKey,
+        extraAccountMetaList: getExtraAccountMetaAddress(mint, hookProgram),
+        hookProgram,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers(mintAuthority === payer ? [] : [mintAuthority])
+      .rpc();
+  }
+
+  describe("initialize_extra_account_meta_list", () => {
+    const literal = Keypair.generate().publicKey;
+    const metas = [
+      { literal: { pubkey: literal, isSigner: false, isWritable: false } },
+      {
+        pda: {
+          seeds: [{ literal: { bytes: Buffer.from("counter") } }, { accountKey: { index: 1 } }],
+          isSigner: false,
+          isWritable: true,
+        },
+      },
+    ];
+
+    it("round-trips a literal and a PDA meta", async () => {
+      const mint = await createSelfHookMint();
+
+      await initializeMetaList(mint, metas);
+
+      const listAddress = getExtraAccountMetaAddress(mint, program.programId);
+      const listAccount = (await connection.getAccountInfo(listAddress))!;
+      expect(listAccount.owner.toBase58()).to.equal(program.programId.toBase58());
+
+      const [literalMeta, pdaMeta] = getExtraAccountMetas(listAccount);
+      expect(literalMeta.discriminator).to.equal(0);
+      expect(new PublicKey(literalMeta.addressConfig).toBase58()).to.equal(literal.toBase58());
+      expect(Boolean(literalMeta.isSigner)).to.equal(false);
+      expect(Boolean(literalMeta.isWritable)).to.equal(false);
+
+      // packed seeds: literal is [1, len, bytes], account key is [3, index], zero padded
+      const packedSeeds = Buffer.alloc(32);
+      Buffer.from([1, 7, ...Buffer.from("counter"), 3, 1]).copy(packedSeeds);
+      expect(pdaMeta.discriminator).to.equal(1);
+      expect(Buffer.from(pdaMeta.addressConfig).equals(packedSeeds)).to.equal(true);
+      expect(Boolean(pdaMeta.isWritable)).to.equal(true);
+    });
+
+    it("rejects a signer that is not the mint authority", async () => {
+      const mint = await createSelfHookMint();
+
+      await expectError(initializeMetaList(mint, metas, program.programId, Keypair.generate()), "InvalidAuthority");
+    });
+
+    it("rejects more than MAX_EXTRA_ACCOUNT_METAS metas", async () => {
+      const mint = await createSelfHookMint();
+      const tooMany = Array.from({ length: 11 }, () => ({
+        literal: { pubkey: Keypair.generate().publicKey, isSigner: false, isWritable: false },
+      }));
+
+      await expectError(initializeMetaList(mint, tooMany), "InvalidExtraAccountMeta");
+    });
+
+    it("rejects a mint without a hook program", async () => {
+      const mint = await createMint(connection, payer, payer.publicKey, null, 6, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+
+      await expectError(initializeMetaList(mint, metas), "TransferHookProgramNotFound");
+    });
+  });
+
+  describe("transfer_checked_with_hook", () => {
+    const hook = anchor.workspace.TestTransferHook as Program<TestTransferHook>;
+
+    function counterFor(mint: PublicKey): PublicKey {
+      return pda([Buffer.from("counter"), mint.toBuffer()], hook.programId);
+    }
+
+    // a mint hooked to the workspace hook program, which counts transfers in a PDA it resolves from its meta list
+    async function createForeignHookMint(): Promise<PublicKey> {
+      const mint = Keypair.generate();
+
+      await program.methods
+        .createMintWithTransferHook(payer.publicKey, hook.programId, 6, false)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          hookProgram: hook.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+
+      await hook.methods
+        .initializeCounter()
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          counter: counterFor(mint.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // the interface instruction has no payer, so the hook expects its list PDA pre-funded
+      const listAddress = getExtraAccountMetaAddress(mint.publicKey, hook.programId);
+      const listRent = await connection.getMinimumBalanceForRentExemption(16 + 35);
+      await provider.sendAndConfirm(
+        new Transaction().add(SystemProgram.transfer({ fromPubkey: payer.publicKey, toPubkey: listAddress, lamports: listRent })),
+      );
+
+      await initializeMetaList(
+        mint.publicKey,
+        [
+          {
+            pda: {
+              seeds: [{ literal: { bytes: Buffer.from("counter") } }, { accountKey: { index: 1 } }],
+              isSigner: false,
+              isWritable: true,
+            },
+          },
+        ],
+        hook.programId,
+      );
+
+      return mint.publicKey;
+    }
+
+    async function fundedAccount(mint: PublicKey, owner: PublicKey, amount: number): Promise<PublicKey> {
+      const tokenAccount = await createAssociatedTokenAccount(connection, payer, mint, owner, undefined, TOKEN_2022_PROGRAM_ID);
+      if (amount > 0) {
+        await mintTo(connection, payer, mint, tokenAccount, payer, amount, [], undefined, TOKEN_2022_PROGRAM_ID);
+      }
+      return tokenAccount;
+    }
+
+    function transferWithHook(mint: PublicKey, source: PublicKey, destination: PublicKey, amount: number, remainingAccounts: PublicKey[]) {
+      return program.methods
+        .transferCheckedWithHook(new anchor.BN(amount))
+        .accountsPartial({
+          source,
+          mint,
+          destination,
+          authority: payer.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts(
+          remainingAccounts.map((pubkey) => ({ pubkey, isSigner: false, isWritable: pubkey.equals(counterFor(mint)) })),
+        )
+        .rpc();
+    }
+
+    it("runs the workspace hook with the accounts resolved from its meta list", async () => {
+      const mint = await createForeignHookMint();
+      const source = await fundedAccount(mint, payer.publicKey, 1_000_000);
+      const destination = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+      const extras = [hook.programId, getExtraAccountMetaAddress(mint, hook.programId), counterFor(mint)];
+
+      await transferWithHook(mint, source, destination, 250_000, extras);
+      await transferWithHook(mint, source, destination, 100_000, extras);
+
+      expect(Number((await getAccount(connection, destination, "confirmed", TOKEN_2022_PROGRAM_ID)).amount)).to.equal(350_000);
+      const counter = await hook.account.counter.fetch(counterFor(mint));
+      expect(counter.count.toNumber()).to.equal(2);
+      expect(counter.volume.toNumber()).to.equal(350_000);
+    });
+
+    it("fails when the hook accounts are not passed", async () => {
+      const mint = await createForeignHookMint();
+      const source = await fundedAccount(mint, payer.publicKey, 1_000_000);
+      const destination = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+
+      let rejected = false;
+      try {
+        await transferWithHook(mint, source, destination, 250_000, []);
+      } catch {
+        rejected = true;
+      }
+      expect(rejected).to.equal(true);
+      expect((await hook.account.counter.fetch(counterFor(mint))).count.toNumber()).to.equal(0);
+    });
+
+    it("degrades to a plain transfer_checked for a mint without a hook", async () => {
+      const mint = await createMint(connection, payer, payer.publicKey, null, 6, undefined, undefined, TOKEN_2022_PROGRAM_ID);
+      const source = await fundedAccount(mint, payer.publicKey, 1_000_000);
+      const destination = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+
+      await transferWithHook(mint, source, destination, 400_000, []);
+
+      expect(Number((await getAccount(connection, destination, "confirmed", TOKEN_2022_PROGRAM_ID)).amount)).to.equal(400_000);
+    });
+  });
+
+  describe("hook program validation", () => {
+    const hook = anchor.workspace.TestTransferHook as Program<TestTransferHook>;
+
+    function createHookMint(mint: Keypair, programId: PublicKey | null, hookProgram: PublicKey | null, selfHook = false) {
+      return program.methods
+        .createMintWithTransferHook(payer.publicKey, programId, 6, selfHook)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          hookProgram,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+    }
+
+    function updateHookProgram(mint: PublicKey, programId: PublicKey | null, hookProgram: PublicKey | null) {
+      return program.methods
+        .updateTransferHookProgram(programId, false)
+        .accountsPartial({
+          mint,
+          authority: payer.publicKey,
+          hookProgram,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+    }
+
+    it("rejects a system-owned account as the hook", async () => {
+      const notAProgram = (await fundedKeypair()).publicKey;
+
+      await expectError(createHookMint(Keypair.generate(), notAProgram, notAProgram), "TransferHookProgramNotFound");
+    });
+
+    it("rejects the token program as the hook", async () => {
+      await expectError(
+        createHookMint(Keypair.generate(), TOKEN_2022_PROGRAM_ID, TOKEN_2022_PROGRAM_ID),
+        "TransferHookProgramNotFound",
+      );
+    });
+
+    it("rejects this program as the hook unless self_hook is set", async () => {
+      await expectError(
+        createHookMint(Keypair.generate(), program.programId, program.programId),
+        "TransferHookProgramNotFound",
+      );
+    });
+
+    it("rejects a hook id without the hook program account", async () => {
+      await expectError(createHookMint(Keypair.generate(), hook.programId, null), "TransferHookProgramNotFound");
+    });
+
+    it("applies the same checks when updating the hook program", async () => {
+      const mint = Keypair.generate();
+      await createHookMint(mint, hook.programId, hook.programId);
+      const notAProgram = (await fundedKeypair()).publicKey;
+
+      await expectError(updateHookProgram(mint.publicKey, notAProgram, notAProgram), "TransferHookProgramNotFound");
+      await expectError(
+        updateHookProgram(mint.publicKey, TOKEN_2022_PROGRAM_ID, TOKEN_2022_PROGRAM_ID),
+        "TransferHookProgramNotFound",
+      );
+
+      const mintInfo = await getMint(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(getTransferHook(mintInfo)!.programId.toBase58()).to.equal(hook.programId.toBase58());
+    });
+  });
+
+  describe("self-hosted counter hook", () => {
+    function transferStatsFor(mint: PublicKey): PublicKey {
+      return pda([Buffer.from("transfer_stats"), mint.toBuffer()]);
+    }
+
+    async function createCounterMint(): Promise<PublicKey> {
+      const mint = Keypair.generate();
+
+      await program.methods
+        .createMintWithSelfHook(6)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          extraAccountMetaList: getExtraAccountMetaAddress(mint.publicKey, program.programId),
+          transferStats: transferStatsFor(mint.publicKey),
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+
+      return mint.publicKey;
+    }
+
+    async function fundedAccount(mint: PublicKey, owner: PublicKey, amount: number): Promise<PublicKey> {
+      const tokenAccount = await createAssociatedTokenAccount(connection, payer, mint, owner, undefined, TOKEN_2022_PROGRAM_ID);
+      if (amount > 0) {
+        await mintTo(connection, payer, mint, tokenAccount, payer, amount, [], undefined, TOKEN_2022_PROGRAM_ID);
+      }
+      return tokenAccount;
+    }
+
+    it("counts two transfers and their volume", async () => {
+      const mint = await createCounterMint();
+      const source = await fundedAccount(mint, payer.publicKey, 1_000_000);
+      const destination = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+
+      // the client resolves the stats PDA from the meta list written at creation
+      await transferCheckedWithTransferHook(connection, payer, source, mint, destination, payer, BigInt(300_000), 6, [], undefined, TOKEN_2022_PROGRAM_ID);
+      await transferCheckedWithTransferHook(connection, payer, source, mint, destination, payer, BigInt(45_000), 6, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+      const stats = await program.account.transferStats.fetch(transferStatsFor(mint));
+      expect(stats.count.toNumber()).to.equal(2);
+      expect(stats.volume.toNumber()).to.equal(345_000);
+    });
+
+    it("rejects a direct execute call outside a transfer", async () => {
+      const mint = await createCounterMint();
+      const source = await fundedAccount(mint, payer.publicKey, 1_000_000);
+      const destination = await fundedAccount(mint, Keypair.generate().publicKey, 0);
+
+      await expectError(
+        program.methods
+          .transferHookExecute(new anchor.BN(1))
+          .accountsPartial({
+            source,
+            mint,
+            destination,
+            owner: payer.publicKey,
+            extraAccountMetaList: getExtraAccountMetaAddress(mint, program.programId),
+            transferStats: transferStatsFor(mint),
+          })
+          .rpc(),
+        "NotTransferring",
+      );
+      expect((await program.account.transferStats.fetch(transferStatsFor(mint))).count.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("transfer hook authority", () => {
+    const hook = anchor.workspace.TestTransferHook as Program<TestTransferHook>;
+
+    async function createHookMint(): Promise<PublicKey> {
+      const mint = Keypair.generate();
+
+      await program.methods
+        .createMintWithTransferHook(payer.publicKey, hook.programId, 6, false)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          hookProgram: hook.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc();
+
+      return mint.publicKey;
+    }
+
+    function setHookAuthority(mint: PublicKey, newAuthority: PublicKey | null, authority: Keypair = payer) {
+      return program.methods
+        .setTransferHookAuthority(newAuthority)
+        .accountsPartial({ mint, authority: authority.publicKey, tokenProgram: TOKEN_2022_PROGRAM_ID })
+        .signers(authority === payer ? [] : [authority])
+        .rpc();
+    }
+
+    function updateHookProgram(mint: PublicKey, programId: PublicKey | null, authority: Keypair = payer) {
+      return program.methods
+        .updateTransferHookProgram(programId, false)
+        .accountsPartial({
+          mint,
+          authority: authority.publicKey,
+          hookProgram: programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers(authority === payer ? [] : [authority])
+        .rpc({ commitment: "confirmed" });
+    }
+
+    async function hookOf(mint: PublicKey) {
+      return getTransferHook(await getMint(connection, mint, "confirmed", TOKEN_2022_PROGRAM_ID))!;
+    }
+
+    it("lets the new authority update the hook after rotation and stops the old one", async () => {
+      const mint = await createHookMint();
+      const newAuthority = Keypair.generate();
+
+      await setHookAuthority(mint, newAuthority.publicKey);
+      expect((await hookOf(mint)).authority.toBase58()).to.equal(newAuthority.publicKey.toBase58());
+
+      await expectError(updateHookProgram(mint, null), "InvalidAuthority");
+      expect((await hookOf(mint)).programId.toBase58()).to.equal(hook.programId.toBase58());
+
+      const sig = await updateHookProgram(mint, null, newAuthority);
+      expect((await hookOf(mint)).programId.equals(PublicKey.default)).to.equal(true);
+
+      const event = (await eventsFrom(sig)).find((e) => e.name === "hookRemoved");
+      expect(event!.data.mint.toBase58()).to.equal(mint.toBase58());
+      expect(event!.data.authority.toBase58()).to.equal(newAuthority.publicKey.toBase58());
+    });
+
+    it("rejects a rotation signed by someone other than the authority", async () => {
+      const mint = await createHookMint();
+      const stranger = Keypair.generate();
+
+      await expectError(setHookAuthority(mint, stranger.publicKey, stranger), "InvalidAuthority");
+      expect((await hookOf(mint)).authority.toBase58()).to.equal(payer.publicKey.toBase58());
+    });
+
+    it("does not emit HookRemoved when the hook is replaced", async () => {
+      const mint = await createHookMint();
+
+      const sig = await updateHookProgram(mint, hook.programId);
+
+      expect((await eventsFrom(sig)).find((e) => e.name === "hookRemoved")).to.equal(undefined);
+    });
+  });
+
+  describe("create_hooked_nft_mint", () => {
+    const hook = anchor.workspace.TestTransferHook as Program<TestTransferHook>;
+
+    it("keeps the metadata and the reference hook through a mint and transfer cycle", async () => {
+      const mint = Keypair.generate();
+      const registry = pda([Buffer.from("registry"), mint.publicKey.toBuffer()]);
+      const counter = pda([Buffer.from("counter"), mint.publicKey.toBuffer()], hook.programId);
+      const listAddress = getExtraAccountMetaAddress(mint.publicKey, hook.programId);
+
+      // the reference hook expects its list PDA pre-funded
+      const listRent = await connection.getMinimumBalanceForRentExemption(16 + 35);
+      await provider.sendAndConfirm(
+        new Transaction().add(SystemProgram.transfer({ fromPubkey: payer.publicKey, toPubkey: listAddress, lamports: listRent })),
+      );
+
+      const metas = [
+        {
+          pda: {
+            seeds: [{ literal: { bytes: Buffer.from("counter") } }, { accountKey: { index: 1 } }],
+            isSigner: false,
+            isWritable: true,
+          },
+        },
+      ];
+      const sig = await program.methods
+        .createHookedNftMint(hook.programId, "Royalty Piece", "RYL", "https://example.com/royalty.json", metas as any)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mint: mint.publicKey,
+          mintAuthority: payer.publicKey,
+          hookProgram: hook.programId,
+          extraAccountMetaList: listAddress,
+          registry,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([mint])
+        .rpc({ commitment: "confirmed" });
+
+      const extensions = [ExtensionType.TransferHook, ExtensionType.MetadataPointer, ExtensionType.TokenMetadata];
+      const entry = await program.account.tokenExtensionMint.fetch(registry);
+      expect(Array.from(entry.extensionTypes)).to.deep.equal(extensions);
+      const event = (await eventsFrom(sig)).find((e) => e.name === "mintCreated");
+      expect(Array.from(event!.data.extensionTypes)).to.deep.equal(extensions);
+
+      await hook.methods
+        .initializeCounter()
+        .accountsPartial({ payer: payer.publicKey, mint: mint.publicKey, counter, systemProgram: SystemProgram.programId })
+        .rpc();
+
+      const recipient = Keypair.generate();
+      const source = await createAssociatedTokenAccount(connection, payer, mint.publicKey, payer.publicKey, undefined, TOKEN_2022_PROGRAM_ID);
+      const destination = await createAssociatedTokenAccount(connection, payer, mint.publicKey, recipient.publicKey, undefined, TOKEN_2022_PROGRAM_ID);
+      await mintTo(connection, payer, mint.publicKey, source, payer, 1, [], undefined, TOKEN_2022_PROGRAM_ID);
+      await transferCheckedWithTransferHook(connection, payer, source, mint.publicKey, destination, payer, BigInt(1), 0, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+      expect(Number((await getAccount(connection, destination, "confirmed", TOKEN_2022_PROGRAM_ID)).amount)).to.equal(1);
+      expect((await hook.account.counter.fetch(counter)).count.toNumber()).to.equal(1);
+
+      const mintInfo = await getMint(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(mintInfo.decimals).to.equal(0);
+      expect(getTransferHook(mintInfo)!.programId.toBase58()).to.equal(hook.programId.toBase58());
+      const metadata = await getTokenMetadata(connection, mint.publicKey, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(metadata!.name).to.equal("Royalty Piece");
+      expect(metadata!.symbol).to.equal("RYL");
+      expect(metadata!.uri).to.equal("https://example.com/royalty.json");
+    });
+  });
+});